@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::tree::{escape_key, group_digits, unescape_key};
+
+/// Collect the statistics of a field over the array items, like `jq` plus
+/// `sort | uniq -c`.
+pub struct Aggregate<'a> {
+    /// The path of the field in each item, split by `/` and unescaped. Empty means the
+    /// item itself.
+    field: Vec<Cow<'a, str>>,
+
+    /// Group the digits of the numbers in report, see [`group_digits`].
+    separator: &'a str,
+
+    items: usize,
+    present: usize,
+
+    /// The distinct values (encoded as JSON) with their counts, and the order they are
+    /// first seen.
+    distinct: HashMap<String, (usize, usize)>,
+
+    numbers: usize,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+impl<'a> Aggregate<'a> {
+    /// Show at most these distinct values in the report.
+    const MAX_DISTINCT: usize = 20;
+
+    pub fn new(field: &'a str, separator: &'a str) -> Self {
+        Self {
+            field: field
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .map(unescape_key)
+                .collect(),
+            separator,
+            items: 0,
+            present: 0,
+            distinct: HashMap::new(),
+            numbers: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, item: &Value) {
+        self.items += 1;
+        let value = match self.get_field(item) {
+            Some(value) => value,
+            None => return,
+        };
+        self.present += 1;
+
+        let key = serde_json::to_string(value).unwrap_or_default();
+        let order = self.distinct.len();
+        self.distinct.entry(key).or_insert((0, order)).0 += 1;
+
+        if let Some(num) = value.as_f64() {
+            self.numbers += 1;
+            self.min = self.min.min(num);
+            self.max = self.max.max(num);
+            self.sum += num;
+        }
+    }
+
+    fn get_field<'b>(&self, item: &'b Value) -> Option<&'b Value> {
+        let mut value = item;
+        for part in self.field.iter() {
+            value = match value {
+                Value::Object(obj) => obj.get(part.as_ref())?,
+                Value::Array(arr) => arr.get(part.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "items: {}", self.group(self.items));
+        if !self.field.is_empty() {
+            let _ = writeln!(
+                report,
+                "field '{}': present in {} items",
+                self.field
+                    .iter()
+                    .map(|part| escape_key(part))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                self.group(self.present)
+            );
+        }
+
+        let _ = writeln!(
+            report,
+            "distinct values: {}",
+            self.group(self.distinct.len())
+        );
+        let mut distinct: Vec<_> = self.distinct.iter().collect();
+        // The most common values first, the values with the same count keep their
+        // order in the array.
+        distinct.sort_by_key(|(_, (count, order))| (usize::MAX - count, *order));
+        let width = distinct
+            .iter()
+            .take(Self::MAX_DISTINCT)
+            .map(|(value, _)| value.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (value, (count, _)) in distinct.iter().take(Self::MAX_DISTINCT) {
+            let _ = writeln!(report, "  {value:width$}  {}", self.group(*count));
+        }
+        if distinct.len() > Self::MAX_DISTINCT {
+            let more = distinct.len() - Self::MAX_DISTINCT;
+            let _ = writeln!(report, "  ... {} more", self.group(more));
+        }
+
+        if self.numbers > 0 {
+            let avg = self.sum / self.numbers as f64;
+            let _ = writeln!(
+                report,
+                "numbers: {}, min: {}, max: {}, sum: {}, avg: {}",
+                self.group(self.numbers),
+                self.group(self.min),
+                self.group(self.max),
+                self.group(self.sum),
+                self.group(format_args!("{avg:.2}"))
+            );
+        }
+        report
+    }
+
+    fn group(&self, num: impl std::fmt::Display) -> String {
+        group_digits(&num.to_string(), self.separator).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let items = json!([
+            {"level": "info", "cost": 1, "user": {"name": "a"}},
+            {"level": "warn", "cost": 2.5, "user": {"name": "b"}},
+            {"level": "info", "cost": 3},
+            {"level": "info"},
+            "text"
+        ]);
+        let items = items.as_array().unwrap();
+
+        let mut aggregate = Aggregate::new("level", "");
+        items.iter().for_each(|item| aggregate.add(item));
+        let expect = "items: 5\nfield 'level': present in 4 items\ndistinct values: 2\n  \"info\"  3\n  \"warn\"  1\n";
+        assert_eq!(aggregate.report(), expect);
+
+        let mut aggregate = Aggregate::new("cost", "");
+        items.iter().for_each(|item| aggregate.add(item));
+        assert!(aggregate
+            .report()
+            .ends_with("numbers: 3, min: 1, max: 3, sum: 6.5, avg: 2.17\n"));
+
+        let mut aggregate = Aggregate::new("user/name", "");
+        items.iter().for_each(|item| aggregate.add(item));
+        assert!(aggregate
+            .report()
+            .contains("field 'user/name': present in 2 items\n"));
+
+        let mut aggregate = Aggregate::new("", "");
+        items.iter().for_each(|item| aggregate.add(item));
+        assert!(aggregate.report().contains("distinct values: 5\n"));
+
+        let sizes = json!([{"size": 1500}, {"size": 2500.5}]);
+        let mut aggregate = Aggregate::new("size", ",");
+        sizes
+            .as_array()
+            .unwrap()
+            .iter()
+            .for_each(|item| aggregate.add(item));
+        assert!(aggregate
+            .report()
+            .ends_with("min: 1,500, max: 2,500.5, sum: 4,000.5, avg: 2,000.25\n"));
+    }
+}
@@ -0,0 +1,413 @@
+use std::borrow::Cow;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+
+use crate::cmd::{CommandArgs, DumpFormat, PrintSelection};
+use crate::config::Config;
+use crate::hook;
+use crate::parse::{self, ContentType};
+use crate::spinner;
+use crate::state::Notes;
+use crate::tree::Tree;
+use crate::ui::{self, App, HeaderContext};
+
+/// Run the otree command with the command line args, see [`crate::run_cli`].
+pub fn run() -> Result<()> {
+    let args = match CommandArgs::parse()? {
+        Some(args) => args,
+        None => return Ok(()),
+    };
+
+    let mut cfg = if args.ignore_config {
+        Config::default()
+    } else {
+        let dir = get_workspace_dir(args.path.as_deref());
+        Config::load(args.config.clone(), args.profile.as_deref(), dir.as_deref())?
+    };
+
+    args.update_config(&mut cfg);
+    cfg.parse().context("parse config")?;
+
+    if args.show_config {
+        return cfg.show();
+    }
+
+    // The user can specify the content type manually, or we can determine it based on the
+    // file extension. Another approach is to use file content (for example, if the file
+    // content starts with '{', we can assume it is JSON). But this approach is not reliable
+    // since the YAML is the superset of JSON, and the TOML is not easy to determine. So
+    // the content detection is only used as a fallback when `data.auto_detect` is enabled.
+    let content_type = match args.content_type {
+        Some(content_type) => Ok(content_type),
+        None => match args.path.as_ref() {
+            Some(path) => get_content_type_by_path(&cfg, path),
+            None => Err(anyhow!(
+                "you must specify content type when reading data from stdin"
+            )),
+        },
+    };
+    // The named pipes and process substitutions (like `otree <(kubectl get pods -o json)`)
+    // usually have no extension, detect the content type for them, as well as the output
+    // of `--command`.
+    let is_stream = args.command.is_some() || args.path.as_deref().is_some_and(is_stream);
+    let content_type = match content_type {
+        Ok(content_type) => Some(content_type),
+        Err(_) if cfg.data.auto_detect || is_stream => None,
+        Err(err) => return Err(err),
+    };
+
+    let max_data_size = args.max_data_size.unwrap_or(cfg.data.max_data_size) * 1024 * 1024;
+    // Without truncation, there is no need to read more than the limitation, this avoids
+    // reading an endless stream into memory.
+    let read_limit = if cfg.data.truncate {
+        None
+    } else {
+        Some(max_data_size + 1)
+    };
+    let data = match (args.command.as_deref(), args.path.as_ref()) {
+        (Some(cmd), _) => hook::run(cmd, None)?.into_bytes(),
+        (None, Some(path)) => {
+            let file = File::open(path).context("open file")?;
+            read_data(file, read_limit).context("read file")?
+        }
+        (None, None) => read_data(io::stdin(), read_limit).context("read stdin")?,
+    };
+
+    let truncated = data.len() > max_data_size;
+    if truncated && !cfg.data.truncate {
+        bail!("the data size is too large, we limit the maximum size to {} to ensure TUI performance, you should try to reduce the read size. HINT: You can use command line arg `--max-data-size` or config option `data.max_data_size` to modify this limitation, or use `--truncate` to load the data partially", humansize::format_size(max_data_size, humansize::BINARY));
+    }
+
+    // To make sure the data is utf8 encoded.
+    let data = String::from_utf8(data).context("parse file utf8")?;
+
+    // When the data is too large, only the first part is loaded, the rest can be loaded
+    // later by the `load_more` action.
+    let loaded = parse::truncate(&data, max_data_size);
+    let loaded_data = &data[..loaded];
+
+    let (content_type, detected) = match content_type {
+        Some(content_type) => (content_type, false),
+        None => match ContentType::detect(&cfg, loaded_data) {
+            Some(content_type) => (content_type, true),
+            None => bail!("cannot detect content type from data, please specify it manually"),
+        },
+    };
+
+    let tree = spinner::run(&cfg, "parsing data...", || {
+        Tree::parse(&cfg, loaded_data, content_type)
+    });
+    let tree = if truncated {
+        tree.context(
+            "parse truncated data, only line based formats (like jsonl) can be truncated safely",
+        )?
+    } else {
+        tree.context("parse data")?
+    };
+
+    let found = match (args.find.as_deref(), args.query.as_deref()) {
+        (Some(keyword), _) => Some(tree.find(keyword)),
+        (None, Some(expr)) => Some(tree.query(expr)),
+        (None, None) => None,
+    };
+    if let Some(found) = found {
+        for identify in found {
+            if let Some(output) = get_output(&tree, &identify, args.find_output) {
+                println!("{output}");
+            }
+        }
+        return Ok(());
+    }
+
+    if args.dump_paths {
+        print!("{}", dump_paths(&tree, args.format));
+        return Ok(());
+    }
+
+    let mut app = App::new(&cfg, tree);
+
+    // The streams cannot be opened again, the notes for them are not saved.
+    if let Some(path) = args.path.as_deref().filter(|_| !is_stream) {
+        // The notes are optional, the document can still be viewed without them.
+        match Notes::load(&cfg, path) {
+            Ok(notes) => app.set_notes(notes),
+            Err(err) => app.set_footer_message(format!("Failed to load notes: {err:#}")),
+        }
+    }
+
+    if cfg.ui.set_title {
+        let source = args.command.as_deref().or(args.path.as_deref());
+        app.set_title(source.unwrap_or("stdin"));
+    }
+
+    if !cfg.header.disable {
+        let source = args.command.clone().or(args.path);
+        let header_ctx = HeaderContext::new(source, content_type, detected, loaded);
+        app.set_header(header_ctx);
+    }
+
+    app.run_start_hook(&data);
+
+    if let Some(cmd) = args.command {
+        app.set_watch(cmd, args.interval, &data, max_data_size);
+    }
+
+    if truncated {
+        app.set_truncated(data, loaded, max_data_size);
+    }
+
+    if args.pick.is_some() {
+        app.set_pick();
+    }
+
+    ui::set_process_handlers(cfg.ui.set_title)?;
+    ui::start(&cfg, &mut app)?;
+
+    if args.pick.is_some() && !app.is_picked() {
+        // Cancelled, the scripts can tell it from the exit code.
+        process::exit(1);
+    }
+    let output = args.pick.or(args.print_selection_on_exit);
+    if let Some(selection) = output.and_then(|output| app.get_selection(output)) {
+        println!("{selection}");
+    }
+    Ok(())
+}
+
+/// Format the item like the selection printed on exit.
+fn get_output(tree: &Tree, identify: &str, output: PrintSelection) -> Option<String> {
+    let item = tree.get_value(identify)?;
+    Some(match output {
+        PrintSelection::Key => item.name.clone(),
+        PrintSelection::Path => format!("/{identify}"),
+        PrintSelection::Value => match &item.value {
+            Value::String(s) => s.clone(),
+            value => tree.value_to_string(identify, value),
+        },
+    })
+}
+
+/// The paths of all the items with their types and value previews, in document order.
+fn dump_paths(tree: &Tree, format: DumpFormat) -> String {
+    let rows = tree.identifies().into_iter().filter_map(|identify| {
+        let (value_type, preview) = tree.get_preview(&identify)?;
+        Some((format!("/{identify}"), value_type, preview))
+    });
+    match format {
+        DumpFormat::Text => rows
+            .map(|(path, value_type, preview)| format!("{path}\t{value_type}\t{preview}\n"))
+            .collect(),
+        DumpFormat::Json => {
+            let rows: Vec<_> = rows
+                .map(|(path, value_type, preview)| {
+                    json!({"path": path, "type": value_type, "preview": preview})
+                })
+                .collect();
+            let mut output = serde_json::to_string_pretty(&rows).unwrap_or_default();
+            output.push('\n');
+            output
+        }
+        DumpFormat::Csv => {
+            let mut output = String::from("path,type,preview\n");
+            for (path, value_type, preview) in rows {
+                let row = [path.as_str(), value_type, preview.as_str()].map(escape_csv);
+                output.push_str(&row.join(","));
+                output.push('\n');
+            }
+            output
+        }
+    }
+}
+
+/// Quote the CSV field if it contains separators, quotes or line breaks.
+fn escape_csv(field: &str) -> Cow<'_, str> {
+    if !field.contains([',', '"', '\n', '\r']) {
+        return Cow::Borrowed(field);
+    }
+    Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+}
+
+/// The directory to search the workspace config from, the directory of the file, or
+/// the current directory when reading from stdin.
+fn get_workspace_dir(path: Option<&str>) -> Option<PathBuf> {
+    match path {
+        Some(path) => fs::canonicalize(path)
+            .ok()
+            .and_then(|path| path.parent().map(PathBuf::from)),
+        None => env::current_dir().ok(),
+    }
+}
+
+/// Check if the path is not a regular file, like a named pipe or a character device.
+fn is_stream(path: &str) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| !metadata.is_file() && !metadata.is_dir())
+}
+
+/// Read all the data from the reader, or at most `limit` bytes. The streams cannot
+/// provide a size hint, so we don't depend on it.
+fn read_data<R: Read>(reader: R, limit: Option<usize>) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match limit {
+        Some(limit) => reader.take(limit as u64).read_to_end(&mut data)?,
+        None => {
+            let mut reader = reader;
+            reader.read_to_end(&mut data)?
+        }
+    };
+    Ok(data)
+}
+
+fn get_content_type_by_path(cfg: &Config, path: &str) -> Result<ContentType> {
+    let path = PathBuf::from(path);
+
+    let ext = match path.extension() {
+        Some(ext) => ext,
+        None => bail!("cannot determine content type, missing extension in file path, you can specify it manually"),
+    };
+    let ext = match ext.to_str() {
+        Some(ext) => ext,
+        None => bail!("invalid extension in file path"),
+    };
+
+    if let Some(content_type) = cfg.data.extensions.get(ext) {
+        return Ok(*content_type);
+    }
+
+    Ok(match ext {
+        "json" => ContentType::Json,
+        "yaml" | "yml" => ContentType::Yaml,
+        "toml" => ContentType::Toml,
+        "jsonl" => ContentType::Jsonl,
+        _ => bail!("unsupported file type, please specify content type manually, or map the extension in config option `data.extensions`"),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_get_content_type_by_path() {
+        let mut cfg = Config::default();
+        cfg.data
+            .extensions
+            .insert(String::from("tpl"), ContentType::Yaml);
+
+        let test_cases = [
+            ("test.json", Some(ContentType::Json)),
+            ("test.yml", Some(ContentType::Yaml)),
+            ("/path/to/Cargo.toml", Some(ContentType::Toml)),
+            ("test.jsonl", Some(ContentType::Jsonl)),
+            ("test.tpl", Some(ContentType::Yaml)),
+            ("test.txt", None),
+            ("/dev/fd/63", None),
+        ];
+
+        for (path, expect) in test_cases {
+            assert_eq!(get_content_type_by_path(&cfg, path).ok(), expect);
+        }
+    }
+
+    #[test]
+    fn test_get_output() {
+        let cfg = Config::default();
+        let data = r#"{"name": "otree", "spec": {"Name": "tui", "size": 3}}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+
+        let found = tree.find("name");
+        assert_eq!(found, vec!["name", "spec/Name"]);
+        assert_eq!(tree.find("3"), vec!["spec/size"]);
+        assert_eq!(tree.query("/spec/*"), vec!["spec/Name", "spec/size"]);
+        assert_eq!(tree.query("**/size"), vec!["spec/size"]);
+        assert_eq!(tree.query("/**"), tree.identifies());
+        assert!(tree.query("/name/*").is_empty());
+
+        let outputs: Vec<_> = found
+            .iter()
+            .map(|identify| get_output(&tree, identify, PrintSelection::Path).unwrap())
+            .collect();
+        assert_eq!(outputs, vec!["/name", "/spec/Name"]);
+        assert_eq!(
+            get_output(&tree, "spec/Name", PrintSelection::Value).unwrap(),
+            "tui"
+        );
+        assert_eq!(
+            get_output(&tree, "spec/size", PrintSelection::Key).unwrap(),
+            "size"
+        );
+    }
+
+    #[test]
+    fn test_dump_paths() {
+        let cfg = Config::default();
+        let data = r#"{"name": "a,b", "spec": {"ports": [80]}}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+
+        assert_eq!(
+            dump_paths(&tree, DumpFormat::Text),
+            "/name\tstring\t\"a,b\"\n/spec\tobject\t1 field\n/spec/ports\tarray\t1 item\n/spec/ports/0\tnumber\t80\n"
+        );
+        assert_eq!(
+            dump_paths(&tree, DumpFormat::Csv),
+            "path,type,preview\n/name,string,\"\"\"a,b\"\"\"\n/spec,object,1 field\n/spec/ports,array,1 item\n/spec/ports/0,number,80\n"
+        );
+
+        let dump: Value = serde_json::from_str(&dump_paths(&tree, DumpFormat::Json)).unwrap();
+        assert_eq!(
+            dump[0],
+            json!({"path": "/name", "type": "string", "preview": "\"a,b\""})
+        );
+        assert_eq!(dump.as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_read_data() {
+        let data = b"{\"name\": \"otree\"}\n";
+        assert_eq!(read_data(&data[..], None).unwrap(), data);
+        assert_eq!(read_data(&data[..], Some(5)).unwrap(), &data[..5]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_read_fifo() {
+        let dir = std::env::temp_dir().join(format!("otree_test_fifo_{}", process::id()));
+        let _ = fs::remove_file(&dir);
+        let status = process::Command::new("mkfifo").arg(&dir).status().unwrap();
+        assert!(status.success());
+        let path = format!("{}", dir.display());
+
+        assert!(is_stream(&path));
+        assert!(!is_stream("Cargo.toml"));
+
+        let writer_path = path.clone();
+        let writer = thread::spawn(move || {
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .open(writer_path)
+                .unwrap();
+            file.write_all(b"[1, 2, 3]").unwrap();
+        });
+
+        let file = File::open(&path).unwrap();
+        let data = read_data(file, None).unwrap();
+        writer.join().unwrap();
+        fs::remove_file(&dir).unwrap();
+
+        let data = String::from_utf8(data).unwrap();
+        assert_eq!(data, "[1, 2, 3]");
+        assert_eq!(
+            ContentType::detect(&Config::default(), &data),
+            Some(ContentType::Json)
+        );
+    }
+}
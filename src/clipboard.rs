@@ -25,6 +25,33 @@ fn get_cmd() -> Result<Command> {
     Ok(cmd)
 }
 
+fn get_read_cmd() -> Result<Command> {
+    let cmd = match env::consts::OS {
+        "macos" => Command::new("pbpaste"),
+        "linux" => {
+            if env::var("WAYLAND_DISPLAY").is_ok() {
+                let mut cmd = Command::new("wl-paste");
+                cmd.arg("--no-newline");
+                cmd
+            } else {
+                let mut cmd = Command::new("xclip");
+                cmd.args(["-selection", "clipboard", "-o"]);
+                cmd
+            }
+        }
+        "windows" => {
+            let mut cmd = Command::new("powershell");
+            cmd.args(["-NoProfile", "-Command", "Get-Clipboard"]);
+            cmd
+        }
+        _ => bail!(
+            "os {} does not support clipboard, you can create issue if you have requirement",
+            env::consts::OS
+        ),
+    };
+    Ok(cmd)
+}
+
 pub fn write_clipboard(text: &str) -> Result<()> {
     let mut cmd = get_cmd()?;
     cmd.stdin(Stdio::piped());
@@ -38,7 +65,10 @@ pub fn write_clipboard(text: &str) -> Result<()> {
         Err(err) => return Err(err).context("launch clipboard program failed"),
     };
 
-    let stdin = child.stdin.as_mut().unwrap();
+    let stdin = match child.stdin.as_mut() {
+        Some(stdin) => stdin,
+        None => bail!("cannot get stdin of clipboard program"),
+    };
     if let Err(err) = stdin.write_all(text.as_bytes()) {
         return Err(err).context("write text to clipboard program");
     }
@@ -55,3 +85,28 @@ pub fn write_clipboard(text: &str) -> Result<()> {
 
     Ok(())
 }
+
+pub fn read_clipboard() -> Result<String> {
+    let mut cmd = get_read_cmd()?;
+    cmd.stdin(Stdio::null());
+
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let program = cmd.get_program().to_string_lossy();
+            bail!("cannot find clipboard program '{program}' in your system, please install it first to support clipboard")
+        }
+        Err(err) => return Err(err).context("launch clipboard program failed"),
+    };
+
+    if !output.status.success() {
+        let code = output
+            .status
+            .code()
+            .map(|code| code.to_string())
+            .unwrap_or("<unknown>".to_string());
+        bail!("clipboard program exited with bad status {code}",);
+    }
+
+    String::from_utf8(output.stdout).context("parse clipboard text utf8")
+}
@@ -1,27 +1,41 @@
+use std::time::Duration;
+
 use anyhow::{bail, Result};
 use clap::error::ErrorKind as ArgsErrorKind;
-use clap::Parser;
+use clap::{ArgGroup, Parser, ValueEnum};
 
 use crate::config::{Config, LayoutDirection};
 use crate::parse::ContentType;
 
 #[derive(Parser, Debug)]
 #[command(disable_version_flag = true)]
+#[command(group(ArgGroup::new("search").args(["find", "query"])))]
 pub struct CommandArgs {
     /// The file to read data. On non-macOS systems, this can be omitted, and data
     /// will be read from stdin.
     pub path: Option<String>,
 
-    /// The config file to use. Default will try to read `~/.config/otree.toml`.
+    /// The config file to use. Default will try to read `~/.config/otree.toml`. The
+    /// `.otree.toml` in the directory of the file (or its ancestors) is merged over it.
     #[clap(long)]
     pub config: Option<String>,
 
+    /// The profile in config file to use, the options in `[profile.{name}]` override the
+    /// ones outside.
+    #[clap(short = 'P', long)]
+    pub profile: Option<String>,
+
     /// The data content type. If the file extension is one of
     /// ["json", "yaml", "yml", "toml", "jsonl"], this can be automatically inferred. In other
     /// cases, this is required.
     #[clap(short = 't', long)]
     pub content_type: Option<ContentType>,
 
+    /// Detect the content type from data when it cannot be determined by the file
+    /// extension, or when reading data from stdin.
+    #[clap(short, long)]
+    pub auto: bool,
+
     /// Don't show the header.
     #[clap(long)]
     pub disable_header: bool,
@@ -46,10 +60,29 @@ pub struct CommandArgs {
     #[clap(short, long)]
     pub size: Option<u16>,
 
+    /// The symbol drawn before the selected item in tree overview.
+    #[clap(long)]
+    pub tree_selected_symbol: Option<String>,
+
+    /// Don't highlight the selected item in tree overview, usually used with
+    /// `--tree-selected-symbol`.
+    #[clap(long)]
+    pub tree_disable_selected_highlight: bool,
+
+    /// Don't capture the mouse, so that the native text selection and scrollback of
+    /// terminal can be used.
+    #[clap(long)]
+    pub no_mouse: bool,
+
     /// Disable syntax highlighting in data block.
     #[clap(long)]
     pub disable_highlight: bool,
 
+    /// Mask the sensitive values (see `mask.rules` in config) and forbid revealing
+    /// them, for screen sharing.
+    #[clap(long)]
+    pub redact: bool,
+
     /// Print loaded config.
     #[clap(long)]
     pub show_config: bool,
@@ -66,18 +99,120 @@ pub struct CommandArgs {
     #[clap(long)]
     pub max_data_size: Option<usize>,
 
+    /// Limit the number of nodes in the tree, `0` means no limitation.
+    #[clap(long)]
+    pub max_nodes: Option<usize>,
+
+    /// When the data size exceeds the limitation, load the first part of it (at line
+    /// boundary) rather than failing. The rest can be loaded by the `load_more` action.
+    #[clap(long)]
+    pub truncate: bool,
+
+    /// Print the path (default) or the value of the selected item to stdout on exit,
+    /// so that otree can be used as an interactive picker in shell scripts. The TUI is
+    /// drawn to stderr when stdout is not a terminal.
+    #[clap(long, value_name = "OUTPUT", num_args = 0..=1, default_missing_value = "path")]
+    pub print_selection_on_exit: Option<PrintSelection>,
+
+    /// Run as a picker, like fzf for structured data. Press Enter to confirm and print
+    /// the key, value or path of the selected item to stdout, press Esc (or quit) to
+    /// cancel with a non-zero exit code.
+    #[clap(
+        long,
+        value_name = "OUTPUT",
+        conflicts_with = "print_selection_on_exit"
+    )]
+    pub pick: Option<PrintSelection>,
+
+    /// Print the paths of the items whose names or values contain the keyword (ignoring
+    /// case) to stdout and exit, without the TUI.
+    #[clap(long, value_name = "KEYWORD", conflicts_with_all = ["pick", "print_selection_on_exit"])]
+    pub find: Option<String>,
+
+    /// Print the paths of the items matching the path pattern to stdout and exit, without
+    /// the TUI. The pattern is like `/spec/*/image`, `*` matches any key and `**` matches
+    /// any number of keys.
+    #[clap(long, value_name = "EXPR", conflicts_with_all = ["pick", "print_selection_on_exit"])]
+    pub query: Option<String>,
+
+    /// What to print for the items found by `--find` or `--query`.
+    #[clap(
+        long,
+        value_name = "OUTPUT",
+        default_value = "path",
+        requires = "search"
+    )]
+    pub find_output: PrintSelection,
+
+    /// Print the path of every item with its type and a value preview to stdout and exit,
+    /// without the TUI. This can be used to diff the structures of two files.
+    #[clap(long, conflicts_with_all = ["search", "pick", "print_selection_on_exit"])]
+    pub dump_paths: bool,
+
+    /// The output format of `--dump-paths`.
+    #[clap(long, default_value = "text", requires = "dump_paths")]
+    pub format: DumpFormat,
+
+    /// Read the data from the output of the shell command rather than a file, and run it
+    /// again every `--interval` to reload the tree, like `watch`.
+    #[clap(long, value_name = "COMMAND", conflicts_with_all = ["path", "truncate"])]
+    pub command: Option<String>,
+
+    /// The interval to run `--command` again, like `5s`, `500ms` or `1m`.
+    #[clap(long, default_value = "2s", value_parser = parse_interval, requires = "command")]
+    pub interval: Duration,
+
     /// Print version.
     #[clap(short, long)]
     pub version: bool,
 }
 
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let (num, unit) = match s.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, "s"),
+    };
+    let num: u64 = num
+        .parse()
+        .map_err(|_| format!("invalid interval '{s}', should be like '5s'"))?;
+    let interval = match unit {
+        "ms" => Duration::from_millis(num),
+        "s" => Duration::from_secs(num),
+        "m" => Duration::from_secs(num * 60),
+        _ => {
+            return Err(format!(
+                "invalid interval unit '{unit}', should be ms, s or m"
+            ))
+        }
+    };
+    if interval.is_zero() {
+        return Err(String::from("the interval cannot be zero"));
+    }
+    Ok(interval)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PrintSelection {
+    Key,
+    Path,
+    Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// One item per line, the fields are separated by tabs.
+    Text,
+    Json,
+    Csv,
+}
+
 impl CommandArgs {
     pub fn parse() -> Result<Option<Self>> {
         let args = match CommandArgs::try_parse() {
             Ok(args) => args,
             Err(err) => {
                 err.use_stderr();
-                err.print().unwrap();
+                let _ = err.print();
                 if matches!(
                     err.kind(),
                     ArgsErrorKind::DisplayHelp
@@ -125,14 +260,43 @@ impl CommandArgs {
             cfg.footer.disable = true;
         }
 
+        if self.no_mouse {
+            cfg.ui.mouse = false;
+        }
+
         if self.disable_highlight {
             cfg.data.disable_highlight = true;
         }
 
+        if self.redact {
+            cfg.mask.enable = true;
+            cfg.mask.redact = true;
+        }
+
+        if self.auto {
+            cfg.data.auto_detect = true;
+        }
+
+        if self.truncate {
+            cfg.data.truncate = true;
+        }
+
+        if let Some(max_nodes) = self.max_nodes {
+            cfg.data.max_nodes = max_nodes;
+        }
+
         if let Some(format) = self.header_format.as_ref() {
             cfg.header.format.clone_from(format);
         }
 
+        if let Some(symbol) = self.tree_selected_symbol.as_ref() {
+            cfg.tree.selected_symbol.clone_from(symbol);
+        }
+
+        if self.tree_disable_selected_highlight {
+            cfg.tree.selected_highlight = false;
+        }
+
         if let Some(size) = self.size {
             cfg.layout.tree_size = size;
         }
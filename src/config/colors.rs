@@ -36,12 +36,24 @@ pub struct Colors {
 
     #[serde(default = "PopupColors::default")]
     pub popup: PopupColors,
+
+    #[serde(default = "ValueColors::default")]
+    pub values: ValueColors,
 }
 
-generate_colors_parse!(Colors, header, footer, tree, data, focus_border, popup);
+generate_colors_parse!(
+    Colors,
+    header,
+    footer,
+    tree,
+    data,
+    focus_border,
+    popup,
+    values
+);
 
-impl Colors {
-    pub fn default() -> Self {
+impl Default for Colors {
+    fn default() -> Self {
         Self {
             header: Self::default_header(),
             tree: TreeColors::default(),
@@ -49,9 +61,12 @@ impl Colors {
             data: DataColors::default(),
             focus_border: Self::default_focus_boder(),
             popup: PopupColors::default(),
+            values: ValueColors::default(),
         }
     }
+}
 
+impl Colors {
     fn default_header() -> Color {
         Color::new("", "", true, false)
     }
@@ -89,9 +104,32 @@ pub struct DataColors {
 
     #[serde(default = "DataColors::default_section")]
     pub section: Color,
+
+    #[serde(default = "DataColors::default_indent_guide")]
+    pub indent_guide: Color,
+
+    #[serde(default = "DataColors::default_selected")]
+    pub selected: Color,
+
+    #[serde(default = "DataColors::default_annotation")]
+    pub annotation: Color,
 }
 
-generate_colors_parse!(DataColors, text, border, symbol, name, str, num, null, bool, section);
+generate_colors_parse!(
+    DataColors,
+    text,
+    border,
+    symbol,
+    name,
+    str,
+    num,
+    null,
+    bool,
+    section,
+    indent_guide,
+    selected,
+    annotation
+);
 
 impl DataColors {
     fn default() -> Self {
@@ -105,6 +143,9 @@ impl DataColors {
             null: Self::default_null(),
             bool: Self::default_bool(),
             section: Self::default_section(),
+            indent_guide: Self::default_indent_guide(),
+            selected: Self::default_selected(),
+            annotation: Self::default_annotation(),
         }
     }
 
@@ -135,6 +176,18 @@ impl DataColors {
     fn default_section() -> Color {
         Color::new("cyan", "", true, false)
     }
+
+    fn default_indent_guide() -> Color {
+        Color::new("dark_gray", "", false, false)
+    }
+
+    fn default_selected() -> Color {
+        Color::new("", "dark_gray", false, false)
+    }
+
+    fn default_annotation() -> Color {
+        Color::new("dark_gray", "", false, true)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,11 +221,27 @@ pub struct TreeColors {
 
     #[serde(default = "TreeColors::default_value")]
     pub value: Color,
+
+    #[serde(default = "TreeColors::default_note")]
+    pub note: Color,
+
+    #[serde(default = "TreeColors::default_difference")]
+    pub difference: Color,
+
+    #[serde(default = "TreeColors::default_empty")]
+    pub empty: Color,
+
+    #[serde(default = "TreeColors::default_null")]
+    pub null: Color,
+
+    /// The placeholders of the parts failed to parse, see `data.partial_parse`.
+    #[serde(default = "TreeColors::default_error")]
+    pub error: Color,
 }
 
 generate_colors_parse!(
     TreeColors, border, selected, name, type_str, type_null, type_bool, type_num, type_arr,
-    type_obj, value
+    type_obj, value, note, difference, empty, null, error
 );
 
 impl TreeColors {
@@ -188,6 +257,11 @@ impl TreeColors {
             type_arr: Self::default_type(),
             type_obj: Self::default_type(),
             value: Self::default_value(),
+            note: Self::default_note(),
+            difference: Self::default_difference(),
+            empty: Self::default_empty(),
+            null: Self::default_null(),
+            error: Self::default_error(),
         }
     }
 
@@ -206,6 +280,26 @@ impl TreeColors {
     fn default_value() -> Color {
         Color::new("dark_gray", "", false, false)
     }
+
+    fn default_note() -> Color {
+        Color::new("yellow", "", false, false)
+    }
+
+    fn default_difference() -> Color {
+        Color::new("light_red", "", true, false)
+    }
+
+    fn default_empty() -> Color {
+        Color::new("dark_gray", "", false, true)
+    }
+
+    fn default_null() -> Color {
+        Color::new("magenta", "", false, true)
+    }
+
+    fn default_error() -> Color {
+        Color::new("red", "", true, false)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -245,24 +339,90 @@ impl FooterColors {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PopupColors {
+    #[serde(default = "Color::default")]
+    pub info_text: Color,
+
     #[serde(default = "PopupColors::default_error_text")]
     pub error_text: Color,
+
+    #[serde(default = "PopupColors::default_diff_added")]
+    pub diff_added: Color,
+
+    #[serde(default = "PopupColors::default_error_text")]
+    pub diff_removed: Color,
 }
 
-generate_colors_parse!(PopupColors, error_text);
+generate_colors_parse!(PopupColors, info_text, error_text, diff_added, diff_removed);
 
 impl PopupColors {
     fn default() -> Self {
         Self {
+            info_text: Color::default(),
             error_text: Self::default_error_text(),
+            diff_added: Self::default_diff_added(),
+            diff_removed: Self::default_error_text(),
         }
     }
 
+    fn default_diff_added() -> Color {
+        Color::new("green", "", false, false)
+    }
+
     fn default_error_text() -> Color {
         Color::new("red", "", false, false)
     }
 }
 
+/// The styles by the values, patched on the styles of the numbers and booleans, in both
+/// the tree overview and the data block. They are empty by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueColors {
+    #[serde(default = "Color::default")]
+    pub negative: Color,
+
+    #[serde(default = "Color::default")]
+    pub zero: Color,
+
+    #[serde(default = "Color::default")]
+    pub bool_true: Color,
+
+    #[serde(default = "Color::default")]
+    pub bool_false: Color,
+}
+
+generate_colors_parse!(ValueColors, negative, zero, bool_true, bool_false);
+
+impl ValueColors {
+    fn default() -> Self {
+        Self {
+            negative: Color::default(),
+            zero: Color::default(),
+            bool_true: Color::default(),
+            bool_false: Color::default(),
+        }
+    }
+
+    /// The style to patch for the number, in its text representation.
+    pub fn number_style(&self, num: &str) -> Style {
+        let digits = num.trim_start_matches(['-', '+']);
+        if !digits.is_empty() && digits.chars().all(|ch| ch == '0' || ch == '.') {
+            self.zero.style
+        } else if num.starts_with('-') {
+            self.negative.style
+        } else {
+            Style::default()
+        }
+    }
+
+    pub fn bool_style(&self, b: bool) -> Style {
+        if b {
+            self.bool_true.style
+        } else {
+            self.bool_false.style
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Color {
     pub fg: Option<String>,
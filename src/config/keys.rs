@@ -1,22 +1,31 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use anyhow::{bail, Context, Result};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use serde::{Deserialize, Serialize};
 
+use super::Config;
+
 macro_rules! generate_keys_default {
     ($($field:ident => $value:expr),+) => {
-        impl Keys {
+        impl Default for Keys {
             ::paste::paste! {
-                pub fn default() -> Self {
+                fn default() -> Self {
                     Self {
                         $(
                             $field: Self::[<default_ $field>](),
                         )+
+                        tree: HashMap::new(),
+                        data_block: HashMap::new(),
+                        popup: HashMap::new(),
                         actions: vec![],
+                        context_actions: vec![],
                     }
                 }
             }
+        }
+
+        impl Keys {
 
             $(
                 ::paste::paste! {
@@ -31,26 +40,46 @@ macro_rules! generate_keys_default {
 
 macro_rules! generate_actions {
     ($($field:ident => $value:ident),+) => {
-        #[derive(Debug, Clone, Copy)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         pub enum Action {
             $($value),+
         }
 
+        impl Action {
+            pub const ALL: &'static [Action] = &[$(Action::$value),+];
+
+            /// The name of the action, same as the field name in the config file.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Action::$value => stringify!($field)),+
+                }
+            }
+        }
+
         impl Keys {
             pub fn parse(&mut self) -> Result<()> {
-                let mut unique = HashSet::new();
                 self.actions = vec![
                     $(
-                        (Key::parse_keys(&self.$field, &mut unique).with_context(|| format!("parse keys for action {}", stringify!($field)))?, Action::$value),
+                        (Key::parse_keys(&self.$field).with_context(|| format!("parse keys for action {}", stringify!($field)))?, Action::$value),
                     )+
                 ];
-                Ok(())
+                self.check_conflicts()?;
+                self.parse_contexts()
+            }
+
+            pub fn get_action_keys(&self, action: Action) -> &[String] {
+                match action {
+                    $(Action::$value => &self.$field),+
+                }
             }
         }
     };
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The parsed keys of each action.
+type KeyActions = Vec<(Vec<Key>, Action)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Key {
     Char(char),
 
@@ -180,16 +209,11 @@ impl Key {
         })
     }
 
-    fn parse_keys(raw_keys: &[String], unique: &mut HashSet<String>) -> Result<Vec<Self>> {
-        let mut keys = Vec::with_capacity(raw_keys.len());
-        for raw_key in raw_keys {
-            if unique.contains(raw_key) {
-                bail!("the key '{raw_key}' is used by another action, cannot be used twice");
-            }
-            unique.insert(raw_key.clone());
-            keys.push(Self::parse(raw_key)?);
-        }
-        Ok(keys)
+    fn parse_keys(raw_keys: &[String]) -> Result<Vec<Self>> {
+        raw_keys
+            .iter()
+            .map(|raw_key| Self::parse(raw_key))
+            .collect()
     }
 }
 
@@ -249,11 +273,127 @@ pub struct Keys {
     #[serde(default = "Keys::default_copy_value")]
     pub copy_value: Vec<String>,
 
+    #[serde(default = "Keys::default_copy_tree")]
+    pub copy_tree: Vec<String>,
+
+    #[serde(default = "Keys::default_copy_raw_value")]
+    pub copy_raw_value: Vec<String>,
+
+    #[serde(default = "Keys::default_copy_json_string")]
+    pub copy_json_string: Vec<String>,
+
+    #[serde(default = "Keys::default_copy_shell_string")]
+    pub copy_shell_string: Vec<String>,
+
+    #[serde(default = "Keys::default_pin_value")]
+    pub pin_value: Vec<String>,
+
+    #[serde(default = "Keys::default_export_view")]
+    pub export_view: Vec<String>,
+
+    #[serde(default = "Keys::default_load_more")]
+    pub load_more: Vec<String>,
+
+    #[serde(default = "Keys::default_sort_keys")]
+    pub sort_keys: Vec<String>,
+
+    #[serde(default = "Keys::default_edit_note")]
+    pub edit_note: Vec<String>,
+
+    #[serde(default = "Keys::default_show_note")]
+    pub show_note: Vec<String>,
+
+    #[serde(default = "Keys::default_diff_clipboard")]
+    pub diff_clipboard: Vec<String>,
+
+    #[serde(default = "Keys::default_diff_file")]
+    pub diff_file: Vec<String>,
+
+    #[serde(default = "Keys::default_aggregate")]
+    pub aggregate: Vec<String>,
+
+    #[serde(default = "Keys::default_show_schema")]
+    pub show_schema: Vec<String>,
+
+    #[serde(default = "Keys::default_export_schema")]
+    pub export_schema: Vec<String>,
+
+    #[serde(default = "Keys::default_mark_differences")]
+    pub mark_differences: Vec<String>,
+
+    #[serde(default = "Keys::default_root_history")]
+    pub root_history: Vec<String>,
+    #[serde(default = "Keys::default_show_operations")]
+    pub show_operations: Vec<String>,
+
+    #[serde(default = "Keys::default_toggle_escape")]
+    pub toggle_escape: Vec<String>,
+
+    #[serde(default = "Keys::default_select_lines")]
+    pub select_lines: Vec<String>,
+
+    #[serde(default = "Keys::default_open_pager")]
+    pub open_pager: Vec<String>,
+
+    #[serde(default = "Keys::default_open_subtree")]
+    pub open_subtree: Vec<String>,
+
+    #[serde(default = "Keys::default_show_path")]
+    pub show_path: Vec<String>,
+
+    #[serde(default = "Keys::default_toggle_hex")]
+    pub toggle_hex: Vec<String>,
+
+    #[serde(default = "Keys::default_reveal_masked")]
+    pub reveal_masked: Vec<String>,
+
+    #[serde(default = "Keys::default_toggle_preview")]
+    pub toggle_preview: Vec<String>,
+
+    #[serde(default = "Keys::default_expand_levels")]
+    pub expand_levels: Vec<String>,
+
+    #[serde(default = "Keys::default_next_sibling")]
+    pub next_sibling: Vec<String>,
+    #[serde(default = "Keys::default_prev_sibling")]
+    pub prev_sibling: Vec<String>,
+    #[serde(default = "Keys::default_first_sibling")]
+    pub first_sibling: Vec<String>,
+    #[serde(default = "Keys::default_last_sibling")]
+    pub last_sibling: Vec<String>,
+
+    #[serde(default = "Keys::default_next_array")]
+    pub next_array: Vec<String>,
+    #[serde(default = "Keys::default_prev_array")]
+    pub prev_array: Vec<String>,
+    #[serde(default = "Keys::default_next_object")]
+    pub next_object: Vec<String>,
+    #[serde(default = "Keys::default_prev_object")]
+    pub prev_object: Vec<String>,
+    #[serde(default = "Keys::default_next_leaf")]
+    pub next_leaf: Vec<String>,
+    #[serde(default = "Keys::default_prev_leaf")]
+    pub prev_leaf: Vec<String>,
+
+    #[serde(default = "Keys::default_command_palette")]
+    pub command_palette: Vec<String>,
+
     #[serde(default = "Keys::default_quit")]
     pub quit: Vec<String>,
 
+    /// The keys of actions in the context, like `[keys.tree]`, override the ones above
+    /// when the widget is in focus.
+    #[serde(default = "Config::empty_map")]
+    pub tree: HashMap<String, Vec<String>>,
+    #[serde(default = "Config::empty_map")]
+    pub data_block: HashMap<String, Vec<String>>,
+    #[serde(default = "Config::empty_map")]
+    pub popup: HashMap<String, Vec<String>>,
+
     #[serde(skip)]
-    actions: Vec<(Vec<Key>, Action)>,
+    actions: KeyActions,
+    #[serde(skip)]
+    context_actions: Vec<(KeyContext, KeyActions)>,
 }
 
 generate_keys_default!(
@@ -277,6 +417,44 @@ generate_keys_default!(
     edit => ["e"],
     copy_name => ["y"],
     copy_value => ["Y"],
+    copy_tree => ["t"],
+    copy_raw_value => [] as [&str; 0],
+    copy_json_string => [] as [&str; 0],
+    copy_shell_string => [] as [&str; 0],
+    pin_value => ["+"],
+    export_view => ["x"],
+    load_more => ["m"],
+    sort_keys => ["s"],
+    edit_note => ["a"],
+    show_note => ["A"],
+    diff_clipboard => ["d"],
+    diff_file => ["D"],
+    aggregate => ["c"],
+    show_schema => ["i"],
+    export_schema => ["I"],
+    mark_differences => ["="],
+    root_history => ["R"],
+    show_operations => ["o"],
+    toggle_escape => ["\\"],
+    select_lines => ["V"],
+    open_pager => ["P"],
+    open_subtree => ["O"],
+    show_path => ["f"],
+    toggle_hex => ["H"],
+    reveal_masked => ["*"],
+    toggle_preview => ["w"],
+    expand_levels => ["E"],
+    next_sibling => ["J"],
+    prev_sibling => ["K"],
+    first_sibling => ["{"],
+    last_sibling => ["}"],
+    next_array => [] as [&str; 0],
+    prev_array => [] as [&str; 0],
+    next_object => [] as [&str; 0],
+    prev_object => [] as [&str; 0],
+    next_leaf => [] as [&str; 0],
+    prev_leaf => [] as [&str; 0],
+    command_palette => ["<ctrl-p>"],
     quit => ["<ctrl-c>", "q"]
 );
 
@@ -301,19 +479,267 @@ generate_actions!(
     edit => Edit,
     copy_name => CopyName,
     copy_value => CopyValue,
+    copy_tree => CopyTree,
+    copy_raw_value => CopyRawValue,
+    copy_json_string => CopyJsonString,
+    copy_shell_string => CopyShellString,
+    pin_value => PinValue,
+    export_view => ExportView,
+    load_more => LoadMore,
+    sort_keys => SortKeys,
+    edit_note => EditNote,
+    show_note => ShowNote,
+    diff_clipboard => DiffClipboard,
+    diff_file => DiffFile,
+    aggregate => Aggregate,
+    show_schema => ShowSchema,
+    export_schema => ExportSchema,
+    mark_differences => MarkDifferences,
+    root_history => RootHistory,
+    show_operations => ShowOperations,
+    toggle_escape => ToggleEscape,
+    select_lines => SelectLines,
+    open_pager => OpenPager,
+    open_subtree => OpenSubtree,
+    show_path => ShowPath,
+    toggle_hex => ToggleHex,
+    reveal_masked => RevealMasked,
+    toggle_preview => TogglePreview,
+    expand_levels => ExpandLevels,
+    next_sibling => NextSibling,
+    prev_sibling => PrevSibling,
+    first_sibling => FirstSibling,
+    last_sibling => LastSibling,
+    next_array => NextArray,
+    prev_array => PrevArray,
+    next_object => NextObject,
+    prev_object => PrevObject,
+    next_leaf => NextLeaf,
+    prev_leaf => PrevLeaf,
+    command_palette => CommandPalette,
     quit => Quit
 );
 
+/// The widget in focus when a key is pressed. A key can be bound to two actions of
+/// different contexts, like `r` to change root in tree overview and to another action
+/// in data block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyContext {
+    Tree,
+    DataBlock,
+    Popup,
+}
+
+impl KeyContext {
+    fn name(&self) -> &'static str {
+        match self {
+            KeyContext::Tree => "tree",
+            KeyContext::DataBlock => "data_block",
+            KeyContext::Popup => "popup",
+        }
+    }
+}
+
+impl Action {
+    /// The context that the action only works in, `None` means the action works
+    /// everywhere.
+    pub fn context(&self) -> Option<KeyContext> {
+        match self {
+            Action::SelectParent
+            | Action::CloseParent
+            | Action::ChangeRoot
+            | Action::ExpandLevels
+            | Action::NextSibling
+            | Action::PrevSibling
+            | Action::FirstSibling
+            | Action::LastSibling => Some(KeyContext::Tree),
+            Action::ToggleEscape | Action::SelectLines | Action::ToggleHex => {
+                Some(KeyContext::DataBlock)
+            }
+            _ => None,
+        }
+    }
+
+    /// The actions can share keys if they work in different contexts.
+    fn can_share_keys(&self, other: &Action) -> bool {
+        match (self.context(), other.context()) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        }
+    }
+}
+
 impl Keys {
-    pub fn get_key_action(&self, event: KeyEvent) -> Option<Action> {
+    /// Get the action of the key, the action of current context takes precedence. If
+    /// there is none, the action bound to the key in other context is returned, so that
+    /// the keys without conflicts work everywhere.
+    pub fn get_key_action(&self, event: KeyEvent, context: Option<KeyContext>) -> Option<Action> {
         let event_key = Key::from_event(event)?;
-        for (keys, action) in self.actions.iter() {
-            for key in keys {
-                if *key == event_key {
+        if let Some(context) = context {
+            let actions = self
+                .context_actions
+                .iter()
+                .find(|(ctx, _)| *ctx == context)
+                .map(|(_, actions)| actions);
+            for (keys, action) in actions.into_iter().flatten() {
+                if keys.contains(&event_key) {
                     return Some(*action);
                 }
             }
         }
-        None
+
+        let mut found = None;
+        for (keys, action) in self.actions.iter() {
+            if !keys.contains(&event_key) {
+                continue;
+            }
+            if context.is_some() && action.context() == context {
+                return Some(*action);
+            }
+            found = found.or(Some(*action));
+        }
+        found
+    }
+
+    fn parse_contexts(&mut self) -> Result<()> {
+        self.context_actions.clear();
+        for (context, table) in [
+            (KeyContext::Tree, &self.tree),
+            (KeyContext::DataBlock, &self.data_block),
+            (KeyContext::Popup, &self.popup),
+        ] {
+            // Sort by the action order, so that the errors are stable.
+            let mut actions = Vec::with_capacity(table.len());
+            for (name, raw_keys) in table {
+                let action = match Action::ALL.iter().find(|action| action.name() == name) {
+                    Some(action) => *action,
+                    None => bail!("unknown action '{name}' in keys.{}", context.name()),
+                };
+                let keys = Key::parse_keys(raw_keys).with_context(|| {
+                    format!("parse keys for action {name} in keys.{}", context.name())
+                })?;
+                actions.push((keys, action));
+            }
+            actions.sort_by_key(|(_, action)| Action::ALL.iter().position(|a| a == action));
+
+            let mut bound: HashMap<Key, Action> = HashMap::new();
+            for (keys, action) in actions.iter() {
+                for (key, raw_key) in keys.iter().zip(&table[action.name()]) {
+                    if let Some(other) = bound.insert(*key, *action) {
+                        bail!(
+                            "conflicting keys in keys.{}: '{raw_key}' is used by both {} and {}",
+                            context.name(),
+                            other.name(),
+                            action.name()
+                        );
+                    }
+                }
+            }
+            if !actions.is_empty() {
+                self.context_actions.push((context, actions));
+            }
+        }
+        Ok(())
+    }
+
+    /// Report all the keys bound to conflicting actions at once, so that they can be
+    /// fixed together.
+    fn check_conflicts(&self) -> Result<()> {
+        let mut bound: HashMap<Key, Vec<Action>> = HashMap::new();
+        let mut conflicts = vec![];
+        for (keys, action) in self.actions.iter() {
+            let raw_keys = self.get_action_keys(*action);
+            for (key, raw_key) in keys.iter().zip(raw_keys) {
+                let actions = bound.entry(*key).or_default();
+                for other in actions.iter() {
+                    if !other.can_share_keys(action) {
+                        conflicts.push(format!(
+                            "'{raw_key}' is used by both {} and {}",
+                            other.name(),
+                            action.name()
+                        ));
+                    }
+                }
+                actions.push(*action);
+            }
+        }
+
+        if !conflicts.is_empty() {
+            bail!("conflicting keys: {}", conflicts.join("; "));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_conflicts() {
+        let mut keys = Keys::default();
+        keys.parse().unwrap();
+
+        keys.quit = vec![String::from("j"), String::from("<ctrl-c>")];
+        keys.change_layout = vec![String::from("<ctrl-c>")];
+        let err = keys.parse().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "conflicting keys: 'j' is used by both move_down and quit; '<ctrl-c>' is used by both change_layout and quit"
+        );
+
+        // The actions of different contexts can share keys.
+        let mut keys = Keys {
+            select_lines: vec![String::from("r")],
+            ..Default::default()
+        };
+        keys.parse().unwrap();
+
+        let event = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        let action = keys.get_key_action(event, Some(KeyContext::Tree));
+        assert_eq!(action, Some(Action::ChangeRoot));
+        let action = keys.get_key_action(event, Some(KeyContext::DataBlock));
+        assert_eq!(action, Some(Action::SelectLines));
+
+        // Without conflicts, the keys work in all contexts.
+        let event = KeyEvent::new(KeyCode::Char('\\'), KeyModifiers::NONE);
+        let action = keys.get_key_action(event, Some(KeyContext::Tree));
+        assert_eq!(action, Some(Action::ToggleEscape));
+    }
+
+    #[test]
+    fn test_context_keys() {
+        let mut keys = Keys::default();
+        keys.data_block
+            .insert(String::from("select_first"), vec![String::from("j")]);
+        keys.popup
+            .insert(String::from("reset"), vec![String::from("q")]);
+        keys.parse().unwrap();
+
+        let event = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        let action = keys.get_key_action(event, Some(KeyContext::DataBlock));
+        assert_eq!(action, Some(Action::SelectFirst));
+        // Fall back to the global keys.
+        let action = keys.get_key_action(event, Some(KeyContext::Tree));
+        assert_eq!(action, Some(Action::MoveDown));
+        let event = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        let action = keys.get_key_action(event, Some(KeyContext::Popup));
+        assert_eq!(action, Some(Action::Reset));
+        assert_eq!(keys.get_key_action(event, None), Some(Action::Quit));
+
+        keys.tree.insert(String::from("unknown"), vec![]);
+        let err = keys.parse().unwrap_err();
+        assert_eq!(err.to_string(), "unknown action 'unknown' in keys.tree");
+
+        let mut keys = Keys::default();
+        keys.tree
+            .insert(String::from("quit"), vec![String::from("x")]);
+        keys.tree
+            .insert(String::from("move_up"), vec![String::from("x")]);
+        let err = keys.parse().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "conflicting keys in keys.tree: 'x' is used by both move_up and quit"
+        );
     }
 }
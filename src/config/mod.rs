@@ -3,36 +3,66 @@ pub mod keys;
 pub mod types;
 
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::{env, fs, io};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, mem};
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::parse::ContentType;
+use crate::render;
+
 use self::colors::Colors;
 use self::keys::Keys;
-use self::types::Types;
+use self::types::{Icons, Types};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "Editor::default")]
     pub editor: Editor,
 
+    #[serde(default = "Pager::default")]
+    pub pager: Pager,
+
     #[serde(default = "Data::default")]
     pub data: Data,
 
     #[serde(default = "Layout::default")]
     pub layout: Layout,
 
+    #[serde(default = "Ui::default")]
+    pub ui: Ui,
+
     #[serde(default = "Header::default")]
     pub header: Header,
 
     #[serde(default = "Footer::default")]
     pub footer: Footer,
 
+    #[serde(default = "Tree::default")]
+    pub tree: Tree,
+
+    #[serde(default = "Popup::default")]
+    pub popup: Popup,
+
+    #[serde(default = "Export::default")]
+    pub export: Export,
+
+    #[serde(default = "Hooks::default")]
+    pub hooks: Hooks,
+
+    #[serde(default = "State::default")]
+    pub state: State,
+
+    #[serde(default = "Mask::default")]
+    pub mask: Mask,
+
     #[serde(default = "Config::empty_map")]
     pub palette: HashMap<String, String>,
 
+    #[serde(default = "Config::empty_map")]
+    pub renderers: HashMap<String, String>,
+
     #[serde(default = "Colors::default")]
     pub colors: Colors,
 
@@ -55,6 +85,15 @@ pub struct Editor {
     pub dir: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pager {
+    #[serde(default = "Pager::default_program")]
+    pub program: String,
+
+    #[serde(default = "Pager::default_args")]
+    pub args: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layout {
     #[serde(default = "Layout::default_direction")]
@@ -62,6 +101,51 @@ pub struct Layout {
 
     #[serde(default = "Layout::default_tree_size")]
     pub tree_size: u16,
+
+    #[serde(default = "Layout::default_mode")]
+    pub mode: LayoutMode,
+
+    /// Fit the tree overview width to the longest visible item in horizontal layout,
+    /// the `tree_size` is the upper bound then.
+    #[serde(default = "Config::disable")]
+    pub auto_size: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ui {
+    /// Capture the mouse events, disable this to use the native text selection and
+    /// scrollback of terminal.
+    #[serde(default = "Config::enable")]
+    pub mouse: bool,
+
+    /// Prepended to the title of the widget in focus, so that the focus can be told
+    /// without colors, like `*`.
+    #[serde(default = "Config::empty_string")]
+    pub focus_symbol: String,
+
+    #[serde(default = "Ui::default_focus_border")]
+    pub focus_border: FocusBorder,
+
+    /// Set the terminal title to the data source, like `otree – file.json`, the title is
+    /// restored on exit.
+    #[serde(default = "Config::disable")]
+    pub set_title: bool,
+
+    /// Only draw ASCII characters, for the terminals and fonts without the box drawing
+    /// and other Unicode glyphs. The glyph options left as default are replaced too.
+    #[serde(default = "Config::disable")]
+    pub ascii_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusBorder {
+    /// Thick when `colors.focus_border` is bold, otherwise plain.
+    Auto,
+    Plain,
+    Thick,
+    Double,
+    Rounded,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -72,6 +156,17 @@ pub enum LayoutDirection {
     Horizontal,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutMode {
+    /// Always show the tree overview and data block side by side.
+    Split,
+    /// Hide the data block until the `toggle_preview` action is pressed.
+    TreeOnly,
+    /// Only show the data block when a leaf is selected, or by `toggle_preview`.
+    Auto,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Header {
     #[serde(default = "Config::disable")]
@@ -87,29 +182,342 @@ pub struct Footer {
     pub disable: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tree {
+    /// The symbol shown at the end of the tree items wider than the tree overview, empty
+    /// string means to cut the items without any indicator.
+    #[serde(default = "Tree::default_ellipsis")]
+    pub ellipsis: String,
+
+    /// Display the object fields in alphabetical order, the `sort_keys` action can toggle
+    /// it at runtime.
+    #[serde(default = "Config::disable")]
+    pub sort_keys: bool,
+
+    /// The symbol shown after the items with notes.
+    #[serde(default = "Tree::default_note_symbol")]
+    pub note_symbol: String,
+
+    /// The first index of arrays, `1` helps to match the line numbers of JSONL sources.
+    /// This only affects the tree labels, footer and path popup, the copied and printed
+    /// paths always use the raw indexes from 0.
+    #[serde(default = "Tree::default_index_base")]
+    pub index_base: usize,
+
+    /// Pad the indexes with zeros to the same width in each array, like `007`.
+    #[serde(default = "Config::disable")]
+    pub index_padding: bool,
+
+    /// Show smart labels for the OpenAPI (or Swagger) documents, like `GET /pets` for
+    /// the operations and `Not Found` for the response codes.
+    #[serde(default = "Config::disable")]
+    pub openapi_labels: bool,
+
+    /// Show the icons of the types before the names, see `types.icons`.
+    #[serde(default = "Config::disable")]
+    pub show_icons: bool,
+
+    /// Show the type labels after the names, hide them to reclaim width for names on
+    /// narrow terminals.
+    #[serde(default = "Config::enable")]
+    pub show_types: bool,
+
+    /// Only show the first character of the type labels, like `s` for `str`.
+    #[serde(default = "Config::disable")]
+    pub short_types: bool,
+
+    /// Align the type labels and value descriptions of the visible items to the same
+    /// columns, like a table.
+    #[serde(default = "Config::disable")]
+    pub align_columns: bool,
+
+    /// The description of empty objects and arrays, so that they can be told from the
+    /// populated ones without expanding.
+    #[serde(default = "Tree::default_empty_placeholder")]
+    pub empty_placeholder: String,
+
+    /// Group the digits of large numbers with this separator in the descriptions and
+    /// the aggregate report, like `1,234,567`. Empty means no grouping.
+    #[serde(default = "Config::empty_string")]
+    pub thousands_separator: String,
+
+    /// The number of levels opened by the `expand_levels` action, including the selected
+    /// item itself.
+    #[serde(default = "Tree::default_expand_levels")]
+    pub expand_levels: usize,
+
+    /// The symbol drawn before the selected item, so that the selection can be told
+    /// without the highlight.
+    #[serde(default = "Config::empty_string")]
+    pub selected_symbol: String,
+
+    /// Highlight the selected item with `colors.tree.selected`.
+    #[serde(default = "Config::enable")]
+    pub selected_highlight: bool,
+
+    #[serde(default = "Tree::default_node_closed_symbol")]
+    pub node_closed_symbol: String,
+    #[serde(default = "Tree::default_node_open_symbol")]
+    pub node_open_symbol: String,
+    #[serde(default = "Tree::default_node_no_children_symbol")]
+    pub node_no_children_symbol: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Popup {
+    /// The size of the popup, in percentage of the terminal size.
+    #[serde(default = "Popup::default_percent")]
+    pub width_percent: u16,
+    #[serde(default = "Popup::default_percent")]
+    pub height_percent: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// The file to save the state across sessions, like the notes of items. Empty means
+    /// `otree/state.json` in the user's data directory.
+    #[serde(default = "Config::empty_string")]
+    pub file: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mask {
+    /// Show the sensitive values as the symbol in tree overview and data block, until
+    /// the `reveal_masked` action is pressed.
+    #[serde(default = "Config::disable")]
+    pub enable: bool,
+
+    /// The rules starting with `/` are paths from the document root, others are matched
+    /// against the field names case-insensitively, like `token` masks `access_token`.
+    #[serde(default = "Mask::default_rules")]
+    pub rules: Vec<String>,
+
+    #[serde(default = "Mask::default_symbol")]
+    pub symbol: String,
+
+    /// Forbid the `reveal_masked` action, for screen sharing.
+    #[serde(default = "Config::disable")]
+    pub redact: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Export {
+    /// The directory to write the exported views.
+    #[serde(default = "Editor::default_dir")]
+    pub dir: String,
+
+    #[serde(default = "Export::default_format")]
+    pub format: ExportFormat,
+
+    /// Ask before copying more than these MiB to the clipboard, which can hang some
+    /// clipboards, the data can be written to a file in `dir` instead. `0` never asks.
+    #[serde(default = "Export::default_confirm_copy_size")]
+    pub confirm_copy_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Text with ANSI escape codes, can be shown by `cat` or `less -R`.
+    Ansi,
+    Html,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hooks {
+    /// The shell command to run when the selected item is changed, the placeholders
+    /// `{path}`, `{name}` and `{value}` are replaced by the quoted values of the item.
+    #[serde(default = "Config::empty_string")]
+    pub on_select: String,
+
+    /// The shell command to run on startup, the document is written to its stdin.
+    #[serde(default = "Config::empty_string")]
+    pub on_start: String,
+
+    /// Wait for the selection to stay unchanged for these milliseconds before running
+    /// `on_select`, to avoid running it for every item when moving the cursor quickly.
+    #[serde(default = "Hooks::default_debounce")]
+    pub debounce: u64,
+
+    #[serde(default = "Hooks::default_output")]
+    pub output: HookOutput,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookOutput {
+    /// Show the first line of the output in the footer.
+    Footer,
+    Popup,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Data {
     #[serde(default = "Config::disable")]
     pub disable_highlight: bool,
     #[serde(default = "Config::default_max_data_size")]
     pub max_data_size: usize,
+    #[serde(default = "Config::disable")]
+    pub auto_detect: bool,
+    #[serde(default = "Config::disable")]
+    pub truncate: bool,
+    /// The maximum number of nodes in the tree, `0` means no limitation.
+    #[serde(default = "Config::default_max_nodes")]
+    pub max_nodes: usize,
+    /// Draw vertical lines at the indentation levels in the data block.
+    #[serde(default = "Config::disable")]
+    pub indent_guides: bool,
+    /// The control characters and escape sequences in the values would corrupt the
+    /// TUI, they are replaced with this string when displayed.
+    #[serde(default = "Data::default_control_replacement")]
+    pub control_replacement: String,
+    /// Show the spaces, tabs and zero-width characters in the data block as visible
+    /// symbols.
+    #[serde(default = "Config::disable")]
+    pub show_invisible: bool,
+    /// Show the names of the ancestors of current item at the top of the data block.
+    #[serde(default = "Config::disable")]
+    pub show_breadcrumbs: bool,
+    /// Remember the scroll positions of the items, and restore them when the items are
+    /// selected again.
+    #[serde(default = "Config::disable")]
+    pub remember_scroll: bool,
+    /// Keep the good parts when some lines of JSONL or documents of YAML fail to parse,
+    /// the bad ones are shown as error items.
+    #[serde(default = "Config::disable")]
+    pub partial_parse: bool,
+
+    /// Map custom file extensions to content types, e.g. `tpl = "yaml"`. This takes
+    /// precedence over the builtin extensions.
+    #[serde(default = "Config::empty_map")]
+    pub extensions: HashMap<String, ContentType>,
+
+    #[serde(default = "Json::default")]
+    pub json: Json,
+
+    #[serde(default = "Yaml::default")]
+    pub yaml: Yaml,
+}
+
+/// The format of the JSON (and JSONL) in the data block and the copied values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Json {
+    /// The number of spaces for each indentation level.
+    #[serde(default = "Json::default_indent")]
+    pub indent: usize,
+
+    /// The arrays of scalars are written in one line when it is not wider than this,
+    /// `0` always writes the items in separate lines.
+    #[serde(default = "Json::default_max_inline_width")]
+    pub max_inline_width: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Yaml {
+    /// The number of spaces for each indentation level.
+    #[serde(default = "Yaml::default_indent")]
+    pub indent: usize,
+
+    #[serde(default = "Yaml::default_quote_style")]
+    pub quote_style: QuoteStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    /// Only quote the strings that would be parsed as other types or are not plain
+    /// scalars, like `"true"`, `"123"` or `"with space"`.
+    Auto,
+    Double,
+    /// Fallback to double quotes for the strings with control characters, since single
+    /// quoted strings do not support escapes.
+    Single,
+    /// Keep the quotes of the strings in the source, others are quoted like `Auto`.
+    Preserve,
 }
 
 impl Config {
     pub const MIN_LAYOUT_TREE_SIZE: u16 = 10;
     pub const MAX_LAYOUT_TREE_SIZE: u16 = 80;
 
-    pub fn load(path: Option<String>) -> Result<Self> {
-        let path = Self::get_path(path).context("get config path")?;
-        if path.is_none() {
-            return Ok(Self::default());
+    pub const MIN_POPUP_PERCENT: u16 = 10;
+    pub const MAX_POPUP_PERCENT: u16 = 100;
+
+    pub const MIN_JSON_INDENT: usize = 1;
+    pub const MAX_JSON_INDENT: usize = 8;
+
+    pub const MIN_YAML_INDENT: usize = 2;
+    pub const MAX_YAML_INDENT: usize = 8;
+
+    /// The project-local config file, searched upward from the directory of the data.
+    const WORKSPACE_CONFIG: &'static str = ".otree.toml";
+
+    /// The options running programs or writing files are ignored in the workspace
+    /// config, so that opening a file in an untrusted repository is safe.
+    const WORKSPACE_IGNORED: [&'static str; 5] = ["editor", "pager", "hooks", "state", "export"];
+
+    /// Load the user config, and merge the workspace config (`.otree.toml` in `dir` or
+    /// its ancestors) over it.
+    pub fn load(path: Option<String>, profile: Option<&str>, dir: Option<&Path>) -> Result<Self> {
+        let mut table = None;
+        if let Some(path) = Self::get_path(path).context("get config path")? {
+            table = Some(Self::read_table(&path)?);
+        }
+        if let Some(path) = dir.and_then(Self::find_workspace) {
+            let mut workspace = Self::read_table(&path)?;
+            strip_workspace_table(&mut workspace);
+            match table.as_mut() {
+                Some(table) => merge_table(table, &workspace),
+                None => table = Some(workspace),
+            }
         }
-        let path = path.unwrap();
 
-        let data = fs::read_to_string(&path)
+        match table {
+            Some(table) => Self::from_table(table, profile),
+            None => match profile {
+                Some(profile) => bail!("cannot use profile '{profile}' without config file"),
+                None => Ok(Self::default()),
+            },
+        }
+    }
+
+    fn read_table(path: &Path) -> Result<toml::Table> {
+        let data = fs::read_to_string(path)
             .with_context(|| format!("read config file '{}'", path.display()))?;
+        toml::from_str(&data).with_context(|| format!("parse config toml '{}'", path.display()))
+    }
+
+    fn find_workspace(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(Self::WORKSPACE_CONFIG))
+            .find(|path| path.is_file())
+    }
+
+    /// Parse the config toml, see [`Config::from_table`].
+    #[cfg(test)]
+    fn from_toml(data: &str, profile: Option<&str>) -> Result<Self> {
+        let table: toml::Table = toml::from_str(data).context("parse config toml")?;
+        Self::from_table(table, profile)
+    }
+
+    /// The table `profile.{name}` overrides the options if the profile is selected. The
+    /// profiles not selected are ignored.
+    fn from_table(mut table: toml::Table, profile: Option<&str>) -> Result<Self> {
+        let profiles = table.remove("profile");
+        if let Some(name) = profile {
+            let profile = profiles
+                .as_ref()
+                .and_then(|profiles| profiles.get(name))
+                .and_then(toml::Value::as_table);
+            match profile {
+                Some(profile) => merge_table(&mut table, profile),
+                None => bail!("cannot find profile '{name}' in config file"),
+            }
+        }
 
-        toml::from_str(&data).context("parse config toml")
+        toml::Value::Table(table)
+            .try_into()
+            .context("parse config toml")
     }
 
     pub fn parse(&mut self) -> Result<()> {
@@ -123,12 +531,82 @@ impl Config {
             );
         }
 
+        for percent in [self.popup.width_percent, self.popup.height_percent] {
+            if !(Self::MIN_POPUP_PERCENT..=Self::MAX_POPUP_PERCENT).contains(&percent) {
+                bail!(
+                    "invalid popup size percent, should be between {} and {}",
+                    Self::MIN_POPUP_PERCENT,
+                    Self::MAX_POPUP_PERCENT
+                );
+            }
+        }
+
+        if self.tree.index_base > 1 {
+            bail!("invalid tree index base, should be 0 or 1");
+        }
+
+        if self.tree.expand_levels == 0 {
+            bail!("invalid tree expand levels, should be greater than 0");
+        }
+
+        if self.data.json.indent < Self::MIN_JSON_INDENT
+            || self.data.json.indent > Self::MAX_JSON_INDENT
+        {
+            bail!(
+                "invalid json indent, should be between {} and {}",
+                Self::MIN_JSON_INDENT,
+                Self::MAX_JSON_INDENT
+            );
+        }
+
+        if self.data.yaml.indent < Self::MIN_YAML_INDENT
+            || self.data.yaml.indent > Self::MAX_YAML_INDENT
+        {
+            bail!(
+                "invalid yaml indent, should be between {} and {}",
+                Self::MIN_YAML_INDENT,
+                Self::MAX_YAML_INDENT
+            );
+        }
+
+        if self.ui.ascii_only {
+            self.use_ascii_glyphs();
+        }
+
         self.validate_palette()?;
+        self.validate_renderers()?;
         self.colors.parse(&self.palette)?;
         self.keys.parse()?;
         Ok(())
     }
 
+    /// Replace the default glyphs with ASCII ones, the options changed by the user are
+    /// kept.
+    fn use_ascii_glyphs(&mut self) {
+        let replace = |option: &mut String, default: String, ascii: &str| {
+            if *option == default {
+                *option = String::from(ascii);
+            }
+        };
+        let tree = &mut self.tree;
+        replace(&mut tree.ellipsis, Tree::default_ellipsis(), "~");
+        replace(&mut tree.note_symbol, Tree::default_note_symbol(), "*");
+        replace(
+            &mut tree.node_closed_symbol,
+            Tree::default_node_closed_symbol(),
+            "> ",
+        );
+        replace(
+            &mut tree.node_open_symbol,
+            Tree::default_node_open_symbol(),
+            "v ",
+        );
+        replace(&mut self.mask.symbol, Mask::default_symbol(), "***");
+        let icons = &mut self.types.icons;
+        replace(&mut icons.null, Icons::default_null(), "~");
+        replace(&mut icons.bool, Icons::default_bool(), "?");
+    }
+
     fn validate_palette(&self) -> Result<()> {
         use ratatui::style::Color;
         for (key, color) in self.palette.iter() {
@@ -139,6 +617,15 @@ impl Config {
         Ok(())
     }
 
+    fn validate_renderers(&self) -> Result<()> {
+        for (rule, name) in self.renderers.iter() {
+            if render::find(name).is_none() {
+                bail!("unknown renderer '{name}' for '{rule}'");
+            }
+        }
+        Ok(())
+    }
+
     fn get_path(path: Option<String>) -> Result<Option<PathBuf>> {
         if let Some(path) = path {
             return Ok(Some(PathBuf::from(path)));
@@ -149,11 +636,10 @@ impl Config {
             return Ok(Some(PathBuf::from(path)));
         }
 
-        let home = dirs::home_dir();
-        if home.is_none() {
-            return Ok(None);
-        }
-        let home = home.unwrap();
+        let home = match dirs::home_dir() {
+            Some(home) => home,
+            None => return Ok(None),
+        };
 
         let path = home.join(".config").join("otree.toml");
         match fs::metadata(&path) {
@@ -164,36 +650,126 @@ impl Config {
         }
     }
 
-    pub fn default() -> Self {
+    pub fn show(&self) -> Result<()> {
+        let toml = toml::to_string(self).context("serialize config to toml")?;
+        println!("{toml}");
+        Ok(())
+    }
+
+    const fn disable() -> bool {
+        false
+    }
+
+    const fn enable() -> bool {
+        true
+    }
+
+    const fn default_max_data_size() -> usize {
+        30
+    }
+
+    const fn default_max_nodes() -> usize {
+        0
+    }
+
+    fn empty_string() -> String {
+        String::new()
+    }
+
+    fn empty_map<T>() -> HashMap<String, T> {
+        HashMap::new()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
         Self {
             editor: Editor::default(),
+            pager: Pager::default(),
             data: Data::default(),
             layout: Layout::default(),
+            ui: Ui::default(),
             header: Header::default(),
             footer: Footer::default(),
+            tree: Tree::default(),
+            popup: Popup::default(),
+            export: Export::default(),
+            hooks: Hooks::default(),
+            state: State::default(),
+            mask: Mask::default(),
             palette: Self::empty_map(),
+            renderers: Self::empty_map(),
             colors: Colors::default(),
             types: Types::default(),
             keys: Keys::default(),
         }
     }
+}
 
-    pub fn show(&self) -> Result<()> {
-        let toml = toml::to_string(self).context("serialize config to toml")?;
-        println!("{toml}");
-        Ok(())
+/// Remove the ignored options from the workspace config, and its profiles.
+fn strip_workspace_table(table: &mut toml::Table) {
+    for key in Config::WORKSPACE_IGNORED {
+        table.remove(key);
     }
-
-    const fn disable() -> bool {
-        false
+    if let Some(toml::Value::Table(profiles)) = table.get_mut("profile") {
+        for (_, profile) in profiles.iter_mut() {
+            if let toml::Value::Table(profile) = profile {
+                strip_workspace_table(profile);
+            }
+        }
     }
+}
 
-    const fn default_max_data_size() -> usize {
-        30
+/// Split the command line into words like the shell, the words can be quoted by `'` or
+/// `"`, and the characters can be escaped by `\` outside of the single quotes.
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    // Whether there is a word being built, the quoted empty string is a word too.
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(q), ch) if ch == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(ch) = chars.next() {
+                    word.push(ch);
+                }
+                in_word = true;
+            }
+            (Some(_), ch) => word.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, ch) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, ch) => {
+                word.push(ch);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
     }
+    words
+}
 
-    fn empty_map() -> HashMap<String, String> {
-        HashMap::new()
+/// Merge the `src` table into `dst` recursively, the values in `src` take precedence.
+fn merge_table(dst: &mut toml::Table, src: &toml::Table) {
+    for (key, value) in src {
+        match (dst.get_mut(key), value) {
+            (Some(toml::Value::Table(dst)), toml::Value::Table(src)) => merge_table(dst, src),
+            _ => {
+                dst.insert(key.clone(), value.clone());
+            }
+        }
     }
 }
 
@@ -206,11 +782,38 @@ impl Editor {
         }
     }
 
-    fn default_program() -> String {
-        if let Some(editor) = env::var_os("EDITOR") {
-            return editor.to_string_lossy().to_string();
+    /// The program with its leading arguments. The editor from env can contain arguments
+    /// (like `code --wait`), it is split like the shell words. The configured program is
+    /// used as is, so that its path can contain spaces.
+    pub fn get_command(&self) -> Vec<String> {
+        match Self::env_program() {
+            Some(editor) if editor == self.program => split_words(&editor),
+            _ => vec![self.program.clone()],
         }
-        String::from("vim")
+    }
+
+    fn env_program() -> Option<String> {
+        // `VISUAL` takes precedence over `EDITOR`, this is the convention followed by most
+        // programs.
+        for key in ["VISUAL", "EDITOR"] {
+            if let Some(editor) = env::var_os(key) {
+                let editor = editor.to_string_lossy();
+                if !editor.trim().is_empty() {
+                    return Some(editor.to_string());
+                }
+            }
+        }
+        None
+    }
+
+    fn default_program() -> String {
+        Self::env_program().unwrap_or_else(|| {
+            if cfg!(windows) {
+                String::from("notepad")
+            } else {
+                String::from("vim")
+            }
+        })
     }
 
     fn default_args() -> Vec<String> {
@@ -222,14 +825,44 @@ impl Editor {
     }
 }
 
+impl Pager {
+    fn default() -> Self {
+        Self {
+            program: Self::default_program(),
+            args: Self::default_args(),
+        }
+    }
+
+    fn default_program() -> String {
+        match env::var("PAGER") {
+            Ok(pager) if !pager.trim().is_empty() => pager,
+            _ => String::from("less"),
+        }
+    }
+
+    fn default_args() -> Vec<String> {
+        // The `PAGER` env usually contains its own arguments.
+        match env::var("PAGER") {
+            Ok(pager) if !pager.trim().is_empty() => vec![],
+            _ => vec![String::from("-R")],
+        }
+    }
+}
+
 impl Layout {
     fn default() -> Self {
         Self {
             direction: Self::default_direction(),
             tree_size: Self::default_tree_size(),
+            mode: Self::default_mode(),
+            auto_size: Config::disable(),
         }
     }
 
+    fn default_mode() -> LayoutMode {
+        LayoutMode::Split
+    }
+
     fn default_direction() -> LayoutDirection {
         LayoutDirection::Horizontal
     }
@@ -239,6 +872,22 @@ impl Layout {
     }
 }
 
+impl Ui {
+    fn default() -> Self {
+        Self {
+            mouse: Config::enable(),
+            focus_symbol: Config::empty_string(),
+            focus_border: Self::default_focus_border(),
+            set_title: Config::disable(),
+            ascii_only: Config::disable(),
+        }
+    }
+
+    fn default_focus_border() -> FocusBorder {
+        FocusBorder::Auto
+    }
+}
+
 impl Header {
     fn default() -> Self {
         Self {
@@ -260,11 +909,321 @@ impl Footer {
     }
 }
 
+impl Tree {
+    fn default() -> Self {
+        Self {
+            ellipsis: Self::default_ellipsis(),
+            sort_keys: Config::disable(),
+            note_symbol: Self::default_note_symbol(),
+            index_base: Self::default_index_base(),
+            index_padding: Config::disable(),
+            openapi_labels: Config::disable(),
+            show_icons: Config::disable(),
+            show_types: Config::enable(),
+            short_types: Config::disable(),
+            align_columns: Config::disable(),
+            empty_placeholder: Self::default_empty_placeholder(),
+            thousands_separator: Config::empty_string(),
+            expand_levels: Self::default_expand_levels(),
+            selected_symbol: Config::empty_string(),
+            selected_highlight: Config::enable(),
+            node_closed_symbol: Self::default_node_closed_symbol(),
+            node_open_symbol: Self::default_node_open_symbol(),
+            node_no_children_symbol: Self::default_node_no_children_symbol(),
+        }
+    }
+
+    fn default_empty_placeholder() -> String {
+        String::from("(empty)")
+    }
+
+    fn default_index_base() -> usize {
+        0
+    }
+
+    fn default_expand_levels() -> usize {
+        2
+    }
+
+    fn default_node_closed_symbol() -> String {
+        String::from("▶ ")
+    }
+
+    fn default_node_open_symbol() -> String {
+        String::from("▼ ")
+    }
+
+    fn default_node_no_children_symbol() -> String {
+        String::from("  ")
+    }
+
+    /// Get the name of the array item at `idx`, the `len` is the length of the array.
+    pub fn index_name(&self, idx: usize, len: usize) -> String {
+        let idx = idx + self.index_base;
+        if !self.index_padding {
+            return idx.to_string();
+        }
+        let last = (len + self.index_base).saturating_sub(1);
+        let width = last.to_string().len();
+        format!("{idx:0width$}")
+    }
+
+    fn default_ellipsis() -> String {
+        String::from("…")
+    }
+
+    fn default_note_symbol() -> String {
+        String::from("✎")
+    }
+}
+
+impl Popup {
+    fn default() -> Self {
+        Self {
+            width_percent: Self::default_percent(),
+            height_percent: Self::default_percent(),
+        }
+    }
+
+    fn default_percent() -> u16 {
+        50
+    }
+}
+
+impl State {
+    fn default() -> Self {
+        Self {
+            file: Config::empty_string(),
+        }
+    }
+}
+
+impl Mask {
+    fn default() -> Self {
+        Self {
+            enable: Config::disable(),
+            rules: Self::default_rules(),
+            symbol: Self::default_symbol(),
+            redact: Config::disable(),
+        }
+    }
+
+    fn default_rules() -> Vec<String> {
+        vec![
+            String::from("password"),
+            String::from("token"),
+            String::from("secret"),
+        ]
+    }
+
+    fn default_symbol() -> String {
+        String::from("•••")
+    }
+}
+
+impl Export {
+    fn default() -> Self {
+        Self {
+            dir: Editor::default_dir(),
+            format: Self::default_format(),
+            confirm_copy_size: Self::default_confirm_copy_size(),
+        }
+    }
+
+    fn default_format() -> ExportFormat {
+        ExportFormat::Ansi
+    }
+
+    const fn default_confirm_copy_size() -> usize {
+        10
+    }
+}
+
+impl Hooks {
+    fn default() -> Self {
+        Self {
+            on_select: Config::empty_string(),
+            on_start: Config::empty_string(),
+            debounce: Self::default_debounce(),
+            output: Self::default_output(),
+        }
+    }
+
+    fn default_debounce() -> u64 {
+        300
+    }
+
+    fn default_output() -> HookOutput {
+        HookOutput::Footer
+    }
+}
+
 impl Data {
     fn default() -> Self {
         Self {
             disable_highlight: Config::disable(),
             max_data_size: Config::default_max_data_size(),
+            auto_detect: Config::disable(),
+            truncate: Config::disable(),
+            max_nodes: Config::default_max_nodes(),
+            indent_guides: Config::disable(),
+            control_replacement: Self::default_control_replacement(),
+            show_invisible: Config::disable(),
+            show_breadcrumbs: Config::disable(),
+            remember_scroll: Config::disable(),
+            partial_parse: Config::disable(),
+            extensions: Config::empty_map(),
+            json: Json::default(),
+            yaml: Yaml::default(),
+        }
+    }
+
+    fn default_control_replacement() -> String {
+        String::from("\u{fffd}")
+    }
+}
+
+impl Json {
+    fn default() -> Self {
+        Self {
+            indent: Self::default_indent(),
+            max_inline_width: Self::default_max_inline_width(),
+        }
+    }
+
+    fn default_indent() -> usize {
+        2
+    }
+
+    const fn default_max_inline_width() -> usize {
+        0
+    }
+}
+
+impl Yaml {
+    fn default() -> Self {
+        Self {
+            indent: Self::default_indent(),
+            quote_style: Self::default_quote_style(),
+        }
+    }
+
+    fn default_indent() -> usize {
+        2
+    }
+
+    fn default_quote_style() -> QuoteStyle {
+        QuoteStyle::Auto
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_profile() {
+        let data = r#"
+[layout]
+direction = "vertical"
+tree_size = 30
+
+[header]
+disable = true
+
+[profile.logs.layout]
+tree_size = 60
+
+[profile.logs.footer]
+disable = true
+"#;
+        let cfg = Config::from_toml(data, None).unwrap();
+        assert_eq!(cfg.layout.tree_size, 30);
+        assert!(!cfg.footer.disable);
+
+        let cfg = Config::from_toml(data, Some("logs")).unwrap();
+        assert_eq!(cfg.layout.tree_size, 60);
+        assert!(matches!(cfg.layout.direction, LayoutDirection::Vertical));
+        assert!(cfg.header.disable);
+        assert!(cfg.footer.disable);
+
+        assert!(Config::from_toml(data, Some("k8s")).is_err());
+    }
+
+    #[test]
+    fn test_themes() {
+        for data in [
+            include_str!("../../config/themes/catppuccin.toml"),
+            include_str!("../../config/themes/high-contrast.toml"),
+        ] {
+            let mut cfg = Config::from_toml(data, None).unwrap();
+            cfg.parse().unwrap();
         }
     }
+
+    #[test]
+    fn test_workspace() {
+        let dir = env::temp_dir().join("otree-test-workspace");
+        let nested = dir.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        let user = dir.join("user.toml");
+        fs::write(
+            &user,
+            "[layout]\ntree_size = 30\n\n[header]\ndisable = true\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join(Config::WORKSPACE_CONFIG),
+            r#"
+[layout]
+tree_size = 50
+
+[hooks]
+on_start = "echo started"
+
+[profile.logs.footer]
+disable = true
+
+[profile.logs.editor]
+program = "evil"
+"#,
+        )
+        .unwrap();
+
+        let user = Some(user.display().to_string());
+        let cfg = Config::load(user.clone(), None, Some(&nested)).unwrap();
+        assert_eq!(cfg.layout.tree_size, 50);
+        assert!(cfg.header.disable);
+        assert!(cfg.hooks.on_start.is_empty());
+
+        let cfg = Config::load(user, Some("logs"), Some(&nested)).unwrap();
+        assert!(cfg.footer.disable);
+        assert_ne!(cfg.editor.program, "evil");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn test_editor_command() {
+        let test_cases = [
+            ("code --wait", vec!["code", "--wait"]),
+            ("  vim  ", vec!["vim"]),
+            (
+                r#""/Applications/Sublime Text.app/subl" -w"#,
+                vec!["/Applications/Sublime Text.app/subl", "-w"],
+            ),
+            (
+                r"my\ editor 'a b' '' x\'y",
+                vec!["my editor", "a b", "", "x'y"],
+            ),
+            (r#""a \"b\"""#, vec![r#"a "b""#]),
+        ];
+        for (s, expect) in test_cases {
+            assert_eq!(split_words(s), expect, "{s}");
+        }
+
+        // The configured program is not split.
+        let mut cfg = Config::default();
+        cfg.editor.program = String::from(r"C:\Program Files\otree test editor\edit.exe");
+        assert_eq!(cfg.editor.get_command(), [cfg.editor.program.clone()]);
+    }
 }
@@ -1,22 +1,28 @@
 use serde::{Deserialize, Serialize};
 
 macro_rules! generate_types_default {
-    ($StructName:ident, $($field:ident),+) => {
-        impl $StructName {
+    ($StructName:ident, $($field:ident => $value:expr),+ $(; $($extra:ident),+)?) => {
+        impl Default for $StructName {
             ::paste::paste! {
-                pub fn default() -> Self {
+                fn default() -> Self {
                     Self {
                         $(
                             $field: Self::[<default_ $field>](),
                         )+
+                        $($(
+                            $extra: Self::[<default_ $extra>](),
+                        )+)?
                     }
                 }
             }
+        }
+
+        impl $StructName {
 
             $(
                 ::paste::paste! {
                     pub fn [<default_ $field>]() -> String {
-                        String::from(stringify!($field))
+                        String::from($value)
                     }
                 }
             )+
@@ -43,6 +49,57 @@ pub struct Types {
 
     #[serde(default = "Types::default_obj")]
     pub obj: String,
+
+    /// The icons shown before the names when `tree.show_icons` is enabled, can be the
+    /// glyphs of nerd fonts.
+    #[serde(default = "Types::default_icons")]
+    pub icons: Icons,
+}
+
+generate_types_default!(
+    Types,
+    str => "str",
+    null => "null",
+    bool => "bool",
+    num => "num",
+    arr => "arr",
+    obj => "obj";
+    icons
+);
+
+impl Types {
+    fn default_icons() -> Icons {
+        Icons::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Icons {
+    #[serde(default = "Icons::default_str")]
+    pub str: String,
+
+    #[serde(default = "Icons::default_null")]
+    pub null: String,
+
+    #[serde(default = "Icons::default_bool")]
+    pub bool: String,
+
+    #[serde(default = "Icons::default_num")]
+    pub num: String,
+
+    #[serde(default = "Icons::default_arr")]
+    pub arr: String,
+
+    #[serde(default = "Icons::default_obj")]
+    pub obj: String,
 }
 
-generate_types_default!(Types, str, null, bool, num, arr, obj);
+generate_types_default!(
+    Icons,
+    str => "\"",
+    null => "∅",
+    bool => "◐",
+    num => "#",
+    arr => "[]",
+    obj => "{}"
+);
@@ -0,0 +1,140 @@
+use serde_json::{Number, Value};
+
+use crate::tree::escape_key;
+
+#[derive(Debug, PartialEq)]
+pub enum Diff<'a> {
+    Added(String, &'a Value),
+    Removed(String, &'a Value),
+    Changed(String, &'a Value, &'a Value),
+}
+
+/// Compare the values structurally, the objects are compared by keys and the arrays
+/// are compared by indexes. The paths are joined by `/` with escaped keys, same as the
+/// identifies of the tree items, the root path is empty.
+pub fn diff_values<'a>(old: &'a Value, new: &'a Value) -> Vec<Diff<'a>> {
+    let mut diffs = vec![];
+    diff_value(String::new(), old, new, &mut diffs);
+    diffs
+}
+
+fn diff_value<'a>(path: String, old: &'a Value, new: &'a Value, diffs: &mut Vec<Diff<'a>>) {
+    match (old, new) {
+        (Value::Object(old_obj), Value::Object(new_obj)) => {
+            for (key, old_value) in old_obj {
+                let sub_path = join_path(&path, key);
+                match new_obj.get(key) {
+                    Some(new_value) => diff_value(sub_path, old_value, new_value, diffs),
+                    None => diffs.push(Diff::Removed(sub_path, old_value)),
+                }
+            }
+            for (key, new_value) in new_obj {
+                if !old_obj.contains_key(key) {
+                    diffs.push(Diff::Added(join_path(&path, key), new_value));
+                }
+            }
+        }
+        (Value::Array(old_arr), Value::Array(new_arr)) => {
+            for (idx, old_value) in old_arr.iter().enumerate() {
+                let sub_path = join_path(&path, &idx.to_string());
+                match new_arr.get(idx) {
+                    Some(new_value) => diff_value(sub_path, old_value, new_value, diffs),
+                    None => diffs.push(Diff::Removed(sub_path, old_value)),
+                }
+            }
+            for (idx, new_value) in new_arr.iter().enumerate().skip(old_arr.len()) {
+                diffs.push(Diff::Added(join_path(&path, &idx.to_string()), new_value));
+            }
+        }
+        (Value::Number(old_num), Value::Number(new_num)) => {
+            if !number_equals(old_num, new_num) {
+                diffs.push(Diff::Changed(path, old, new));
+            }
+        }
+        _ => {
+            if old != new {
+                diffs.push(Diff::Changed(path, old, new));
+            }
+        }
+    }
+}
+
+/// The numbers keep their literals (like `1.50` and `1e2`), so they are compared by
+/// values. The integers are compared exactly, then the floats, then the literals.
+fn number_equals(old: &Number, new: &Number) -> bool {
+    if let (Some(old), Some(new)) = (old.as_i64(), new.as_i64()) {
+        return old == new;
+    }
+    if let (Some(old), Some(new)) = (old.as_u64(), new.as_u64()) {
+        return old == new;
+    }
+    match (old.as_f64(), new.as_f64()) {
+        (Some(old), Some(new)) => old == new,
+        _ => old == new,
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        return escape_key(key).into_owned();
+    }
+    format!("{path}/{}", escape_key(key))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_values() {
+        let old = json!({
+            "name": "otree",
+            "version": 1,
+            "tags": ["tui", "json"],
+            "removed": null,
+            "nested": {"a": true}
+        });
+        let new = json!({
+            "name": "otree",
+            "version": 2,
+            "tags": ["tui", "json", "yaml"],
+            "nested": {"a": true, "b": false},
+            "added": "x"
+        });
+
+        let diffs = diff_values(&old, &new);
+        assert_eq!(
+            diffs,
+            vec![
+                Diff::Changed(String::from("version"), &json!(1), &json!(2)),
+                Diff::Added(String::from("tags/2"), &json!("yaml")),
+                Diff::Removed(String::from("removed"), &Value::Null),
+                Diff::Added(String::from("nested/b"), &json!(false)),
+                Diff::Added(String::from("added"), &json!("x")),
+            ]
+        );
+
+        assert!(diff_values(&old, &old).is_empty());
+        assert_eq!(
+            diff_values(&json!(1), &json!("1")),
+            vec![Diff::Changed(String::new(), &json!(1), &json!("1"))]
+        );
+    }
+
+    #[test]
+    fn test_diff_numbers() {
+        let parse = |s: &str| serde_json::from_str::<Value>(s).unwrap();
+        assert!(diff_values(&parse("1.5"), &parse("1.50")).is_empty());
+        assert!(diff_values(&parse("100"), &parse("1e2")).is_empty());
+        assert!(diff_values(&parse("[-0, 2.0]"), &parse("[0, 2]")).is_empty());
+
+        let (old, new) = (parse("9007199254740993"), parse("9007199254740992"));
+        assert_eq!(
+            diff_values(&old, &new),
+            vec![Diff::Changed(String::new(), &old, &new)]
+        );
+        assert_eq!(diff_values(&parse("1.5"), &parse("1.6")).len(), 1);
+    }
+}
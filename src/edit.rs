@@ -1,40 +1,74 @@
-use std::fs;
-use std::io::{self, Read, Write};
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::process::{self, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 use anyhow::{bail, Context, Result};
 
 use crate::config::Config;
 
+/// The sequence of the edit files created by this process, together with the pid, it
+/// makes the file name unique between concurrent sessions and repeated edits.
+static EDIT_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// The edit file being edited, removed by [`cleanup`] when the process is terminated.
+static EDITING: Mutex<Option<String>> = Mutex::new(None);
+
 pub struct Edit {
     path: String,
     data: String,
     cmd: Command,
+
+    /// Whether the file is created by us, only then it is safe to be removed.
+    created: bool,
 }
 
 impl Edit {
-    pub fn new(cfg: &Config, identify: String, data: String, extension: &'static str) -> Self {
-        let mut cmd = Command::new(&cfg.editor.program);
+    pub fn new(
+        cfg: &Config,
+        identify: String,
+        data: String,
+        extension: &'static str,
+        line: usize,
+    ) -> Self {
+        let mut program = cfg.editor.get_command().into_iter();
+        let mut cmd = Command::new(program.next().unwrap_or_default());
+        cmd.args(program);
         cmd.stdin(Stdio::inherit());
-        cmd.stdout(Stdio::inherit());
+        // Like the TUI, the stdout is kept for the output on exit when it is redirected.
+        if io::stdout().is_terminal() {
+            cmd.stdout(Stdio::inherit());
+        } else {
+            cmd.stdout(io::stderr());
+        }
         cmd.stderr(Stdio::inherit());
 
         let name = identify.replace('/', "_");
-        let path = PathBuf::from(&cfg.editor.dir).join(format!("otree_{name}.{extension}"));
+        let pid = process::id();
+        let seq = EDIT_SEQ.fetch_add(1, Ordering::Relaxed);
+        let path =
+            PathBuf::from(&cfg.editor.dir).join(format!("otree_{pid}_{seq}_{name}.{extension}"));
         let path = format!("{}", path.display());
 
+        let line = line.to_string();
         for arg in cfg.editor.args.iter() {
-            if !arg.contains("{file}") {
+            if !arg.contains("{file}") && !arg.contains("{line}") {
                 cmd.arg(arg);
                 continue;
             }
 
-            let arg = arg.replace("{file}", &path);
+            let arg = arg.replace("{file}", &path).replace("{line}", &line);
             cmd.arg(arg);
         }
 
-        Self { path, data, cmd }
+        Self {
+            path,
+            data,
+            cmd,
+            created: false,
+        }
     }
 
     pub fn run(mut self) {
@@ -42,11 +76,11 @@ impl Edit {
             eprintln!("Edit error: {err:#}");
             eprintln!();
             eprintln!("Press any key to continue...");
-            io::stdout().flush().unwrap();
+            let _ = io::stdout().flush();
 
             // Wait for a single character input
             let mut buffer = [0; 1];
-            io::stdin().read_exact(&mut buffer).unwrap();
+            let _ = io::stdin().read_exact(&mut buffer);
         }
     }
 
@@ -63,7 +97,7 @@ impl Edit {
         Ok(())
     }
 
-    fn write_file(&self) -> Result<()> {
+    fn write_file(&mut self) -> Result<()> {
         let path = PathBuf::from(&self.path);
         if let Some(dir) = path.parent() {
             match fs::metadata(dir) {
@@ -83,7 +117,24 @@ impl Edit {
             }
         }
 
-        fs::write(&path, &self.data)
+        // The data may contain secrets, so the file is only readable by the current
+        // user. Never reuse an existing file, it may be a link planted by others.
+        let mut opts = OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let mut file = opts
+            .open(&path)
+            .with_context(|| format!("create file '{}'", path.display()))?;
+        self.created = true;
+        if let Ok(mut editing) = EDITING.lock() {
+            *editing = Some(self.path.clone());
+        }
+
+        file.write_all(self.data.as_bytes())
             .with_context(|| format!("write data to file '{}'", path.display()))?;
 
         Ok(())
@@ -97,7 +148,72 @@ impl Edit {
         Ok(())
     }
 
-    fn delete_file(&self) -> Result<()> {
+    fn delete_file(&mut self) -> Result<()> {
+        self.created = false;
+        if let Ok(mut editing) = EDITING.lock() {
+            *editing = None;
+        }
         fs::remove_file(&self.path).with_context(|| format!("delete file '{}'", self.path))
     }
 }
+
+/// Remove the edit file when the process is terminated during editing.
+pub fn cleanup() {
+    if let Some(path) = EDITING.lock().ok().and_then(|mut editing| editing.take()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+impl Drop for Edit {
+    fn drop(&mut self) {
+        // Make sure the file is not left behind when the editing is interrupted, like a
+        // panic.
+        if self.created {
+            let _ = self.delete_file();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_edit_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("otree_test_edit_{}", process::id()));
+        let mut cfg = Config::default();
+        cfg.editor.dir = format!("{}", dir.display());
+        let new_edit = || Edit::new(&cfg, String::from("a/b"), String::from("{}"), "json", 1);
+
+        let mut edit = new_edit();
+        edit.write_file().unwrap();
+        assert!(edit.path.ends_with("_a_b.json"));
+        assert_eq!(fs::read_to_string(&edit.path).unwrap(), "{}");
+        let mode = fs::metadata(&edit.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let path = edit.path.clone();
+        drop(edit);
+        assert!(!Path::new(&path).exists());
+
+        // The existing file is never reused or removed.
+        let mut edit = new_edit();
+        fs::write(&edit.path, "planted").unwrap();
+        assert!(edit.write_file().is_err());
+        let path = edit.path.clone();
+        drop(edit);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "planted");
+
+        let mut edit = new_edit();
+        edit.write_file().unwrap();
+        cleanup();
+        assert!(!Path::new(&edit.path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
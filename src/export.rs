@@ -0,0 +1,342 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Text;
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::{Config, ExportFormat};
+
+/// Write the rendered frame to a file in the configured directory, returns the path of
+/// the file.
+pub fn export_view(cfg: &Config, buffer: &Buffer) -> Result<String> {
+    let (text, extension) = match cfg.export.format {
+        ExportFormat::Ansi => (to_ansi(buffer), "ansi"),
+        ExportFormat::Html => (to_html(buffer), "html"),
+    };
+    write_export(cfg, "view", extension, text)
+}
+
+/// Write the JSON Schema to a file in the configured directory, returns the path of
+/// the file.
+pub fn export_schema(cfg: &Config, schema: &Value) -> Result<String> {
+    let text = serde_json::to_string_pretty(schema).context("encode json schema")?;
+    write_export(cfg, "schema", "json", text)
+}
+
+/// Write the text that is too large for the clipboard to a file in the configured
+/// directory, returns the path of the file.
+pub fn export_copy(cfg: &Config, text: String) -> Result<String> {
+    write_export(cfg, "copy", "txt", text)
+}
+
+fn write_export(cfg: &Config, name: &str, extension: &str, text: String) -> Result<String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = PathBuf::from(&cfg.export.dir).join(format!("otree_{name}_{now}.{extension}"));
+    let path = format!("{}", path.display());
+
+    fs::write(&path, text).with_context(|| format!("write {name} to file '{path}'"))?;
+    Ok(path)
+}
+
+/// Iterate the rows of the buffer, the cells hidden by the wide characters are skipped.
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<&Cell>> {
+    let width = buffer.area.width as usize;
+    buffer.content().chunks(width.max(1)).map(|row| {
+        let mut cells = Vec::with_capacity(row.len());
+        let mut hidden = 0;
+        for cell in row {
+            if hidden > 0 {
+                hidden -= 1;
+                continue;
+            }
+            hidden = cell.symbol().width().saturating_sub(1);
+            cells.push(cell);
+        }
+        cells
+    })
+}
+
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    a.fg == b.fg && a.bg == b.bg && a.modifier == b.modifier
+}
+
+fn to_ansi(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for row in rows(buffer) {
+        let mut last: Option<&Cell> = None;
+        for cell in row {
+            if !last.is_some_and(|last| same_style(last, cell)) {
+                push_ansi_style(&mut text, cell.fg, cell.bg, cell.modifier);
+            }
+            text.push_str(cell.symbol());
+            last = Some(cell);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Encode the styled text with ANSI escape sequences, like [`to_ansi`], for the
+/// programs that accept colored text, like `less -R`.
+pub fn text_to_ansi(text: &Text) -> String {
+    let mut ansi = String::new();
+    for line in text.lines.iter() {
+        for span in line.spans.iter() {
+            let style = text.style.patch(line.style).patch(span.style);
+            let fg = style.fg.unwrap_or(Color::Reset);
+            let bg = style.bg.unwrap_or(Color::Reset);
+            let modifier = style.add_modifier - style.sub_modifier;
+            push_ansi_style(&mut ansi, fg, bg, modifier);
+            ansi.push_str(&span.content);
+        }
+        ansi.push_str("\x1b[0m\n");
+    }
+    ansi
+}
+
+fn push_ansi_style(text: &mut String, fg: Color, bg: Color, modifiers: Modifier) {
+    text.push_str("\x1b[0");
+    push_ansi_color(text, fg, false);
+    push_ansi_color(text, bg, true);
+    for (modifier, code) in [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::REVERSED, 7),
+        (Modifier::HIDDEN, 8),
+        (Modifier::CROSSED_OUT, 9),
+    ] {
+        if modifiers.contains(modifier) {
+            let _ = write!(text, ";{code}");
+        }
+    }
+    text.push('m');
+}
+
+fn push_ansi_color(text: &mut String, color: Color, bg: bool) {
+    let offset = if bg { 10 } else { 0 };
+    let code = match color {
+        Color::Reset => return,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(idx) => {
+            let _ = write!(text, ";{};5;{idx}", 38 + offset);
+            return;
+        }
+        Color::Rgb(r, g, b) => {
+            let _ = write!(text, ";{};2;{r};{g};{b}", 38 + offset);
+            return;
+        }
+    };
+    let _ = write!(text, ";{}", code + offset);
+}
+
+fn to_html(buffer: &Buffer) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>otree</title></head>\n<body>\n<pre style=\"background-color: #000000; color: #e5e5e5; font-family: monospace;\">\n",
+    );
+    for row in rows(buffer) {
+        let mut last: Option<&Cell> = None;
+        for cell in row {
+            if !last.is_some_and(|last| same_style(last, cell)) {
+                if last.is_some() {
+                    html.push_str("</span>");
+                }
+                html.push_str("<span style=\"");
+                push_html_style(&mut html, cell);
+                html.push_str("\">");
+            }
+            for ch in cell.symbol().chars() {
+                match ch {
+                    '&' => html.push_str("&amp;"),
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    '"' => html.push_str("&quot;"),
+                    _ => html.push(ch),
+                }
+            }
+            last = Some(cell);
+        }
+        if last.is_some() {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+fn push_html_style(html: &mut String, cell: &Cell) {
+    let (mut fg, mut bg) = (html_color(cell.fg), html_color(cell.bg));
+    if cell.modifier.contains(Modifier::REVERSED) {
+        // The default colors are defined by the `pre` element.
+        (fg, bg) = (
+            bg.or(Some(String::from("#000000"))),
+            fg.or(Some(String::from("#e5e5e5"))),
+        );
+    }
+    if let Some(fg) = fg {
+        let _ = write!(html, "color: {fg}; ");
+    }
+    if let Some(bg) = bg {
+        let _ = write!(html, "background-color: {bg}; ");
+    }
+    if cell.modifier.contains(Modifier::BOLD) {
+        html.push_str("font-weight: bold; ");
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        html.push_str("opacity: 0.5; ");
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        html.push_str("font-style: italic; ");
+    }
+    if cell.modifier.contains(Modifier::UNDERLINED) {
+        html.push_str("text-decoration: underline; ");
+    } else if cell.modifier.contains(Modifier::CROSSED_OUT) {
+        html.push_str("text-decoration: line-through; ");
+    }
+}
+
+/// The basic colors follow the xterm palette.
+fn html_color(color: Color) -> Option<String> {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+
+    let idx = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(idx) => idx,
+    };
+
+    let (r, g, b) = match idx {
+        0..=15 => BASIC[idx as usize],
+        16..=231 => {
+            // 6x6x6 color cube.
+            let level = |n: u8| if n == 0 { 0 } else { n * 40 + 55 };
+            let n = idx - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        _ => {
+            // Grayscale ramp.
+            let level = (idx - 232) * 10 + 8;
+            (level, level, level)
+        }
+    };
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::layout::Rect;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+
+    use super::*;
+
+    fn test_buffer() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "a<", Style::default().red().bold());
+        buffer.set_string(2, 0, "b", Style::default());
+        buffer.set_string(0, 1, "中x", Style::default().on_blue());
+        buffer
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let expect = "\x1b[0;31;1ma<\x1b[0mb   \x1b[0m\n\x1b[0;44m中x\x1b[0m   \x1b[0m\n";
+        assert_eq!(to_ansi(&test_buffer()), expect);
+    }
+
+    #[test]
+    fn test_text_to_ansi() {
+        let text = Text::from(vec![
+            Line::from(vec![
+                Span::raw("{"),
+                Span::styled("1", Style::default().red()),
+            ]),
+            Line::from("}"),
+        ]);
+        let expect = "\x1b[0m{\x1b[0;31m1\x1b[0m\n\x1b[0m}\x1b[0m\n";
+        assert_eq!(text_to_ansi(&text), expect);
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = to_html(&test_buffer());
+        let expect = "<span style=\"color: #cd0000; font-weight: bold; \">a&lt;</span><span style=\"\">b   </span>\n<span style=\"background-color: #0000ee; \">中x</span><span style=\"\">   </span>\n";
+        assert!(html.contains(expect), "{html}");
+    }
+
+    #[test]
+    fn test_html_color() {
+        let test_cases = [
+            (Color::Reset, None),
+            (Color::Red, Some("#cd0000")),
+            (Color::Rgb(1, 2, 255), Some("#0102ff")),
+            (Color::Indexed(16), Some("#000000")),
+            (Color::Indexed(196), Some("#ff0000")),
+            (Color::Indexed(232), Some("#080808")),
+        ];
+
+        for (color, expect) in test_cases {
+            assert_eq!(html_color(color).as_deref(), expect);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+/// The bytes of the string if it looks binary: the base64 payload decoded to binary
+/// data, or the string itself containing many non-printable characters.
+pub fn get_binary(s: &str) -> Option<Vec<u8>> {
+    if let Some(bytes) = decode_base64(s) {
+        if is_binary(&bytes) {
+            return Some(bytes);
+        }
+    }
+    if is_binary(s.as_bytes()) {
+        return Some(s.as_bytes().to_vec());
+    }
+    None
+}
+
+/// Consider the data binary if it isn't valid utf8 or more than 1/10 of the characters
+/// are not printable (except the whitespaces).
+fn is_binary(bytes: &[u8]) -> bool {
+    let s = match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(_) => return true,
+    };
+    let total = s.chars().count();
+    let unprintable = s
+        .chars()
+        .filter(|ch| ch.is_control() && !matches!(ch, '\n' | '\r' | '\t'))
+        .count();
+    total > 0 && unprintable * 10 > total
+}
+
+/// The short strings are likely to be words rather than payloads.
+const MIN_BASE64_LEN: usize = 16;
+
+/// Decode the standard or url-safe base64, the padding is optional.
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim().trim_end_matches('=');
+    if s.len() < MIN_BASE64_LEN || s.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for ch in s.bytes() {
+        let value = match ch {
+            b'A'..=b'Z' => ch - b'A',
+            b'a'..=b'z' => ch - b'a' + 26,
+            b'0'..=b'9' => ch - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Dump the bytes like `hexdump -C`, with the offsets and the ASCII column.
+pub fn dump(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for (idx, chunk) in bytes.chunks(16).enumerate() {
+        let _ = write!(text, "{:08x} ", idx * 16);
+        for pos in 0..16 {
+            if pos == 8 {
+                text.push(' ');
+            }
+            match chunk.get(pos) {
+                Some(byte) => {
+                    let _ = write!(text, " {byte:02x}");
+                }
+                None => text.push_str("   "),
+            }
+        }
+        text.push_str("  |");
+        for byte in chunk {
+            let ch = if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            };
+            text.push(ch);
+        }
+        text.push_str("|\n");
+    }
+    let _ = write!(text, "{:08x}", bytes.len());
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_binary() {
+        assert_eq!(get_binary("hello world"), None);
+        // The decoded text is printable.
+        assert_eq!(get_binary("aGVsbG8gd29ybGQgaGVsbG8="), None);
+        assert_eq!(
+            get_binary("AAECAwQFBgcICQoLDA0ODw=="),
+            Some((0..16).collect())
+        );
+        assert_eq!(
+            get_binary("AAECAwQFBgcICQoLDA0ODw").map(|b| b.len()),
+            Some(16)
+        );
+        assert_eq!(
+            get_binary("\u{1b}[31m\u{0}\u{1}red").as_deref(),
+            Some("\u{1b}[31m\u{0}\u{1}red".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_dump() {
+        let bytes: Vec<u8> = b"Hello, world!\n\x00\x01\xffend".to_vec();
+        let expect = [
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |Hello, world!...|",
+            "00000010  ff 65 6e 64                                       |.end|",
+            "00000014",
+        ];
+        assert_eq!(dump(&bytes), expect.join("\n"));
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+/// Run the hook commands in background threads, so that the slow commands (like
+/// looking up something in a remote system) will not block the TUI.
+pub struct HookRunner {
+    sender: Sender<Result<String>>,
+    receiver: Receiver<Result<String>>,
+    running: usize,
+}
+
+impl HookRunner {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            running: 0,
+        }
+    }
+
+    /// Run the command by shell, the `stdin` will be written to the stdin of the command.
+    pub fn spawn(&mut self, cmd: String, stdin: Option<String>) {
+        let sender = self.sender.clone();
+        self.running += 1;
+        thread::spawn(move || {
+            let result = run(&cmd, stdin);
+            let _ = sender.send(result);
+        });
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running > 0
+    }
+
+    /// Get the output of a finished command.
+    pub fn try_recv(&mut self) -> Option<Result<String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => {
+                self.running -= 1;
+                Some(result)
+            }
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+/// Run the command by shell and wait for its output, the `stdin` will be written to the
+/// stdin of the command.
+pub fn run(cmd: &str, stdin: Option<String>) -> Result<String> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", cmd]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd]);
+        command
+    };
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    command.stdin(if stdin.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    });
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("launch command '{cmd}'"))?;
+    // Write the stdin in another thread while reading the output, otherwise the large
+    // data will fill both of the pipes and block the command and us forever.
+    let writer = match (stdin, child.stdin.take()) {
+        (Some(data), Some(mut child_stdin)) => Some(thread::spawn(move || {
+            // The command might not read the stdin, ignore the broken pipe error.
+            let _ = child_stdin.write_all(data.as_bytes());
+        })),
+        _ => None,
+    };
+
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("wait command '{cmd}'"))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "command '{cmd}' exited with {}: {}",
+            output.status,
+            stderr.trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{(\w+)\}").unwrap());
+
+/// Replace the placeholders like `{path}` in the command with the quoted values, the
+/// unknown placeholders are kept as is. This is done in one pass, so the placeholders
+/// inside the values will not be replaced again.
+pub fn render_command<F>(cmd: &str, get_value: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    PLACEHOLDER_RE
+        .replace_all(cmd, |caps: &Captures| match get_value(&caps[1]) {
+            Some(value) => shell_quote(&value),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Quote the value to be used as a single shell argument, so that the content of the
+/// document cannot inject commands.
+pub(crate) fn shell_quote(s: &str) -> String {
+    if cfg!(windows) {
+        return format!("\"{}\"", s.replace('"', "\"\""));
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run() {
+        let output = run("tr a-z A-Z", Some(String::from("hello"))).unwrap();
+        assert_eq!(output, "HELLO");
+
+        let value = "it's $(echo injected) {name}";
+        let cmd = render_command(
+            "printf '%s|%s|%s' {value} {name} {unknown}",
+            |name| match name {
+                "value" => Some(value.to_string()),
+                "name" => Some(String::from("name")),
+                _ => None,
+            },
+        );
+        let output = run(&cmd, None).unwrap();
+        assert_eq!(output, format!("{value}|name|{{unknown}}"));
+
+        assert!(run("exit 3", None).is_err());
+
+        // Larger than the pipe buffers.
+        let data = "0123456789abcdef\n".repeat(256 * 1024);
+        let output = run("cat", Some(data.clone())).unwrap();
+        assert_eq!(output, data);
+        run("true", Some(data)).unwrap();
+    }
+}
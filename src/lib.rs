@@ -0,0 +1,109 @@
+//! otree shows the structured data (json/yaml/toml) in a TUI tree widget. The viewer can
+//! be embedded in other programs with [`run_tui`]:
+//!
+//! ```no_run
+//! use otree::{run_tui, TuiOptions};
+//!
+//! let value = serde_json::json!({"name": "otree", "tags": ["tui", "tree"]});
+//! run_tui(value, TuiOptions::default()).unwrap();
+//! ```
+//!
+//! The data can also be parsed into a [`Tree`] and written with its [`Parser`] without
+//! the TUI:
+//!
+//! ```
+//! use otree::{Config, ContentType, Tree};
+//!
+//! let mut cfg = Config::default();
+//! cfg.parse().unwrap();
+//! let tree = Tree::parse(&cfg, "name: otree\ntags: [tui]\n", ContentType::Yaml).unwrap();
+//! let tags = tree.get_value("tags").unwrap();
+//! assert_eq!(tree.value_to_string("tags", &tags.value), "- tui\n");
+//! ```
+
+// #![warn(clippy::pedantic)]
+
+mod aggregate;
+mod cli;
+mod clipboard;
+mod cmd;
+mod config;
+mod diff;
+mod edit;
+mod export;
+mod hex;
+mod hook;
+mod mask;
+mod openapi;
+mod pager;
+mod parse;
+mod render;
+mod schema;
+mod spinner;
+mod state;
+mod tree;
+mod ui;
+
+use std::rc::Rc;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::cmd::PrintSelection;
+pub use crate::config::Config;
+pub use crate::parse::{ContentType, Parser, ScalarStyle, ScalarStyles, SyntaxToken};
+pub use crate::tree::{ItemValue, Tree};
+
+/// The options of [`run_tui`].
+pub struct TuiOptions {
+    /// The config of the viewer, it is parsed by [`run_tui`], so it can be deserialized
+    /// from a custom file or built from [`Config::default`].
+    pub config: Config,
+    /// The format to show the values in the data block and to copy or edit them.
+    pub content_type: ContentType,
+    /// The terminal title when `ui.set_title` is enabled.
+    pub title: Option<String>,
+}
+
+impl Default for TuiOptions {
+    fn default() -> Self {
+        Self {
+            config: Config::default(),
+            content_type: ContentType::Json,
+            title: None,
+        }
+    }
+}
+
+/// The entry point of the otree command, it is not a part of the library API.
+#[doc(hidden)]
+pub fn run_cli() -> Result<()> {
+    cli::run()
+}
+
+/// Show the value in the TUI until the user quits, returns the path of the item
+/// selected at that time. No panic hook or signal handler is installed, the terminal
+/// is left to the embedding program to restore in these cases.
+pub fn run_tui(value: Value, options: TuiOptions) -> Result<Option<String>> {
+    let mut cfg = options.config;
+    cfg.parse().context("parse config")?;
+
+    let parser = options.content_type.new_parser(&cfg);
+    let tree = Tree::from_value(
+        &cfg,
+        value,
+        ScalarStyles::default(),
+        Rc::new(parser),
+        cfg.tree.sort_keys,
+        cfg.mask.enable,
+    )
+    .context("build tree")?;
+
+    let mut app = ui::App::new(&cfg, tree);
+    if cfg.ui.set_title {
+        app.set_title(options.title.as_deref().unwrap_or("otree"));
+    }
+    ui::start(&cfg, &mut app)?;
+
+    Ok(app.get_selection(PrintSelection::Path))
+}
@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::tree::{escape_key, unescape_key};
+
+/// The rules to mask the sensitive values, from `mask.rules` in config. The rules
+/// starting with `/` are paths from the document root, others are matched against the
+/// field names case-insensitively, like `token` masks `access_token`.
+#[derive(Default)]
+pub struct Masks {
+    paths: HashSet<String>,
+    names: Vec<String>,
+}
+
+impl Masks {
+    pub fn new(cfg: &Config) -> Self {
+        let mut masks = Self::default();
+        if !cfg.mask.enable {
+            return masks;
+        }
+        for rule in cfg.mask.rules.iter() {
+            match rule.strip_prefix('/') {
+                Some(path) => {
+                    masks.paths.insert(path.to_string());
+                }
+                None => masks.names.push(rule.to_lowercase()),
+            }
+        }
+        masks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.names.is_empty()
+    }
+
+    /// Check the item by the path (identify) first, then the field name.
+    pub fn matches(&self, path: &str, name: &str) -> bool {
+        if self.paths.contains(path) {
+            return true;
+        }
+        if self.names.is_empty() {
+            return false;
+        }
+        let name = name.to_lowercase();
+        self.names.iter().any(|rule| name.contains(rule.as_str()))
+    }
+
+    /// Whether the item at the path, or any of its ancestors and descendants, is masked.
+    pub fn is_sensitive(&self, path: &str, value: &Value) -> bool {
+        self.matches_path(path) || self.contains(path, value)
+    }
+
+    /// Whether the item at the path, or any of its ancestors, is masked.
+    pub fn matches_path(&self, path: &str) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let mut ancestor = String::new();
+        for key in path.split('/') {
+            ancestor = join_path(&ancestor, &unescape_key(key));
+            if self.matches(&ancestor, &unescape_key(key)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether the value at the path has any masked field inside, the value itself is
+    /// not checked.
+    pub fn contains(&self, path: &str, value: &Value) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        match value {
+            Value::Array(arr) => arr.iter().enumerate().any(|(idx, item)| {
+                let name = idx.to_string();
+                let path = join_path(path, &name);
+                self.matches(&path, &name) || self.contains(&path, item)
+            }),
+            Value::Object(obj) => obj.iter().any(|(field, item)| {
+                let path = join_path(path, field);
+                self.matches(&path, field) || self.contains(&path, item)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Replace the masked fields inside the value with the mask symbol, return `None`
+    /// if there is nothing to mask, so that the value can be used as is.
+    pub fn apply(&self, cfg: &Config, path: &str, value: &Value) -> Option<Value> {
+        if !self.contains(path, value) {
+            return None;
+        }
+        let masked = match value {
+            Value::Array(arr) => Value::Array(
+                arr.iter()
+                    .enumerate()
+                    .map(|(idx, item)| self.apply_field(cfg, path, &idx.to_string(), item))
+                    .collect(),
+            ),
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(field, item)| (field.clone(), self.apply_field(cfg, path, field, item)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        };
+        Some(masked)
+    }
+
+    fn apply_field(&self, cfg: &Config, parent: &str, name: &str, value: &Value) -> Value {
+        let path = join_path(parent, name);
+        if self.matches(&path, name) {
+            return mask_value(cfg, value);
+        }
+        self.apply(cfg, &path, value)
+            .unwrap_or_else(|| value.clone())
+    }
+}
+
+/// Mask all the scalars in the value, the structure is kept so that the number of
+/// items and fields can still be seen.
+pub fn mask_value(cfg: &Config, value: &Value) -> Value {
+    match value {
+        Value::Array(arr) => Value::Array(arr.iter().map(|item| mask_value(cfg, item)).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(field, item)| (field.clone(), mask_value(cfg, item)))
+                .collect(),
+        ),
+        _ => Value::String(cfg.mask.symbol.clone()),
+    }
+}
+
+fn join_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        return escape_key(name).into_owned();
+    }
+    format!("{parent}/{}", escape_key(name))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_masks() {
+        let mut cfg = Config::default();
+        cfg.mask.enable = true;
+        cfg.mask.rules = vec![String::from("password"), String::from("/spec/key")];
+
+        let masks = Masks::new(&cfg);
+        assert!(masks.matches("db/Password", "Password"));
+        assert!(masks.matches("db/user_password", "user_password"));
+        assert!(masks.matches("spec/key", "key"));
+        assert!(!masks.matches("other/key", "key"));
+
+        let value = json!({"spec": {"key": "abc", "name": "otree"}, "users": [{"password": 123}]});
+        assert!(masks.contains("", &value));
+        assert!(!masks.contains("spec/name", &json!("otree")));
+        assert_eq!(
+            masks.apply(&cfg, "", &value).unwrap(),
+            json!({"spec": {"key": "•••", "name": "otree"}, "users": [{"password": "•••"}]})
+        );
+        assert!(masks.apply(&cfg, "", &json!({"name": "otree"})).is_none());
+        assert!(masks.is_sensitive("db/password/0", &json!("abc")));
+        assert!(!masks.is_sensitive("spec/name", &json!("otree")));
+
+        cfg.mask.enable = false;
+        assert!(Masks::new(&cfg).is_empty());
+    }
+}
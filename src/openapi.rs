@@ -0,0 +1,171 @@
+use serde_json::Value;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// An operation of the OpenAPI document, like `GET /pets`.
+pub struct Operation {
+    /// The key of the operation in the path item, like `get`.
+    pub method: String,
+    pub path: String,
+    pub summary: Option<String>,
+}
+
+impl Operation {
+    pub fn label(&self) -> String {
+        match self.summary.as_ref() {
+            Some(summary) => format!("{} {} · {summary}", self.method.to_uppercase(), self.path),
+            None => format!("{} {}", self.method.to_uppercase(), self.path),
+        }
+    }
+}
+
+/// Check if the document looks like OpenAPI (or Swagger 2.0), by its version field and
+/// the `paths` object.
+pub fn is_openapi(version: Option<&Value>, paths: Option<&Value>) -> bool {
+    matches!(version, Some(Value::String(_))) && matches!(paths, Some(Value::Object(_)))
+}
+
+/// List the operations under `paths`, in the document order.
+pub fn operations(paths: &Value) -> Vec<Operation> {
+    let paths = match paths.as_object() {
+        Some(paths) => paths,
+        None => return vec![],
+    };
+
+    let mut operations = vec![];
+    for (path, item) in paths {
+        let item = match item.as_object() {
+            Some(item) => item,
+            None => continue,
+        };
+        for (method, operation) in item {
+            if !METHODS.contains(&method.as_str()) {
+                continue;
+            }
+            operations.push(Operation {
+                method: method.clone(),
+                path: path.clone(),
+                summary: get_summary(operation),
+            });
+        }
+    }
+    operations
+}
+
+/// The smart label of the item at `parent/name`, shown in place of the value
+/// description in the tree overview. The `parent` is from the document root.
+pub fn label(parent: &[String], name: &str, value: &Value) -> Option<String> {
+    match parent {
+        [paths, path] if paths == "paths" && METHODS.contains(&name) => {
+            let operation = Operation {
+                method: name.to_string(),
+                path: path.clone(),
+                summary: get_summary(value),
+            };
+            Some(operation.label())
+        }
+        [paths, _, method, responses]
+            if paths == "paths"
+                && METHODS.contains(&method.as_str())
+                && responses == "responses" =>
+        {
+            let code = name.parse::<u16>().ok()?;
+            status_name(code).map(String::from)
+        }
+        _ => None,
+    }
+}
+
+fn get_summary(operation: &Value) -> Option<String> {
+    let summary = operation
+        .get("summary")
+        .or_else(|| operation.get("operationId"))?;
+    summary.as_str().map(String::from)
+}
+
+fn status_name(code: u16) -> Option<&'static str> {
+    let name = match code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        409 => "Conflict",
+        410 => "Gone",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_operations() {
+        let doc = json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": {
+                    "parameters": [],
+                    "get": {"summary": "List all pets", "responses": {"200": {}}},
+                    "post": {"operationId": "createPets"}
+                },
+                "/pets/{id}": {"delete": {}}
+            }
+        });
+        assert!(is_openapi(doc.get("openapi"), doc.get("paths")));
+        assert!(!is_openapi(None, doc.get("paths")));
+
+        let labels: Vec<_> = operations(&doc["paths"])
+            .iter()
+            .map(Operation::label)
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "GET /pets · List all pets",
+                "POST /pets · createPets",
+                "DELETE /pets/{id}",
+            ]
+        );
+
+        let parent = ["paths", "/pets", "get", "responses"].map(String::from);
+        assert_eq!(label(&parent, "200", &json!({})), Some(String::from("OK")));
+        assert_eq!(label(&parent, "default", &json!({})), None);
+        let parent = ["paths", "/pets"].map(String::from);
+        assert_eq!(
+            label(&parent, "get", &doc["paths"]["/pets"]["get"]),
+            Some(String::from("GET /pets · List all pets"))
+        );
+        assert_eq!(label(&parent, "parameters", &json!([])), None);
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::Config;
+
+/// Show the text in an external pager, like `less -R`, the text is written to the
+/// stdin of the pager.
+pub struct Pager {
+    text: String,
+    cmd: Command,
+}
+
+impl Pager {
+    pub fn new(cfg: &Config, text: String) -> Self {
+        // The program can contain arguments, like `less -R`, which is common in the
+        // `PAGER` env.
+        let mut program = cfg.pager.program.split_whitespace();
+        let mut cmd = Command::new(program.next().unwrap_or_default());
+        cmd.args(program);
+        cmd.args(&cfg.pager.args);
+        cmd.stdin(Stdio::piped());
+        // Like the TUI, the stdout is kept for the output on exit when it is redirected.
+        if io::stdout().is_terminal() {
+            cmd.stdout(Stdio::inherit());
+        } else {
+            cmd.stdout(io::stderr());
+        }
+        cmd.stderr(Stdio::inherit());
+
+        Self { text, cmd }
+    }
+
+    pub fn run(mut self) {
+        if let Err(err) = self._run() {
+            eprintln!("Pager error: {err:#}");
+            eprintln!();
+            eprintln!("Press any key to continue...");
+            let _ = io::stdout().flush();
+
+            // Wait for a single character input
+            let mut buffer = [0; 1];
+            let _ = io::stdin().read_exact(&mut buffer);
+        }
+    }
+
+    fn _run(&mut self) -> Result<()> {
+        let mut child = self.cmd.spawn().context("execute pager command")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The pager may quit before reading all the text, the broken pipe is expected.
+            if let Err(err) = stdin.write_all(self.text.as_bytes()) {
+                if err.kind() != io::ErrorKind::BrokenPipe {
+                    let _ = child.kill();
+                    return Err(err).context("write text to pager");
+                }
+            }
+        }
+
+        let status = child.wait().context("wait pager command")?;
+        if !status.success() {
+            bail!("pager command exited with bad code");
+        }
+        Ok(())
+    }
+}
@@ -1,83 +1,221 @@
 use anyhow::{Context, Result};
 use serde_json::Value;
 
-use super::{Parser, SyntaxToken};
+use crate::config::Config;
 
-pub(super) struct JsonParser {}
+use super::{Parser, ScalarStyles, SyntaxToken};
 
-impl Parser for JsonParser {
-    fn extension(&self) -> &'static str {
-        "json"
-    }
+/// The indentation and inline arrays of the JSON output, see `data.json` in config.
+#[derive(Debug, Clone)]
+pub(super) struct JsonFormat {
+    indent: usize,
+    max_inline_width: usize,
+}
 
-    fn parse(&self, data: &str) -> Result<Value> {
-        serde_json::from_str(data).context("parse JSON")
+impl Default for JsonFormat {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            max_inline_width: 0,
+        }
     }
+}
 
-    fn to_string(&self, value: &Value) -> String {
-        serde_json::to_string_pretty(value).expect("serialize JSON")
+impl JsonFormat {
+    pub(super) fn new(cfg: &Config) -> Self {
+        Self {
+            indent: cfg.data.json.indent,
+            max_inline_width: cfg.data.json.max_inline_width,
+        }
     }
 
-    fn syntax_highlight(&self, value: &Value) -> Vec<SyntaxToken> {
-        highlight(value, 0, false)
+    pub(super) fn indent_width(&self) -> usize {
+        self.indent
     }
-}
 
-pub(super) fn highlight(value: &Value, indent: usize, has_next: bool) -> Vec<SyntaxToken> {
-    let mut tokens = Vec::new();
+    /// Like `serde_json::to_string_pretty`, with the configured indentation and inline
+    /// arrays.
+    pub(super) fn to_string(&self, value: &Value) -> String {
+        let mut text = String::new();
+        self.write(&mut text, value, 0);
+        text
+    }
 
-    match value {
-        Value::Null => tokens.push(SyntaxToken::Null("null")),
-        Value::String(s) => tokens.push(SyntaxToken::String(format!("{s:?}"))),
-        Value::Number(num) => tokens.push(SyntaxToken::Number(num.to_string())),
-        Value::Bool(b) => {
-            let b = if *b { "true" } else { "false" };
-            tokens.push(SyntaxToken::Bool(b));
-        }
-        Value::Object(obj) => {
-            tokens.push(SyntaxToken::Symbol("{"));
-            if obj.is_empty() {
-                tokens.push(SyntaxToken::Symbol("}"));
-            } else {
-                tokens.push(SyntaxToken::Break);
+    fn write(&self, text: &mut String, value: &Value, level: usize) {
+        let indent = |text: &mut String, level: usize| {
+            text.push('\n');
+            text.push_str(&" ".repeat(level * self.indent));
+        };
+        match value {
+            Value::Object(obj) if !obj.is_empty() => {
+                text.push('{');
                 for (idx, (field, value)) in obj.iter().enumerate() {
-                    tokens.push(SyntaxToken::Indent(indent + 1));
-                    let field = format!("{field:?}");
-                    tokens.push(SyntaxToken::Name(field));
-                    tokens.push(SyntaxToken::Symbol(": "));
-
-                    let has_next = idx != obj.len() - 1;
-                    let value_tokens = highlight(value, indent + 1, has_next);
-                    tokens.extend(value_tokens);
+                    if idx > 0 {
+                        text.push(',');
+                    }
+                    indent(text, level + 1);
+                    text.push_str(&to_json(&Value::String(field.clone())));
+                    text.push_str(": ");
+                    self.write(text, value, level + 1);
                 }
-                tokens.push(SyntaxToken::Indent(indent));
-                tokens.push(SyntaxToken::Symbol("}"));
+                indent(text, level);
+                text.push('}');
             }
-        }
-        Value::Array(arr) => {
-            tokens.push(SyntaxToken::Symbol("["));
-            if arr.is_empty() {
-                tokens.push(SyntaxToken::Symbol("]"));
-            } else {
-                tokens.push(SyntaxToken::Break);
+            Value::Array(arr) if !arr.is_empty() => {
+                if let Some(items) = self.inline_items(arr) {
+                    text.push('[');
+                    text.push_str(&items.join(", "));
+                    text.push(']');
+                    return;
+                }
+                text.push('[');
                 for (idx, value) in arr.iter().enumerate() {
-                    tokens.push(SyntaxToken::Indent(indent + 1));
-                    let has_next = idx != arr.len() - 1;
-                    let value_tokens = highlight(value, indent + 1, has_next);
-                    tokens.extend(value_tokens);
+                    if idx > 0 {
+                        text.push(',');
+                    }
+                    indent(text, level + 1);
+                    self.write(text, value, level + 1);
                 }
-                tokens.push(SyntaxToken::Indent(indent));
-                tokens.push(SyntaxToken::Symbol("]"));
+                indent(text, level);
+                text.push(']');
             }
+            _ => text.push_str(&to_json(value)),
         }
     }
 
-    if has_next {
-        tokens.push(SyntaxToken::Symbol(","));
+    /// The items of the array to write in one line, `None` if the array contains
+    /// objects or arrays, or it is too wide.
+    fn inline_items(&self, arr: &[Value]) -> Option<Vec<String>> {
+        if self.max_inline_width == 0 {
+            return None;
+        }
+        let mut width = 2 + arr.len().saturating_sub(1) * 2;
+        let mut items = Vec::with_capacity(arr.len());
+        for value in arr {
+            if matches!(value, Value::Array(_) | Value::Object(_)) {
+                return None;
+            }
+            let item = to_json(value);
+            width += item.chars().count();
+            if width > self.max_inline_width {
+                return None;
+            }
+            items.push(item);
+        }
+        Some(items)
+    }
+
+    pub(super) fn highlight(
+        &self,
+        value: &Value,
+        level: usize,
+        has_next: bool,
+    ) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+
+        match value {
+            Value::Object(obj) => {
+                tokens.push(SyntaxToken::Symbol("{"));
+                if obj.is_empty() {
+                    tokens.push(SyntaxToken::Symbol("}"));
+                } else {
+                    tokens.push(SyntaxToken::Break);
+                    for (idx, (field, value)) in obj.iter().enumerate() {
+                        tokens.push(SyntaxToken::Indent((level + 1) * self.indent));
+                        let field = format!("{field:?}");
+                        tokens.push(SyntaxToken::Name(field));
+                        tokens.push(SyntaxToken::Symbol(": "));
+
+                        let has_next = idx != obj.len() - 1;
+                        let value_tokens = self.highlight(value, level + 1, has_next);
+                        tokens.extend(value_tokens);
+                    }
+                    tokens.push(SyntaxToken::Indent(level * self.indent));
+                    tokens.push(SyntaxToken::Symbol("}"));
+                }
+            }
+            Value::Array(arr) => {
+                tokens.push(SyntaxToken::Symbol("["));
+                if arr.is_empty() {
+                    tokens.push(SyntaxToken::Symbol("]"));
+                } else if self.inline_items(arr).is_some() {
+                    for (idx, value) in arr.iter().enumerate() {
+                        if idx > 0 {
+                            tokens.push(SyntaxToken::Symbol(", "));
+                        }
+                        tokens.push(scalar_token(value));
+                    }
+                    tokens.push(SyntaxToken::Symbol("]"));
+                } else {
+                    tokens.push(SyntaxToken::Break);
+                    for (idx, value) in arr.iter().enumerate() {
+                        tokens.push(SyntaxToken::Indent((level + 1) * self.indent));
+                        let has_next = idx != arr.len() - 1;
+                        let value_tokens = self.highlight(value, level + 1, has_next);
+                        tokens.extend(value_tokens);
+                    }
+                    tokens.push(SyntaxToken::Indent(level * self.indent));
+                    tokens.push(SyntaxToken::Symbol("]"));
+                }
+            }
+            _ => tokens.push(scalar_token(value)),
+        }
+
+        if has_next {
+            tokens.push(SyntaxToken::Symbol(","));
+        }
+        tokens.push(SyntaxToken::Break);
+
+        tokens
     }
-    tokens.push(SyntaxToken::Break);
+}
 
-    tokens
+pub(super) struct JsonParser {
+    format: JsonFormat,
+}
+
+impl JsonParser {
+    pub(super) fn new(cfg: &Config) -> Self {
+        Self {
+            format: JsonFormat::new(cfg),
+        }
+    }
+}
+
+impl Parser for JsonParser {
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn parse(&self, data: &str) -> Result<Value> {
+        serde_json::from_str(data).context("parse JSON")
+    }
+
+    fn to_string(&self, value: &Value, _styles: &ScalarStyles) -> String {
+        self.format.to_string(value)
+    }
+
+    fn syntax_highlight(&self, value: &Value, _styles: &ScalarStyles) -> Vec<SyntaxToken> {
+        self.format.highlight(value, 0, false)
+    }
+
+    fn indent_width(&self) -> usize {
+        self.format.indent_width()
+    }
+}
+
+fn scalar_token(value: &Value) -> SyntaxToken {
+    match value {
+        Value::String(s) => SyntaxToken::String(format!("{s:?}")),
+        Value::Number(num) => SyntaxToken::Number(num.to_string()),
+        Value::Bool(true) => SyntaxToken::Bool("true"),
+        Value::Bool(false) => SyntaxToken::Bool("false"),
+        _ => SyntaxToken::Null("null"),
+    }
+}
+
+fn to_json(value: &Value) -> String {
+    serde_json::to_string(value).expect("serialize JSON")
 }
 
 #[cfg(test)]
@@ -107,12 +245,16 @@ mod test {
                 include_str!("test_cases/json/empty.json"),
                 include_str!("test_cases/json/empty.json"),
             ),
+            (
+                include_str!("test_cases/json/numbers.json"),
+                include_str!("test_cases/json/numbers.json"),
+            ),
         ];
 
-        let parser = JsonParser {};
+        let parser = JsonParser::new(&Config::default());
         for (raw, expect) in test_cases {
             let value = parser.parse(raw).unwrap();
-            let tokens = parser.syntax_highlight(&value);
+            let tokens = parser.syntax_highlight(&value, &ScalarStyles::default());
             let result = SyntaxToken::pure_text(&tokens);
             assert_eq!(result, expect);
 
@@ -120,4 +262,25 @@ mod test {
             assert_eq!(value, highlight_value);
         }
     }
+
+    #[test]
+    fn test_format() {
+        let value: Value =
+            serde_json::from_str(include_str!("test_cases/json/array_of_objects.json")).unwrap();
+        let format = JsonFormat::default();
+        assert_eq!(
+            format.to_string(&value),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+
+        let format = JsonFormat {
+            indent: 4,
+            max_inline_width: 12,
+        };
+        let value = serde_json::json!({"ports": [80, 443], "names": ["a", "bcdefgh"], "empty": []});
+        let expect = "{\n    \"ports\": [80, 443],\n    \"names\": [\n        \"a\",\n        \"bcdefgh\"\n    ],\n    \"empty\": []\n}";
+        assert_eq!(format.to_string(&value), expect);
+        let tokens = format.highlight(&value, 0, false);
+        assert_eq!(SyntaxToken::pure_text(&tokens), format!("{expect}\n"));
+    }
 }
@@ -1,10 +1,22 @@
 use anyhow::{Context, Result};
 use serde_json::Value;
 
-use super::json;
-use super::{Parser, SyntaxToken};
+use crate::config::Config;
 
-pub(super) struct JsonlParser;
+use super::json::JsonFormat;
+use super::{Parser, ScalarStyles, SyntaxToken};
+
+pub(super) struct JsonlParser {
+    format: JsonFormat,
+}
+
+impl JsonlParser {
+    pub(super) fn new(cfg: &Config) -> Self {
+        Self {
+            format: JsonFormat::new(cfg),
+        }
+    }
+}
 
 impl Parser for JsonlParser {
     fn extension(&self) -> &'static str {
@@ -28,12 +40,41 @@ impl Parser for JsonlParser {
         Ok(Value::Array(objects))
     }
 
-    fn to_string(&self, value: &Value) -> String {
-        serde_json::to_string_pretty(value).expect("serialize JSON")
+    fn parse_partial(&self, data: &str) -> Result<(Value, ScalarStyles, Vec<usize>)> {
+        let mut objects = vec![];
+        let mut errors = vec![];
+        for (idx, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(object) => objects.push(object),
+                Err(err) => {
+                    errors.push(objects.len());
+                    objects.push(Value::String(format!("line {}: {err}", idx + 1)));
+                }
+            }
+        }
+        if !errors.is_empty() && errors.len() == objects.len() {
+            // Nothing to keep, report the error of the first line.
+            return self
+                .parse_styles(data)
+                .map(|(value, styles)| (value, styles, vec![]));
+        }
+        Ok((Value::Array(objects), ScalarStyles::default(), errors))
+    }
+
+    fn to_string(&self, value: &Value, _styles: &ScalarStyles) -> String {
+        self.format.to_string(value)
+    }
+
+    fn syntax_highlight(&self, value: &Value, _styles: &ScalarStyles) -> Vec<SyntaxToken> {
+        self.format.highlight(value, 0, false)
     }
 
-    fn syntax_highlight(&self, value: &Value) -> Vec<SyntaxToken> {
-        json::highlight(value, 0, false)
+    fn indent_width(&self) -> usize {
+        self.format.indent_width()
     }
 }
 
@@ -58,10 +99,10 @@ mod test {
             ),
         ];
 
-        let parser = JsonlParser {};
+        let parser = JsonlParser::new(&Config::default());
         for (raw, expect) in test_cases {
             let value = parser.parse(raw).unwrap();
-            let tokens = parser.syntax_highlight(&value);
+            let tokens = parser.syntax_highlight(&value, &ScalarStyles::default());
             let result = SyntaxToken::pure_text(&tokens);
             assert_eq!(result, expect);
         }
@@ -6,11 +6,17 @@ mod yaml;
 
 pub use syntax::SyntaxToken;
 
+use std::collections::HashMap;
+
 use anyhow::Result;
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ContentType {
     Json,
     Yaml,
@@ -20,23 +26,197 @@ pub enum ContentType {
     Jsonl,
 }
 
+/// How a scalar is written in the source, which is lost after converting to the JSON
+/// value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarStyle {
+    /// Written as is, like the TOML datetimes, which are strings in the JSON value.
+    Bare,
+    SingleQuoted,
+    DoubleQuoted,
+}
+
+/// The styles of the scalars in a document, by their paths from the root. They belong to
+/// the parsed document rather than the parser, so that the values can be written back
+/// the same way as the source.
+#[derive(Debug, Default, Clone)]
+pub struct ScalarStyles {
+    /// The style of the scalar at this path, with its value. The style is dropped if the
+    /// value is changed (like masked).
+    style: Option<(String, ScalarStyle)>,
+    children: HashMap<String, ScalarStyles>,
+}
+
+impl ScalarStyles {
+    fn insert(&mut self, path: &[String], value: String, style: ScalarStyle) {
+        let mut styles = self;
+        for key in path {
+            styles = styles.children.entry(key.clone()).or_default();
+        }
+        styles.style = Some((value, style));
+    }
+
+    /// The styles of the value at the path, the keys are not escaped.
+    pub fn get<S: AsRef<str>>(&self, path: &[S]) -> Option<&Self> {
+        let mut styles = self;
+        for key in path {
+            styles = styles.children.get(key.as_ref())?;
+        }
+        Some(styles)
+    }
+
+    fn child<'a>(styles: Option<&'a Self>, key: &str) -> Option<&'a Self> {
+        styles.and_then(|styles| styles.children.get(key))
+    }
+
+    fn style(styles: Option<&Self>, value: &str) -> Option<ScalarStyle> {
+        match styles?.style.as_ref()? {
+            (style_value, style) if style_value == value => Some(*style),
+            _ => None,
+        }
+    }
+}
+
 pub trait Parser {
     fn extension(&self) -> &'static str;
 
     fn parse(&self, data: &str) -> Result<Value>;
 
-    fn to_string(&self, value: &Value) -> String;
+    /// Parse the data with the styles of its scalars, only the formats that lose some
+    /// information in the JSON value have the styles.
+    fn parse_styles(&self, data: &str) -> Result<(Value, ScalarStyles)> {
+        self.parse(data)
+            .map(|value| (value, ScalarStyles::default()))
+    }
+
+    /// Parse the data and keep the good parts, each bad part is replaced with its error
+    /// message in the root array. Returns the value with the indexes of the bad parts.
+    /// Only the formats made of independent parts (like the lines of JSONL) can recover,
+    /// others fail like [`Parser::parse_styles`].
+    fn parse_partial(&self, data: &str) -> Result<(Value, ScalarStyles, Vec<usize>)> {
+        self.parse_styles(data)
+            .map(|(value, styles)| (value, styles, vec![]))
+    }
+
+    /// Write the value, the `styles` are the ones of the value, not the document.
+    fn to_string(&self, value: &Value, styles: &ScalarStyles) -> String;
+
+    fn syntax_highlight(&self, value: &Value, styles: &ScalarStyles) -> Vec<SyntaxToken>;
 
-    fn syntax_highlight(&self, value: &Value) -> Vec<SyntaxToken>;
+    /// The number of spaces for each indentation level in the highlight tokens.
+    fn indent_width(&self) -> usize;
 }
 
 impl ContentType {
-    pub fn new_parser(&self) -> Box<dyn Parser> {
+    /// The order to try parsers during detection. JSON must come before YAML since YAML
+    /// is the superset of JSON, and TOML is more strict than YAML, so it goes first too.
+    const DETECT_ORDER: [Self; 4] = [Self::Json, Self::Jsonl, Self::Toml, Self::Yaml];
+
+    pub fn new_parser(&self, cfg: &Config) -> Box<dyn Parser> {
         match self {
-            Self::Json => Box::new(json::JsonParser {}),
-            Self::Yaml => Box::new(yaml::YamlParser {}),
-            Self::Toml => Box::new(toml::TomlParser {}),
-            Self::Jsonl => Box::new(jsonl::JsonlParser {}),
+            Self::Json => Box::new(json::JsonParser::new(cfg)),
+            Self::Yaml => Box::new(yaml::YamlParser::new(cfg)),
+            Self::Toml => Box::<toml::TomlParser>::default(),
+            Self::Jsonl => Box::new(jsonl::JsonlParser::new(cfg)),
+        }
+    }
+
+    /// Guess the content type by trying to parse the data with each parser. This is
+    /// only a fallback, it is slow for large data and might be wrong for some ambiguous
+    /// content, so the file extension or the manually specified type is preferred.
+    pub fn detect(cfg: &Config, data: &str) -> Option<Self> {
+        if data.trim().is_empty() {
+            return None;
+        }
+
+        for content_type in Self::DETECT_ORDER {
+            let parser = content_type.new_parser(cfg);
+            let value = match parser.parse(data) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+
+            // Almost any text is a valid YAML scalar, so we only accept structured
+            // values here, except the JSON scalars like `"hello"` or `42`.
+            if content_type == Self::Json || matches!(value, Value::Array(_) | Value::Object(_)) {
+                return Some(content_type);
+            }
+        }
+
+        None
+    }
+}
+
+/// Truncate the data to at most `size` bytes, at the line boundary, returns the length of
+/// the truncated data. Line based formats (like JSONL) can still be parsed after this.
+pub fn truncate(data: &str, size: usize) -> usize {
+    if data.len() <= size {
+        return data.len();
+    }
+
+    match data.as_bytes()[..size].iter().rposition(|b| *b == b'\n') {
+        Some(pos) => pos + 1,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let test_cases = [
+            ("a\nb\nc\n", 100, 6),
+            ("a\nb\nc\n", 6, 6),
+            ("a\nb\nc\n", 5, 4),
+            ("a\nb\nc\n", 4, 4),
+            ("a\nb\nc\n", 3, 2),
+            ("abc", 2, 0),
+            ("中文\n中文\n", 10, 7),
+        ];
+
+        for (data, size, expect) in test_cases {
+            assert_eq!(truncate(data, size), expect);
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let test_cases = [
+            (
+                include_str!("test_cases/json/object.json"),
+                Some(ContentType::Json),
+            ),
+            (
+                include_str!("test_cases/json/2d_array.json"),
+                Some(ContentType::Json),
+            ),
+            (
+                include_str!("test_cases/jsonl/objects.jsonl"),
+                Some(ContentType::Jsonl),
+            ),
+            (
+                include_str!("test_cases/toml/basic.toml"),
+                Some(ContentType::Toml),
+            ),
+            (
+                include_str!("test_cases/yaml/common.yaml"),
+                Some(ContentType::Yaml),
+            ),
+            (
+                include_str!("test_cases/yaml/multidocs.yaml"),
+                Some(ContentType::Yaml),
+            ),
+            ("", None),
+            ("just some text", None),
+            ("\"hello\"\n", Some(ContentType::Json)),
+            ("42", Some(ContentType::Json)),
+        ];
+
+        let cfg = Config::default();
+        for (data, expect) in test_cases {
+            assert_eq!(ContentType::detect(&cfg, data), expect);
         }
     }
 }
@@ -1,8 +1,12 @@
+use std::mem;
+
 use once_cell::sync::Lazy;
+use ratatui::style::Style;
 use ratatui::text::{Line, Span, Text};
 use regex::Regex;
+use unicode_width::UnicodeWidthStr;
 
-use crate::config::Config;
+use crate::config::{Config, QuoteStyle};
 
 pub enum SyntaxToken {
     Symbol(&'static str),
@@ -16,61 +20,122 @@ pub enum SyntaxToken {
 
     Section(String),
 
+    /// The text with its own style, like the extra information from renderers.
+    Styled(String, Style),
+
     Break,
+    /// The number of spaces to indent, the parsers decide the width of each level.
     Indent(usize),
 }
 
 impl SyntaxToken {
-    pub fn render<'a>(cfg: &Config, tokens: &'a [SyntaxToken]) -> Text<'a> {
+    const INDENT_GUIDE: &'static str = "│";
+    const ASCII_INDENT_GUIDE: &'static str = "|";
+
+    pub fn render<'a>(cfg: &Config, tokens: &'a [SyntaxToken], indent_width: usize) -> Text<'a> {
         let mut lines: Vec<Line> = vec![];
-        let mut current_line = Some(Line::default());
+        let mut current_line = Line::default();
         for token in tokens {
             let (token, style) = match token {
                 Self::Symbol(sym) => (*sym, cfg.colors.data.symbol.style),
                 Self::Name(name) => (name.as_str(), cfg.colors.data.name.style),
                 Self::String(str) => (str.as_str(), cfg.colors.data.str.style),
-                Self::Number(num) => (num.as_str(), cfg.colors.data.num.style),
+                Self::Number(num) => (
+                    num.as_str(),
+                    cfg.colors
+                        .data
+                        .num
+                        .style
+                        .patch(cfg.colors.values.number_style(num)),
+                ),
                 Self::Null(null) => (*null, cfg.colors.data.null.style),
-                Self::Bool(b) => (*b, cfg.colors.data.bool.style),
+                Self::Bool(b) => (
+                    *b,
+                    cfg.colors
+                        .data
+                        .bool
+                        .style
+                        .patch(cfg.colors.values.bool_style(*b == "true")),
+                ),
                 Self::Section(sec) => (sec.as_str(), cfg.colors.data.section.style),
+                Self::Styled(text, style) => (text.as_str(), *style),
                 Self::Break => {
-                    let line = current_line.take().unwrap();
-                    lines.push(line);
-                    current_line = Some(Line::default());
+                    lines.push(mem::take(&mut current_line));
                     continue;
                 }
                 Self::Indent(indent) => {
-                    let indent = "  ".repeat(*indent);
-                    current_line.as_mut().unwrap().push_span(Span::raw(indent));
+                    // Only the indentation at the beginning of a line gets the guides, the
+                    // indents in the middle are paddings, like the one after YAML `-`.
+                    if cfg.data.indent_guides && indent_width > 0 && current_line.spans.is_empty() {
+                        Self::render_indent_guides(cfg, &mut current_line, *indent, indent_width);
+                    } else {
+                        current_line.push_span(Span::raw(" ".repeat(*indent)));
+                    }
                     continue;
                 }
             };
-            current_line
-                .as_mut()
-                .unwrap()
-                .push_span(Span::styled(token, style));
+            current_line.push_span(Span::styled(token, style));
         }
-        if current_line.as_ref().unwrap().width() > 0 {
-            let line = current_line.take().unwrap();
-            lines.push(line);
+        if current_line.width() > 0 {
+            lines.push(current_line);
         }
 
         Text::from(lines)
     }
 
+    /// The plain text of the tokens, without styles and indent guides.
+    pub fn plain(tokens: &[SyntaxToken]) -> String {
+        let mut text = String::new();
+        for token in tokens {
+            match token {
+                Self::Symbol(s) | Self::Null(s) | Self::Bool(s) => text.push_str(s),
+                Self::Name(s)
+                | Self::String(s)
+                | Self::Number(s)
+                | Self::Section(s)
+                | Self::Styled(s, _) => text.push_str(s),
+                Self::Break => text.push('\n'),
+                Self::Indent(indent) => text.push_str(&" ".repeat(*indent)),
+            }
+        }
+        text
+    }
+
+    fn render_indent_guides(cfg: &Config, line: &mut Line, indent: usize, indent_width: usize) {
+        let style = cfg.colors.data.indent_guide.style;
+        let guide = if cfg.ui.ascii_only {
+            Self::ASCII_INDENT_GUIDE
+        } else {
+            Self::INDENT_GUIDE
+        };
+        let padding = " ".repeat(indent_width - 1);
+        for _ in 0..indent / indent_width {
+            line.push_span(Span::styled(guide, style));
+            line.push_span(Span::raw(padding.clone()));
+        }
+        let rest = indent % indent_width;
+        if rest > 0 {
+            line.push_span(Span::raw(" ".repeat(rest)));
+        }
+    }
+
+    /// Get the rows and the max display columns of the tokens. The columns are measured in
+    /// display width rather than bytes, since CJK characters and emojis take two columns in
+    /// terminal, while they take three or four bytes.
     pub fn get_size(tokens: &[SyntaxToken]) -> (usize, usize) {
         let mut rows: usize = 0;
         let mut current_columns: usize = 0;
         let mut max_columns: usize = 0;
         for token in tokens {
             match token {
-                Self::Symbol(sym) => current_columns += sym.len(),
-                Self::Name(name) => current_columns += name.len(),
-                Self::String(str) => current_columns += str.len(),
-                Self::Number(num) => current_columns += num.len(),
-                Self::Null(null) => current_columns += null.len(),
-                Self::Bool(b) => current_columns += b.len(),
-                Self::Section(sec) => current_columns += sec.len(),
+                Self::Symbol(sym) => current_columns += sym.width(),
+                Self::Name(name) => current_columns += name.width(),
+                Self::String(str) => current_columns += str.width(),
+                Self::Number(num) => current_columns += num.width(),
+                Self::Null(null) => current_columns += null.width(),
+                Self::Bool(b) => current_columns += b.width(),
+                Self::Section(sec) => current_columns += sec.width(),
+                Self::Styled(text, _) => current_columns += text.width(),
                 Self::Break => {
                     if current_columns > max_columns {
                         max_columns = current_columns;
@@ -78,7 +143,7 @@ impl SyntaxToken {
                     current_columns = 0;
                     rows += 1;
                 }
-                Self::Indent(indent) => current_columns += 2 * indent,
+                Self::Indent(indent) => current_columns += indent,
             }
         }
 
@@ -92,7 +157,18 @@ impl SyntaxToken {
         (rows, max_columns)
     }
 
-    #[cfg(test)]
+    pub fn memory_size(&self) -> usize {
+        let data_size = match self {
+            Self::Name(s)
+            | Self::String(s)
+            | Self::Number(s)
+            | Self::Section(s)
+            | Self::Styled(s, _) => s.len(),
+            _ => 0,
+        };
+        mem::size_of::<Self>() + data_size
+    }
+
     pub(super) fn pure_text(tokens: &[SyntaxToken]) -> String {
         let mut text = String::new();
         for token in tokens {
@@ -104,10 +180,11 @@ impl SyntaxToken {
                 Self::Null(null) => null,
                 Self::Bool(b) => b,
                 Self::Section(sec) => sec.as_str(),
+                Self::Styled(text, _) => text.as_str(),
                 Self::Break => "\n",
                 Self::Indent(indent) => {
                     for _ in 0..*indent {
-                        text.push_str("  ");
+                        text.push(' ');
                     }
                     continue;
                 }
@@ -128,7 +205,7 @@ pub(super) fn quote_field_name(name: &str) -> String {
     } else {
         // Not a standard field name, quote it.
         // Like: "/some/path", "with space" (include empty string "")
-        format!("{name:?}")
+        StringValue::quote_string(name)
     }
 }
 
@@ -138,7 +215,7 @@ pub(super) enum StringValue {
 }
 
 impl StringValue {
-    pub(super) fn new(s: &str, must_quote: bool) -> Self {
+    pub(super) fn new(s: &str, quote_style: QuoteStyle) -> Self {
         if s.is_empty() {
             return StringValue::String("\"\"".to_string());
         }
@@ -155,14 +232,16 @@ impl StringValue {
             return StringValue::MultiLines(lines);
         }
 
-        if must_quote {
-            return StringValue::String(Self::quote_string(s));
+        match quote_style {
+            QuoteStyle::Double => return StringValue::String(Self::quote_string(s)),
+            QuoteStyle::Single => return StringValue::String(Self::single_quote_string(s)),
+            QuoteStyle::Auto | QuoteStyle::Preserve => {}
         }
 
-        if s == "true" || s == "false" || Self::is_numeric(s) {
-            // Special strings. Boolean or numeric. These strings should be quoted to prevent
-            // the schema parser from interpreting them as actual booleans or numbers, as
-            // they are actually strings.
+        if Self::is_keyword(s) || Self::is_numeric(s) {
+            // Special strings. Boolean, null or numeric. These strings should be quoted to
+            // prevent the schema parser from interpreting them as actual booleans, nulls or
+            // numbers, as they are actually strings.
             return StringValue::String(Self::quote_string(s));
         }
 
@@ -173,11 +252,126 @@ impl StringValue {
         }
     }
 
-    fn quote_string(s: &str) -> String {
-        format!("{s:?}")
+    /// Quote the string with double quotes. The escapes follow JSON, which are also valid
+    /// in YAML and TOML; the Rust debug format is not used here since it produces escapes
+    /// like `\u{1b}` that the schema parsers cannot read.
+    pub(super) fn quote_string(s: &str) -> String {
+        serde_json::to_string(s).expect("serialize string")
+    }
+
+    /// Single quoted strings cannot contain escapes, fallback to double quotes for the
+    /// strings that have control characters.
+    fn single_quote_string(s: &str) -> String {
+        if s.chars().any(char::is_control) {
+            return Self::quote_string(s);
+        }
+        format!("'{}'", s.replace('\'', "''"))
+    }
+
+    fn is_keyword(s: &str) -> bool {
+        matches!(
+            s,
+            "true" | "True" | "TRUE" | "false" | "False" | "FALSE" | "null" | "Null" | "NULL"
+        )
     }
 
     fn is_numeric(s: &str) -> bool {
         s.parse::<f64>().is_ok()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_size() {
+        let test_cases = [
+            (vec![SyntaxToken::String(String::from("hello"))], (1, 5)),
+            (vec![SyntaxToken::String(String::from("你好世界"))], (1, 8)),
+            (vec![SyntaxToken::String(String::from("héllo"))], (1, 5)),
+            (vec![SyntaxToken::String(String::from("a😀b"))], (1, 4)),
+            (
+                vec![
+                    SyntaxToken::Name(String::from("名字")),
+                    SyntaxToken::Symbol(": "),
+                    SyntaxToken::String(String::from("值")),
+                    SyntaxToken::Break,
+                    SyntaxToken::Indent(2),
+                    SyntaxToken::String(String::from("ascii")),
+                    SyntaxToken::Break,
+                ],
+                (2, 8),
+            ),
+        ];
+
+        for (tokens, expect) in test_cases {
+            assert_eq!(SyntaxToken::get_size(&tokens), expect);
+        }
+    }
+
+    #[test]
+    fn test_render_indent_guides() {
+        let mut cfg = Config::default();
+        cfg.data.indent_guides = true;
+
+        let tokens = [
+            SyntaxToken::Name(String::from("a")),
+            SyntaxToken::Symbol(":"),
+            SyntaxToken::Break,
+            SyntaxToken::Indent(4),
+            SyntaxToken::Symbol("-"),
+            SyntaxToken::Indent(1),
+            SyntaxToken::Name(String::from("b")),
+            SyntaxToken::Symbol(": "),
+            SyntaxToken::Number(String::from("1")),
+            SyntaxToken::Break,
+            SyntaxToken::Indent(3),
+            SyntaxToken::Name(String::from("c")),
+            SyntaxToken::Break,
+        ];
+        let text = SyntaxToken::render(&cfg, &tokens, 2);
+        let lines: Vec<_> = text.lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["a:", "│ │ - b: 1", "│  c"]);
+
+        cfg.data.indent_guides = false;
+        let text = SyntaxToken::render(&cfg, &tokens, 2);
+        let lines: Vec<_> = text.lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["a:", "    - b: 1", "   c"]);
+    }
+
+    #[test]
+    fn test_render_value_colors() {
+        use ratatui::style::Color;
+
+        let mut cfg = Config::default();
+        cfg.colors.values.negative.fg = Some(String::from("red"));
+        cfg.colors.values.zero.fg = Some(String::from("dark-gray"));
+        cfg.colors.values.bool_true.fg = Some(String::from("green"));
+        cfg.parse().unwrap();
+
+        let tokens = [
+            SyntaxToken::Number(String::from("-1.5")),
+            SyntaxToken::Number(String::from("0.0")),
+            SyntaxToken::Number(String::from("10")),
+            SyntaxToken::Bool("true"),
+            SyntaxToken::Bool("false"),
+        ];
+        let text = SyntaxToken::render(&cfg, &tokens, 0);
+        let colors: Vec<_> = text.lines[0]
+            .spans
+            .iter()
+            .map(|span| span.style.fg)
+            .collect();
+        assert_eq!(
+            colors,
+            [
+                Some(Color::Red),
+                Some(Color::DarkGray),
+                cfg.colors.data.num.style.fg,
+                Some(Color::Green),
+                cfg.colors.data.bool.style.fg,
+            ]
+        );
+    }
+}
@@ -2,11 +2,17 @@ use anyhow::{Context, Result};
 use serde_json::{Map, Number, Value};
 use toml::Value as TomlValue;
 
-use super::json::highlight as json_highlight;
+use crate::config::QuoteStyle;
+
+use super::json::JsonFormat;
 use super::syntax::{self, StringValue};
-use super::{Parser, SyntaxToken};
+use super::{Parser, ScalarStyle, ScalarStyles, SyntaxToken};
+
+/// The number of spaces for each indentation level of the arrays.
+const INDENT: usize = 2;
 
-pub(super) struct TomlParser {}
+#[derive(Default)]
+pub(super) struct TomlParser;
 
 impl Parser for TomlParser {
     fn extension(&self) -> &'static str {
@@ -14,24 +20,36 @@ impl Parser for TomlParser {
     }
 
     fn parse(&self, data: &str) -> Result<Value> {
+        self.parse_styles(data).map(|(value, _)| value)
+    }
+
+    fn parse_styles(&self, data: &str) -> Result<(Value, ScalarStyles)> {
         let toml_value: TomlValue = toml::from_str(data).context("parse TOML")?;
-        Ok(toml_value_to_json(toml_value))
+        let mut styles = ScalarStyles::default();
+        let value = toml_value_to_json(toml_value, &mut vec![], &mut styles);
+        Ok((value, styles))
     }
 
-    fn to_string(&self, value: &Value) -> String {
-        if let Value::Array(arr) = value {
-            // TOML does not support direct array, fallback to JSON schama.
-            return serde_json::to_string_pretty(arr).expect("serialize JSON");
-        }
-        toml::to_string_pretty(value).expect("serialize TOML")
+    fn to_string(&self, value: &Value, styles: &ScalarStyles) -> String {
+        // Use the same output as the data block, the TOML serializer cannot handle null
+        // and the array values.
+        SyntaxToken::pure_text(&self.syntax_highlight(value, styles))
     }
 
-    fn syntax_highlight(&self, value: &Value) -> Vec<SyntaxToken> {
-        if let Value::Array(_) = value {
+    fn syntax_highlight(&self, value: &Value, styles: &ScalarStyles) -> Vec<SyntaxToken> {
+        let styles = Some(styles);
+        let obj = match value {
             // TOML does not support direct array, fallback to JSON schama.
-            return json_highlight(value, 0, false);
-        }
-        let mut tokens = highlight(value, None, false, false);
+            Value::Array(_) => return JsonFormat::default().highlight(value, 0, false),
+            Value::Object(obj) => obj,
+            _ => {
+                let mut tokens = self.highlight_inline(value, styles, 0, false);
+                tokens.push(SyntaxToken::Break);
+                return tokens;
+            }
+        };
+
+        let mut tokens = self.highlight_table(obj, styles, None, false);
         if !tokens.is_empty() {
             // Trim the first break line
             let first_token = tokens.remove(0);
@@ -42,154 +60,243 @@ impl Parser for TomlParser {
 
         tokens
     }
+
+    fn indent_width(&self) -> usize {
+        INDENT
+    }
 }
 
-fn toml_value_to_json(toml_value: TomlValue) -> Value {
-    match toml_value {
-        TomlValue::String(s) => Value::String(s),
-        TomlValue::Integer(i) => Value::Number(Number::from(i)),
-        TomlValue::Float(f) => Value::Number(Number::from_f64(f).unwrap_or(Number::from(0))),
-        TomlValue::Boolean(b) => Value::Bool(b),
-        TomlValue::Datetime(datetime) => Value::String(datetime.to_string()),
-        TomlValue::Array(arr) => {
-            let mut json_arr = Vec::with_capacity(arr.len());
-            for toml_value in arr {
-                let value = toml_value_to_json(toml_value);
-                json_arr.push(value);
-            }
-            Value::Array(json_arr)
-        }
-        TomlValue::Table(table) => {
-            let mut json_obj = Map::with_capacity(table.len());
-            for (field, toml_value) in table {
-                let value = toml_value_to_json(toml_value);
-                json_obj.insert(field, value);
+impl TomlParser {
+    fn highlight_table(
+        &self,
+        obj: &Map<String, Value>,
+        styles: Option<&ScalarStyles>,
+        section: Option<&str>,
+        from_arr: bool,
+    ) -> Vec<SyntaxToken> {
+        // The simple fields must come before the sub tables, otherwise they will be parsed
+        // as the fields of the last sub table.
+        let (complex_fields, simple_fields): (Vec<_>, Vec<_>) =
+            obj.iter().partition(|(_, value)| is_value_complex(value));
+
+        let mut tokens = Vec::new();
+        if let Some(section) = section {
+            // The header of the table only contains sub tables can be omitted, the sub tables
+            // define it implicitly. Like `[profile]` before `[profile.release]`.
+            let implicit = !from_arr && simple_fields.is_empty() && !complex_fields.is_empty();
+            if !implicit {
+                let section = if from_arr {
+                    format!("[[{section}]]")
+                } else {
+                    format!("[{section}]")
+                };
+                tokens.push(SyntaxToken::Break);
+                tokens.push(SyntaxToken::Section(section));
+                tokens.push(SyntaxToken::Break);
             }
-            Value::Object(json_obj)
         }
-    }
-}
 
-fn highlight(
-    value: &Value,
-    section: Option<String>,
-    from_arr: bool,
-    arr_complex: bool,
-) -> Vec<SyntaxToken> {
-    let mut tokens = Vec::new();
-    if from_arr {
-        if let Some(section) = section.as_ref() {
-            tokens.push(SyntaxToken::Break);
-            tokens.push(SyntaxToken::Section(format!("[[{section}]]")));
+        for (field, value) in simple_fields {
+            let value_styles = ScalarStyles::child(styles, field);
+            let value_tokens = self.highlight_inline(value, value_styles, 0, false);
+
+            let field = syntax::quote_field_name(field);
+            tokens.push(SyntaxToken::Name(field));
+            tokens.push(SyntaxToken::Symbol(" = "));
+            tokens.extend(value_tokens);
             tokens.push(SyntaxToken::Break);
         }
-    }
 
-    match value {
-        // The TOML does not support null type, let's use empty string instead
-        Value::Null => tokens.push(SyntaxToken::String(String::from("\"\""))),
-        Value::String(s) => {
-            // TODO: Handle datetimes, they should not be quoted.
-            // See: <https://toml.io/en/v1.0.0#offset-date-time>
-            let value = StringValue::new(s, true);
+        for (field, value) in complex_fields {
+            let value_styles = ScalarStyles::child(styles, field);
+            let field = syntax::quote_field_name(field);
+            let child_section = match section {
+                Some(section) => format!("{section}.{field}"),
+                None => field,
+            };
+
             match value {
-                StringValue::String(s) => tokens.push(SyntaxToken::String(s)),
-                StringValue::MultiLines(lines) if !s.contains("'''") => {
-                    tokens.push(SyntaxToken::String(String::from("'''")));
-                    tokens.push(SyntaxToken::Break);
-                    for line in lines {
-                        tokens.push(SyntaxToken::String(line));
-                        tokens.push(SyntaxToken::Break);
+                Value::Object(obj) => {
+                    let value_tokens =
+                        self.highlight_table(obj, value_styles, Some(&child_section), false);
+                    tokens.extend(value_tokens);
+                }
+                Value::Array(arr) => {
+                    for (idx, value) in arr.iter().enumerate() {
+                        if let Value::Object(obj) = value {
+                            let item_styles = ScalarStyles::child(value_styles, &idx.to_string());
+                            let value_tokens =
+                                self.highlight_table(obj, item_styles, Some(&child_section), true);
+                            tokens.extend(value_tokens);
+                        }
                     }
-                    tokens.push(SyntaxToken::String(String::from("'''")));
                 }
-                _ => tokens.push(SyntaxToken::String(format!("{s:?}"))),
+                _ => unreachable!(),
             }
         }
-        Value::Number(num) => tokens.push(SyntaxToken::Number(num.to_string())),
-        Value::Bool(b) => {
-            let b = if *b { "true" } else { "false" };
-            tokens.push(SyntaxToken::Bool(b));
-        }
 
-        Value::Object(obj) => {
-            if !from_arr {
-                if let Some(section) = section.as_ref() {
-                    tokens.push(SyntaxToken::Break);
-                    tokens.push(SyntaxToken::Section(format!("[{section}]")));
+        tokens
+    }
+
+    /// Highlight the value that can be put at the right side of `=`. The arrays are split
+    /// into multiple lines like JSON, unless `single_line` is true, which is required inside
+    /// the inline tables.
+    fn highlight_inline(
+        &self,
+        value: &Value,
+        styles: Option<&ScalarStyles>,
+        indent: usize,
+        single_line: bool,
+    ) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+
+        match value {
+            // The TOML does not support null type, let's use empty string instead
+            Value::Null => tokens.push(SyntaxToken::String(String::from("\"\""))),
+            Value::String(s) if ScalarStyles::style(styles, s) == Some(ScalarStyle::Bare) => {
+                // The datetimes should not be quoted, otherwise they will become strings
+                // after parsing again.
+                // See: <https://toml.io/en/v1.0.0#offset-date-time>
+                tokens.push(SyntaxToken::Number(s.clone()));
+            }
+            Value::String(s) => {
+                let value = StringValue::new(s, QuoteStyle::Double);
+                match value {
+                    StringValue::String(s) => tokens.push(SyntaxToken::String(s)),
+                    StringValue::MultiLines(lines) if !single_line && is_literal(s) => {
+                        tokens.push(SyntaxToken::String(String::from("\'\'\'")));
+                        tokens.push(SyntaxToken::Break);
+                        for (idx, line) in lines.into_iter().enumerate() {
+                            if idx > 0 {
+                                tokens.push(SyntaxToken::Break);
+                            }
+                            tokens.push(SyntaxToken::String(line));
+                        }
+                        // Keep the closing quotes in the last line if there is no trailing
+                        // newline in the string.
+                        if s.ends_with('\n') {
+                            tokens.push(SyntaxToken::Break);
+                        }
+                        tokens.push(SyntaxToken::String(String::from("\'\'\'")));
+                    }
+                    _ => tokens.push(SyntaxToken::String(StringValue::quote_string(s))),
+                }
+            }
+            Value::Number(num) => tokens.push(SyntaxToken::Number(num.to_string())),
+            Value::Bool(b) => {
+                let b = if *b { "true" } else { "false" };
+                tokens.push(SyntaxToken::Bool(b));
+            }
+            Value::Array(arr) => {
+                tokens.push(SyntaxToken::Symbol("["));
+                if single_line {
+                    for (idx, value) in arr.iter().enumerate() {
+                        if idx > 0 {
+                            tokens.push(SyntaxToken::Symbol(", "));
+                        }
+                        let item_styles = ScalarStyles::child(styles, &idx.to_string());
+                        tokens.extend(self.highlight_inline(value, item_styles, indent, true));
+                    }
+                } else if !arr.is_empty() {
                     tokens.push(SyntaxToken::Break);
+                    for (idx, value) in arr.iter().enumerate() {
+                        tokens.push(SyntaxToken::Indent((indent + 1) * INDENT));
+                        let item_styles = ScalarStyles::child(styles, &idx.to_string());
+                        tokens.extend(self.highlight_inline(value, item_styles, indent + 1, false));
+                        if idx != arr.len() - 1 {
+                            tokens.push(SyntaxToken::Symbol(","));
+                        }
+                        tokens.push(SyntaxToken::Break);
+                    }
+                    tokens.push(SyntaxToken::Indent(indent * INDENT));
                 }
+                tokens.push(SyntaxToken::Symbol("]"));
             }
-
-            let mut complex_fields = Vec::new();
-            for (field, value) in obj {
-                let is_complex = is_value_complex(value);
-                if is_complex {
-                    complex_fields.push((field, value));
-                    continue;
+            Value::Object(obj) => {
+                if obj.is_empty() {
+                    tokens.push(SyntaxToken::Symbol("{}"));
+                    return tokens;
                 }
 
-                let field = syntax::quote_field_name(field);
-                tokens.push(SyntaxToken::Name(field));
-                tokens.push(SyntaxToken::Symbol(" = "));
+                // Inline tables are intended to appear on a single line.
+                // See: <https://toml.io/en/v1.0.0#inline-table>
+                tokens.push(SyntaxToken::Symbol("{ "));
+                for (idx, (field, value)) in obj.iter().enumerate() {
+                    if idx > 0 {
+                        tokens.push(SyntaxToken::Symbol(", "));
+                    }
+                    let value_styles = ScalarStyles::child(styles, field);
+                    let value_tokens = self.highlight_inline(value, value_styles, indent, true);
 
-                let value_tokens = highlight(value, None, false, false);
-                tokens.extend(value_tokens);
+                    let field = syntax::quote_field_name(field);
+                    tokens.push(SyntaxToken::Name(field));
+                    tokens.push(SyntaxToken::Symbol(" = "));
+                    tokens.extend(value_tokens);
+                }
+                tokens.push(SyntaxToken::Symbol(" }"));
             }
+        }
 
-            for (field, value) in complex_fields {
-                let field = syntax::quote_field_name(field);
-                let child_section = match section.as_ref() {
-                    Some(section) => format!("{section}.{field}"),
-                    None => field,
-                };
-
-                let value_tokens = highlight(value, Some(child_section), false, true);
-                tokens.extend(value_tokens);
-            }
+        tokens
+    }
+}
 
-            return tokens;
+/// Convert the TOML value to JSON, the TOML datetimes become strings, their styles are
+/// recorded to be written back without quotes.
+fn toml_value_to_json(
+    toml_value: TomlValue,
+    path: &mut Vec<String>,
+    styles: &mut ScalarStyles,
+) -> Value {
+    match toml_value {
+        TomlValue::String(s) => Value::String(s),
+        TomlValue::Integer(i) => Value::Number(Number::from(i)),
+        TomlValue::Float(f) => Value::Number(Number::from_f64(f).unwrap_or(Number::from(0))),
+        TomlValue::Boolean(b) => Value::Bool(b),
+        TomlValue::Datetime(datetime) => {
+            let datetime = datetime.to_string();
+            styles.insert(path, datetime.clone(), ScalarStyle::Bare);
+            Value::String(datetime)
         }
-
-        Value::Array(arr) => {
-            if !arr_complex {
-                // Simple array, we can use json schema.
-                let json_tokens = json_highlight(value, 0, false);
-                tokens.extend(json_tokens);
-                return tokens;
+        TomlValue::Array(arr) => {
+            let mut json_arr = Vec::with_capacity(arr.len());
+            for (idx, toml_value) in arr.into_iter().enumerate() {
+                path.push(idx.to_string());
+                json_arr.push(toml_value_to_json(toml_value, path, styles));
+                path.pop();
             }
-
-            // TOML does not support direct array. Section MUST be provided.
-            debug_assert!(section.is_some());
-
-            for value in arr {
-                let value_tokens = highlight(value, section.clone(), true, false);
-                tokens.extend(value_tokens);
+            Value::Array(json_arr)
+        }
+        TomlValue::Table(table) => {
+            let mut json_obj = Map::with_capacity(table.len());
+            for (field, toml_value) in table {
+                path.push(field.clone());
+                let value = toml_value_to_json(toml_value, path, styles);
+                path.pop();
+                json_obj.insert(field, value);
             }
-
-            return tokens;
+            Value::Object(json_obj)
         }
     }
-
-    tokens.push(SyntaxToken::Break);
-    tokens
 }
 
+/// The complex values are written as sections, the tables use `[section]` and the arrays
+/// of tables use `[[section]]`. Other arrays are written inline, the tables inside them
+/// become inline tables.
 fn is_value_complex(value: &Value) -> bool {
     match value {
         Value::Object(_) => true,
-        Value::Array(arr) => {
-            for value in arr {
-                if is_value_complex(value) {
-                    return true;
-                }
-            }
-            false
-        }
+        Value::Array(arr) => !arr.is_empty() && arr.iter().all(Value::is_object),
         _ => false,
     }
 }
 
+/// The multi-line literal strings cannot contain `\'\'\'` and control characters other
+/// than tab.
+fn is_literal(s: &str) -> bool {
+    !s.contains("\'\'\'") && !s.chars().any(|c| c.is_control() && c != '\t' && c != '\n')
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -213,12 +320,20 @@ mod test {
                 include_str!("test_cases/toml/2d_array.toml"),
                 include_str!("test_cases/toml/2d_array_highlight.toml"),
             ),
+            (
+                include_str!("test_cases/toml/cargo.toml"),
+                include_str!("test_cases/toml/cargo_highlight.toml"),
+            ),
+            (
+                include_str!("test_cases/toml/wrangler.toml"),
+                include_str!("test_cases/toml/wrangler_highlight.toml"),
+            ),
         ];
 
-        let parser = TomlParser {};
+        let parser = TomlParser;
         for (raw, expect) in test_cases {
-            let value = parser.parse(raw).unwrap();
-            let tokens = parser.syntax_highlight(&value);
+            let (value, styles) = parser.parse_styles(raw).unwrap();
+            let tokens = parser.syntax_highlight(&value, &styles);
             let result = SyntaxToken::pure_text(&tokens);
             assert_eq!(result, expect);
 
@@ -226,4 +341,52 @@ mod test {
             assert_eq!(value, highlight_value);
         }
     }
+
+    #[test]
+    fn test_to_string() {
+        let parser = TomlParser;
+        let (value, styles) = parser
+            .parse_styles(include_str!("test_cases/toml/cargo.toml"))
+            .unwrap();
+        let Value::Object(mut obj) = value else {
+            panic!("expect object");
+        };
+        // TOML does not have null, it should not break the serialization.
+        obj.insert(String::from("null"), Value::Null);
+        let value = Value::Object(obj);
+
+        let result = parser.to_string(&value, &styles);
+        assert!(result.contains("published = 2024-06-01T10:30:00Z\n"));
+        assert!(result.contains("null = \"\"\n"));
+        assert!(parser.parse(&result).is_ok());
+
+        // The styles of the subtree.
+        let package = value.get("package").unwrap();
+        let package_styles = styles.get(&["package"]).unwrap();
+        let result = parser.to_string(package, package_styles);
+        assert!(result.contains("published = 2024-06-01T10:30:00Z\n"));
+
+        // The changed value (like masked) is a normal string.
+        let mut package = package.clone();
+        package["published"] = Value::String(String::from("***"));
+        let result = parser.to_string(&package, package_styles);
+        assert!(result.contains("published = \"***\"\n"));
+    }
+
+    #[test]
+    fn test_datetime_like_string() {
+        let parser = TomlParser;
+        let raw = "date = 1979-05-27T07:32:00Z\nnote = \"1979-05-27T07:32:00Z\"\n";
+        let (value, styles) = parser.parse_styles(raw).unwrap();
+        let result = parser.to_string(&value, &styles);
+        assert_eq!(result, raw);
+
+        // Parsing another document does not affect the styles of this one.
+        parser.parse_styles("other = 1").unwrap();
+        assert_eq!(parser.to_string(&value, &styles), raw);
+
+        // The datetimes are unknown without the styles.
+        let result = parser.to_string(&value, &ScalarStyles::default());
+        assert!(result.contains("date = \"1979-05-27T07:32:00Z\"\n"));
+    }
 }
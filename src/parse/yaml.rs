@@ -1,11 +1,121 @@
+use std::borrow::Cow;
+
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
 use serde_json::Value;
+use serde_yml::libyml::parser::{Event, Parser as EventParser, ScalarStyle as EventStyle};
+
+use crate::config::{Config, QuoteStyle};
 
 use super::syntax::{self, StringValue};
-use super::{Parser, SyntaxToken};
+use super::{Parser, ScalarStyle, ScalarStyles, SyntaxToken};
+
+/// Split the multi-document YAML by the `---` lines, the empty documents are skipped.
+/// This is only used to recover from the bad documents, the separators inside the
+/// values (like in a multi-line quoted string) are not taken care of.
+fn split_documents(data: &str) -> Vec<&str> {
+    let mut documents = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+    for line in data.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed.starts_with("--- ") {
+            documents.push(&data[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    documents.push(&data[start..]);
+    documents
+        .into_iter()
+        .filter(|document| {
+            document
+                .lines()
+                .any(|line| !line.trim().is_empty() && line.trim_end() != "---")
+        })
+        .collect()
+}
+
+/// The quoted strings in the source, by their paths from the root (the index of the
+/// document comes first if there are many). Only the quotes of the values are recorded,
+/// the keys are always quoted when required.
+fn source_quotes(data: &str) -> ScalarStyles {
+    enum Frame {
+        /// The key of the value being parsed, `None` means that a key is expected.
+        Mapping(Option<String>),
+        Sequence(usize),
+    }
+
+    /// Move to the next item after parsing a value.
+    fn advance(frames: &mut [Frame]) {
+        match frames.last_mut() {
+            Some(Frame::Mapping(key)) => *key = None,
+            Some(Frame::Sequence(idx)) => *idx += 1,
+            None => {}
+        }
+    }
+
+    /// The path of the value being parsed, prefixed with the index of the document.
+    fn current_path(frames: &[Frame], document: usize) -> Vec<String> {
+        let keys = frames.iter().filter_map(|frame| match frame {
+            Frame::Mapping(key) => key.clone(),
+            Frame::Sequence(idx) => Some(idx.to_string()),
+        });
+        [document.to_string()].into_iter().chain(keys).collect()
+    }
 
-pub(super) struct YamlParser {}
+    let mut parser = EventParser::new(Cow::Borrowed(data.as_bytes()));
+    let mut quotes = vec![];
+    let mut frames = vec![];
+    let mut documents: usize = 0;
+    while let Ok((event, _)) = parser.parse_next_event() {
+        match event {
+            Event::StreamEnd => break,
+            Event::DocumentStart => {
+                documents += 1;
+                frames.clear();
+            }
+            Event::Scalar(scalar) => {
+                let value = String::from_utf8_lossy(&scalar.value).into_owned();
+                if let Some(Frame::Mapping(key @ None)) = frames.last_mut() {
+                    *key = Some(value);
+                    continue;
+                }
+                let style = match scalar.style {
+                    EventStyle::SingleQuoted => Some(ScalarStyle::SingleQuoted),
+                    EventStyle::DoubleQuoted => Some(ScalarStyle::DoubleQuoted),
+                    _ => None,
+                };
+                if let Some(style) = style {
+                    let path = current_path(&frames, documents.saturating_sub(1));
+                    quotes.push((path, value, style));
+                }
+                advance(&mut frames);
+            }
+            Event::Alias(_) => advance(&mut frames),
+            Event::MappingStart(_) => frames.push(Frame::Mapping(None)),
+            Event::SequenceStart(_) => frames.push(Frame::Sequence(0)),
+            Event::MappingEnd | Event::SequenceEnd => {
+                frames.pop();
+                advance(&mut frames);
+            }
+            _ => {}
+        }
+    }
+
+    let mut styles = ScalarStyles::default();
+    for (path, value, style) in quotes {
+        // The single document is the root value.
+        let path = if documents == 1 { &path[1..] } else { &path };
+        styles.insert(path, value, style);
+    }
+    styles
+}
+
+pub(super) struct YamlParser {
+    indent: usize,
+    quote_style: QuoteStyle,
+}
 
 impl Parser for YamlParser {
     fn extension(&self) -> &'static str {
@@ -30,11 +140,52 @@ impl Parser for YamlParser {
         Ok(Value::Array(values))
     }
 
-    fn to_string(&self, value: &Value) -> String {
-        serde_yml::to_string(value).expect("serialize YAML")
+    fn parse_styles(&self, data: &str) -> Result<(Value, ScalarStyles)> {
+        let value = self.parse(data)?;
+        let styles = if self.quote_style == QuoteStyle::Preserve {
+            source_quotes(data)
+        } else {
+            ScalarStyles::default()
+        };
+        Ok((value, styles))
+    }
+
+    fn parse_partial(&self, data: &str) -> Result<(Value, ScalarStyles, Vec<usize>)> {
+        let err = match self.parse_styles(data) {
+            Ok((value, styles)) => return Ok((value, styles, vec![])),
+            Err(err) => err,
+        };
+
+        let documents = split_documents(data);
+        if documents.len() < 2 {
+            return Err(err);
+        }
+        let mut values = Vec::with_capacity(documents.len());
+        let mut errors = vec![];
+        for (idx, document) in documents.into_iter().enumerate() {
+            let deserializer = serde_yml::Deserializer::from_str(document);
+            match Value::deserialize(deserializer) {
+                Ok(value) => values.push(value),
+                Err(doc_err) => {
+                    errors.push(values.len());
+                    values.push(Value::String(format!("document {}: {doc_err}", idx + 1)));
+                }
+            }
+        }
+        if errors.len() == values.len() {
+            return Err(err);
+        }
+        Ok((Value::Array(values), ScalarStyles::default(), errors))
     }
 
-    fn syntax_highlight(&self, value: &Value) -> Vec<SyntaxToken> {
+    fn to_string(&self, value: &Value, styles: &ScalarStyles) -> String {
+        // Use the same output as the data block, so that the copied or edited snippets
+        // follow the configured indent and quote style. The multi documents are not used
+        // here since the value might be a nested array.
+        SyntaxToken::pure_text(&self.highlight(value, Some(styles), 0, false))
+    }
+
+    fn syntax_highlight(&self, value: &Value, styles: &ScalarStyles) -> Vec<SyntaxToken> {
         if let Value::Array(arr) = value {
             if arr.is_empty() {
                 return vec![SyntaxToken::Symbol("[]")];
@@ -50,106 +201,163 @@ impl Parser for YamlParser {
 
             if !has_complex {
                 // For non-complex array, we don't need to use multi-documents.
-                return highlight(value, 0, false);
+                return self.highlight(value, Some(styles), 0, false);
             }
 
             // YAML Multi Documents
             // See: <https://gettaurus.org/docs/YAMLTutorial/#YAML-Multi-Documents>
             let mut tokens = Vec::new();
-            for value in arr {
+            for (idx, value) in arr.iter().enumerate() {
                 tokens.push(SyntaxToken::Symbol("---"));
                 tokens.push(SyntaxToken::Break);
-                let document_tokens = highlight(value, 0, false);
+                let document_styles = ScalarStyles::child(Some(styles), &idx.to_string());
+                let document_tokens = self.highlight(value, document_styles, 0, false);
                 tokens.extend(document_tokens);
             }
             return tokens;
         }
 
-        highlight(value, 0, false)
+        self.highlight(value, Some(styles), 0, false)
+    }
+
+    fn indent_width(&self) -> usize {
+        self.indent
     }
 }
 
-fn highlight(value: &Value, indent: usize, from_arr: bool) -> Vec<SyntaxToken> {
-    let mut tokens = Vec::new();
-
-    match value {
-        Value::Null => tokens.push(SyntaxToken::Null("null")),
-        Value::String(s) => {
-            let value = StringValue::new(s, false);
-            match value {
-                StringValue::String(s) => tokens.push(SyntaxToken::String(s)),
-                StringValue::MultiLines(lines) => {
-                    tokens.push(SyntaxToken::Symbol("|"));
-                    tokens.push(SyntaxToken::Break);
-                    for line in lines {
-                        if line.is_empty() {
-                            tokens.push(SyntaxToken::Break);
-                            continue;
-                        }
-                        tokens.push(SyntaxToken::Indent(indent));
-                        tokens.push(SyntaxToken::String(line));
-                        tokens.push(SyntaxToken::Break);
-                    }
+impl YamlParser {
+    pub(super) fn new(cfg: &Config) -> Self {
+        Self::with_format(cfg.data.yaml.indent, cfg.data.yaml.quote_style)
+    }
 
-                    // MultiLines done, don't need to append the last break.
-                    return tokens;
-                }
-            }
+    fn with_format(indent: usize, quote_style: QuoteStyle) -> Self {
+        Self {
+            indent,
+            quote_style,
         }
-        Value::Number(num) => tokens.push(SyntaxToken::Number(num.to_string())),
-        Value::Bool(b) => {
-            let b = if *b { "true" } else { "false" };
-            tokens.push(SyntaxToken::Bool(b));
+    }
+
+    /// The quote style of the string, the preserved ones use the quotes in the source.
+    fn get_quote_style(&self, styles: Option<&ScalarStyles>, s: &str) -> QuoteStyle {
+        if self.quote_style != QuoteStyle::Preserve {
+            return self.quote_style;
         }
-        Value::Object(obj) => {
-            if !obj.is_empty() {
-                for (idx, (field, value)) in obj.iter().enumerate() {
-                    if idx > 0 || !from_arr {
-                        tokens.push(SyntaxToken::Indent(indent));
-                    }
-                    let field = syntax::quote_field_name(field);
-                    tokens.push(SyntaxToken::Name(field));
+        match ScalarStyles::style(styles, s) {
+            Some(ScalarStyle::SingleQuoted) => QuoteStyle::Single,
+            Some(ScalarStyle::DoubleQuoted) => QuoteStyle::Double,
+            _ => QuoteStyle::Auto,
+        }
+    }
 
-                    let is_value_complex = match value {
-                        Value::Object(obj) => !obj.is_empty(),
-                        Value::Array(arr) => !arr.is_empty(),
-                        _ => false,
-                    };
+    fn highlight(
+        &self,
+        value: &Value,
+        styles: Option<&ScalarStyles>,
+        level: usize,
+        from_arr: bool,
+    ) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        let indent = level * self.indent;
 
-                    if is_value_complex {
-                        tokens.push(SyntaxToken::Symbol(":"));
-                        tokens.push(SyntaxToken::Break);
-                    } else {
-                        tokens.push(SyntaxToken::Symbol(": "));
+        match value {
+            Value::Null => tokens.push(SyntaxToken::Null("null")),
+            Value::String(s) => {
+                let value = StringValue::new(s, self.get_quote_style(styles, s));
+                match value {
+                    StringValue::String(s) => tokens.push(SyntaxToken::String(s)),
+                    // The block scalar cannot start with spaces without an indentation
+                    // indicator, quote it to keep things simple.
+                    StringValue::MultiLines(lines)
+                        if lines.first().is_some_and(|line| line.starts_with(' ')) =>
+                    {
+                        tokens.push(SyntaxToken::String(StringValue::quote_string(s)));
                     }
+                    StringValue::MultiLines(lines) => {
+                        // The chomping indicator tells the parser how to handle the trailing
+                        // newlines, so that the string can be parsed back without changes.
+                        let symbol = if !s.ends_with('\n') {
+                            "|-"
+                        } else if s.ends_with("\n\n") {
+                            "|+"
+                        } else {
+                            "|"
+                        };
+                        tokens.push(SyntaxToken::Symbol(symbol));
+                        tokens.push(SyntaxToken::Break);
+                        for line in lines {
+                            if line.is_empty() {
+                                tokens.push(SyntaxToken::Break);
+                                continue;
+                            }
+                            tokens.push(SyntaxToken::Indent(indent));
+                            tokens.push(SyntaxToken::String(line));
+                            tokens.push(SyntaxToken::Break);
+                        }
 
-                    let value_tokens = highlight(value, indent + 1, false);
-                    tokens.extend(value_tokens);
+                        // MultiLines done, don't need to append the last break.
+                        return tokens;
+                    }
                 }
-                return tokens;
             }
-            tokens.push(SyntaxToken::Symbol("{}"));
-        }
-        Value::Array(arr) => {
-            if !arr.is_empty() {
-                for (idx, value) in arr.iter().enumerate() {
-                    if idx > 0 || !from_arr {
-                        tokens.push(SyntaxToken::Indent(indent));
+            Value::Number(num) => tokens.push(SyntaxToken::Number(num.to_string())),
+            Value::Bool(b) => {
+                let b = if *b { "true" } else { "false" };
+                tokens.push(SyntaxToken::Bool(b));
+            }
+            Value::Object(obj) => {
+                if !obj.is_empty() {
+                    for (idx, (field, value)) in obj.iter().enumerate() {
+                        if idx > 0 || !from_arr {
+                            tokens.push(SyntaxToken::Indent(indent));
+                        }
+                        tokens.push(SyntaxToken::Name(syntax::quote_field_name(field)));
+
+                        let is_value_complex = match value {
+                            Value::Object(obj) => !obj.is_empty(),
+                            Value::Array(arr) => !arr.is_empty(),
+                            _ => false,
+                        };
+
+                        if is_value_complex {
+                            tokens.push(SyntaxToken::Symbol(":"));
+                            tokens.push(SyntaxToken::Break);
+                        } else {
+                            tokens.push(SyntaxToken::Symbol(": "));
+                        }
+
+                        let value_styles = ScalarStyles::child(styles, field);
+                        let value_tokens = self.highlight(value, value_styles, level + 1, false);
+                        tokens.extend(value_tokens);
                     }
+                    return tokens;
+                }
+                tokens.push(SyntaxToken::Symbol("{}"));
+            }
+            Value::Array(arr) => {
+                if !arr.is_empty() {
+                    for (idx, value) in arr.iter().enumerate() {
+                        if idx > 0 || !from_arr {
+                            tokens.push(SyntaxToken::Indent(indent));
+                        }
 
-                    tokens.push(SyntaxToken::Symbol("- "));
+                        // The item content must be aligned with the next indentation level,
+                        // so pad the dash to the indent width.
+                        tokens.push(SyntaxToken::Symbol("-"));
+                        tokens.push(SyntaxToken::Indent(self.indent - 1));
 
-                    let value_tokens = highlight(value, indent + 1, true);
-                    tokens.extend(value_tokens);
+                        let item_styles = ScalarStyles::child(styles, &idx.to_string());
+                        let value_tokens = self.highlight(value, item_styles, level + 1, true);
+                        tokens.extend(value_tokens);
+                    }
+                    return tokens;
                 }
-                return tokens;
+                tokens.push(SyntaxToken::Symbol("[]"));
             }
-            tokens.push(SyntaxToken::Symbol("[]"));
         }
-    }
 
-    tokens.push(SyntaxToken::Break);
-    tokens
+        tokens.push(SyntaxToken::Break);
+        tokens
+    }
 }
 
 #[cfg(test)]
@@ -193,10 +401,10 @@ mod test {
             ),
         ];
 
-        let parser = YamlParser {};
+        let parser = YamlParser::new(&Config::default());
         for (raw, expect) in test_cases {
             let value = parser.parse(raw).unwrap();
-            let tokens = parser.syntax_highlight(&value);
+            let tokens = parser.syntax_highlight(&value, &ScalarStyles::default());
             let result = SyntaxToken::pure_text(&tokens);
             assert_eq!(result, expect);
 
@@ -204,4 +412,95 @@ mod test {
             assert_eq!(value, highlight_value);
         }
     }
+
+    #[test]
+    fn test_format_options() {
+        let raw = include_str!("test_cases/yaml/array_of_objects.yaml");
+        let test_cases = [
+            (
+                4,
+                QuoteStyle::Auto,
+                include_str!("test_cases/yaml/array_of_objects_indent4.yaml"),
+            ),
+            (
+                2,
+                QuoteStyle::Single,
+                include_str!("test_cases/yaml/array_of_objects_single.yaml"),
+            ),
+        ];
+
+        for (indent, quote_style, expect) in test_cases {
+            let parser = YamlParser::with_format(indent, quote_style);
+            let value = parser.parse(raw).unwrap();
+            let result = parser.to_string(&value, &ScalarStyles::default());
+            assert_eq!(result, expect);
+
+            let result_value = parser.parse(&result).unwrap();
+            assert_eq!(value, result_value);
+        }
+    }
+
+    #[test]
+    fn test_to_string() {
+        let test_cases = [
+            "no newline\nat the end",
+            "one newline\nat the end\n",
+            "more newlines\nat the end\n\n",
+            "  leading spaces\nline",
+            "null",
+            "True",
+            "it's",
+            "\u{1b}[0m",
+        ];
+
+        let quote_styles = [
+            QuoteStyle::Auto,
+            QuoteStyle::Double,
+            QuoteStyle::Single,
+            QuoteStyle::Preserve,
+        ];
+        for quote_style in quote_styles {
+            let parser = YamlParser::with_format(2, quote_style);
+            for s in test_cases {
+                let mut obj = serde_json::Map::new();
+                obj.insert(String::from("value"), Value::String(s.to_string()));
+                let value = Value::Object(obj);
+
+                let result = parser.to_string(&value, &ScalarStyles::default());
+                let result_value = parser.parse(&result).unwrap();
+                assert_eq!(value, result_value, "{result}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_preserve_quotes() {
+        let parser = YamlParser::with_format(2, QuoteStyle::Preserve);
+        let raw = "single: 'x'\ndouble: \"y\"\nplain: z\nkeyword: 'true'\nitems:\n  - 'p'\n  - \"p\"\n  - p\n";
+        let (value, styles) = parser.parse_styles(raw).unwrap();
+        assert_eq!(parser.to_string(&value, &styles), raw);
+
+        // The styles of the part of the document.
+        let items = value.get("items").unwrap();
+        let items_styles = styles.get(&["items"]).unwrap();
+        assert_eq!(
+            parser.to_string(items, items_styles),
+            "- 'p'\n- \"p\"\n- p\n"
+        );
+
+        // The quotes of other values with the same content are not picked up.
+        let result = parser.to_string(&Value::String(String::from("x")), &styles);
+        assert_eq!(result, "x\n");
+        let result = parser.to_string(&value, &ScalarStyles::default());
+        assert!(result.starts_with("single: x\ndouble: y\n"));
+
+        let raw = "a: 'x'\n---\na: x\nb: \"y\"\n";
+        let (value, styles) = parser.parse_styles(raw).unwrap();
+        let result = SyntaxToken::pure_text(&parser.syntax_highlight(&value, &styles));
+        assert_eq!(result, format!("---\n{raw}"));
+
+        let parser = YamlParser::with_format(2, QuoteStyle::Auto);
+        let (_, styles) = parser.parse_styles(raw).unwrap();
+        assert!(styles.get(&["0"]).is_none());
+    }
 }
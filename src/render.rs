@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use ratatui::style::{Color, Style};
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::parse::SyntaxToken;
+
+/// Render extra information after the value in the data block, like a swatch for the
+/// colors. Return `None` if the value is not supported, then it is shown as usual.
+pub trait Renderer {
+    fn render(&self, cfg: &Config, value: &Value) -> Option<Vec<SyntaxToken>>;
+}
+
+/// The built-in renderers, selected by name in the `[renderers]` rules.
+const RENDERERS: &[(&str, &dyn Renderer)] = &[
+    ("color", &ColorRenderer),
+    ("duration", &DurationRenderer { millis: false }),
+    ("duration_ms", &DurationRenderer { millis: true }),
+    ("bytes", &BytesRenderer),
+];
+
+pub fn find(name: &str) -> Option<&'static dyn Renderer> {
+    RENDERERS
+        .iter()
+        .find(|(renderer, _)| *renderer == name)
+        .map(|(_, renderer)| *renderer)
+}
+
+/// The renderers for paths and field names, from the `[renderers]` rules in config. The
+/// rules starting with `/` are paths from the document root, others are field names.
+#[derive(Default)]
+pub struct Renderers {
+    paths: HashMap<String, &'static dyn Renderer>,
+    names: HashMap<String, &'static dyn Renderer>,
+}
+
+impl Renderers {
+    pub fn new(cfg: &Config) -> Self {
+        let mut renderers = Self::default();
+        for (rule, name) in cfg.renderers.iter() {
+            // The names are checked in config validation.
+            let Some(renderer) = find(name) else {
+                continue;
+            };
+            match rule.strip_prefix('/') {
+                Some(path) => renderers.paths.insert(path.to_string(), renderer),
+                None => renderers.names.insert(rule.clone(), renderer),
+            };
+        }
+        renderers
+    }
+
+    /// Get the renderer by the path (identify) first, then the field name.
+    pub fn get(&self, path: &str, name: &str) -> Option<&'static dyn Renderer> {
+        self.paths
+            .get(path)
+            .or_else(|| self.names.get(name))
+            .copied()
+    }
+}
+
+/// Show a swatch for the color strings, like `#ff0000` or `red`.
+struct ColorRenderer;
+
+impl Renderer for ColorRenderer {
+    fn render(&self, _cfg: &Config, value: &Value) -> Option<Vec<SyntaxToken>> {
+        let color = value.as_str()?.trim().parse::<Color>().ok()?;
+        Some(vec![SyntaxToken::Styled(
+            String::from("██"),
+            Style::default().fg(color),
+        )])
+    }
+}
+
+/// Humanize the numbers of seconds (or milliseconds), like `(1h 2m 5s)`.
+struct DurationRenderer {
+    millis: bool,
+}
+
+impl Renderer for DurationRenderer {
+    fn render(&self, cfg: &Config, value: &Value) -> Option<Vec<SyntaxToken>> {
+        let num = value.as_f64()?;
+        if num < 0.0 {
+            return None;
+        }
+        let millis = if self.millis { num } else { num * 1000.0 };
+        Some(annotation(cfg, format_duration(millis.round() as u64)))
+    }
+}
+
+/// Humanize the numbers of bytes, like `(1.50 MiB)`.
+struct BytesRenderer;
+
+impl Renderer for BytesRenderer {
+    fn render(&self, cfg: &Config, value: &Value) -> Option<Vec<SyntaxToken>> {
+        let bytes = value.as_u64()?;
+        let size = humansize::format_size(bytes, humansize::BINARY);
+        Some(annotation(cfg, size))
+    }
+}
+
+fn annotation(cfg: &Config, text: String) -> Vec<SyntaxToken> {
+    vec![SyntaxToken::Styled(
+        format!("({text})"),
+        cfg.colors.data.annotation.style,
+    )]
+}
+
+fn format_duration(millis: u64) -> String {
+    if millis < 1000 {
+        return format!("{millis}ms");
+    }
+
+    let units = [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)];
+    let mut secs = millis / 1000;
+    let mut parts = vec![];
+    for (unit, size) in units {
+        if secs >= size {
+            parts.push(format!("{}{unit}", secs / size));
+            secs %= size;
+        }
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_renderers() {
+        let mut cfg = Config::default();
+        cfg.renderers
+            .insert(String::from("/spec/timeout"), String::from("duration"));
+        cfg.renderers
+            .insert(String::from("color"), String::from("color"));
+        cfg.parse().unwrap();
+
+        let renderers = Renderers::new(&cfg);
+        assert!(renderers.get("spec/timeout", "timeout").is_some());
+        assert!(renderers.get("timeout", "timeout").is_none());
+        assert!(renderers.get("a/0/color", "color").is_some());
+
+        let color = find("color").unwrap();
+        let tokens = color.render(&cfg, &json!("#ff0000")).unwrap();
+        assert_eq!(SyntaxToken::plain(&tokens), "██");
+        assert!(color.render(&cfg, &json!("not a color")).is_none());
+        assert!(color.render(&cfg, &json!(1)).is_none());
+
+        let duration = find("duration").unwrap();
+        let tokens = duration.render(&cfg, &json!(3725)).unwrap();
+        assert_eq!(SyntaxToken::plain(&tokens), "(1h 2m 5s)");
+        let tokens = find("duration_ms")
+            .unwrap()
+            .render(&cfg, &json!(250))
+            .unwrap();
+        assert_eq!(SyntaxToken::plain(&tokens), "(250ms)");
+
+        let tokens = find("bytes").unwrap().render(&cfg, &json!(1536)).unwrap();
+        assert_eq!(SyntaxToken::plain(&tokens), "(1.50 KiB)");
+
+        cfg.renderers
+            .insert(String::from("size"), String::from("unknown"));
+        assert!(cfg.parse().is_err());
+    }
+}
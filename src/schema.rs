@@ -0,0 +1,234 @@
+use std::fmt::Write;
+
+use serde_json::{json, Map, Value};
+
+/// The schema inferred from the values, the values at the same path are merged, so
+/// that an array of objects gives the fields of all the objects.
+#[derive(Debug, Default)]
+pub struct Schema {
+    /// The types seen, in the order they are first seen.
+    types: Vec<&'static str>,
+
+    /// The number of values merged into the schema, and how many of them are objects.
+    count: usize,
+    objects: usize,
+
+    /// The first scalar value seen, encoded as JSON.
+    example: Option<String>,
+
+    /// The fields of objects, with the number of objects containing them.
+    fields: Vec<(String, Schema)>,
+
+    /// The items of arrays.
+    items: Option<Box<Schema>>,
+}
+
+impl Schema {
+    const MAX_EXAMPLE_WIDTH: usize = 30;
+
+    pub fn infer(value: &Value) -> Self {
+        let mut schema = Self::default();
+        schema.add(value);
+        schema
+    }
+
+    pub fn add(&mut self, value: &Value) {
+        self.count += 1;
+        let name = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(num) if num.is_i64() || num.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(arr) => {
+                // Keep the items schema for empty arrays, so that they are known as arrays.
+                self.items.get_or_insert_with(Box::default);
+                arr.iter().for_each(|item| self.add_array_item(item));
+                "array"
+            }
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (key, value) in obj {
+                    self.get_field(key).add(value);
+                }
+                "object"
+            }
+        };
+        if !self.types.contains(&name) {
+            self.types.push(name);
+        }
+
+        if self.example.is_none()
+            && !matches!(value, Value::Null | Value::Array(_) | Value::Object(_))
+        {
+            let example = serde_json::to_string(value).unwrap_or_default();
+            self.example = Some(truncate(example, Self::MAX_EXAMPLE_WIDTH));
+        }
+    }
+
+    /// Merge the item into the array schema, without counting an array value. This is
+    /// used when the array is not available as a value, like the root of the tree.
+    pub fn add_array_item(&mut self, item: &Value) {
+        self.items.get_or_insert_with(Box::default).add(item);
+    }
+
+    fn get_field(&mut self, key: &str) -> &mut Schema {
+        let idx = match self.fields.iter().position(|(name, _)| name == key) {
+            Some(idx) => idx,
+            None => {
+                self.fields.push((key.to_string(), Schema::default()));
+                self.fields.len() - 1
+            }
+        };
+        &mut self.fields[idx].1
+    }
+
+    fn type_name(&self) -> String {
+        let mut name = self.types.join(" | ");
+        if let Some(items) = self.items.as_ref() {
+            if name.is_empty() {
+                name.push_str("array");
+            }
+            if !items.types.is_empty() {
+                let _ = write!(name, " of {}", items.types.join(" | "));
+            }
+        }
+        name
+    }
+
+    /// Render the schema as indented lines, the optional fields (missing in some of
+    /// the objects) are marked with `?`.
+    pub fn render(&self) -> String {
+        let mut text = self.type_name();
+        if let Some(example) = self.example.as_ref() {
+            let _ = write!(text, ", e.g. {example}");
+        }
+        text.push('\n');
+        self.render_children(&mut text, 1);
+        text
+    }
+
+    fn render_children(&self, text: &mut String, level: usize) {
+        let indent = "  ".repeat(level);
+        for (name, field) in self.fields.iter() {
+            let optional = if field.count < self.objects { "?" } else { "" };
+            let _ = write!(text, "{indent}{name}{optional}: {}", field.type_name());
+            if let Some(example) = field.example.as_ref() {
+                let _ = write!(text, ", e.g. {example}");
+            }
+            text.push('\n');
+            field.render_children(text, level + 1);
+        }
+
+        if let Some(items) = self.items.as_ref() {
+            if !items.fields.is_empty() || items.items.is_some() {
+                let _ = writeln!(text, "{indent}[]: {}", items.type_name());
+                items.render_children(text, level + 1);
+            }
+        }
+    }
+
+    /// Convert to [JSON Schema](https://json-schema.org/).
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = self.to_json_schema_inner();
+        if let Value::Object(obj) = &mut schema {
+            obj.insert(
+                String::from("$schema"),
+                json!("https://json-schema.org/draft/2020-12/schema"),
+            );
+        }
+        schema
+    }
+
+    fn to_json_schema_inner(&self) -> Value {
+        let mut schema = Map::new();
+        match self.types.as_slice() {
+            [] if self.items.is_some() => {
+                schema.insert(String::from("type"), json!("array"));
+            }
+            [] => {}
+            [name] => {
+                schema.insert(String::from("type"), json!(name));
+            }
+            names => {
+                schema.insert(String::from("type"), json!(names));
+            }
+        }
+
+        if !self.fields.is_empty() {
+            let mut properties = Map::new();
+            let mut required = vec![];
+            for (name, field) in self.fields.iter() {
+                properties.insert(name.clone(), field.to_json_schema_inner());
+                if field.count >= self.objects {
+                    required.push(json!(name));
+                }
+            }
+            schema.insert(String::from("properties"), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert(String::from("required"), Value::Array(required));
+            }
+        }
+
+        if let Some(items) = self.items.as_ref() {
+            if !items.types.is_empty() {
+                schema.insert(String::from("items"), items.to_json_schema_inner());
+            }
+        }
+        Value::Object(schema)
+    }
+}
+
+fn truncate(s: String, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s;
+    }
+    let mut s: String = s.chars().take(width).collect();
+    s.push('…');
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let value = json!([
+            {"name": "Gilbert", "score": 24, "tags": ["a"], "user": {"id": 1}},
+            {"name": "Alexa", "score": 29.5, "tags": [], "user": null},
+            {"name": "May", "score": null, "tags": ["b", 1]}
+        ]);
+        let schema = Schema::infer(&value);
+
+        let expect = r#"array of object
+  []: object
+    name: string, e.g. "Gilbert"
+    score: integer | number | null, e.g. 24
+    tags: array of string | integer
+    user?: object | null
+      id: integer, e.g. 1
+"#;
+        assert_eq!(schema.render(), expect);
+
+        let expect = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "score": {"type": ["integer", "number", "null"]},
+                    "tags": {"type": "array", "items": {"type": ["string", "integer"]}},
+                    "user": {
+                        "type": ["object", "null"],
+                        "properties": {"id": {"type": "integer"}},
+                        "required": ["id"]
+                    }
+                },
+                "required": ["name", "score", "tags"]
+            }
+        });
+        assert_eq!(schema.to_json_schema(), expect);
+    }
+}
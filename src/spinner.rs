@@ -0,0 +1,53 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+
+/// Only show the spinner when the work takes longer than this, so that the small
+/// documents don't get a flash in the terminal.
+const SHOW_DELAY: Duration = Duration::from_millis(100);
+
+const FRAME_INTERVAL: Duration = Duration::from_millis(80);
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const ASCII_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Run the work and show a spinner with the message in stderr while it is running, the
+/// spinner is drawn in a background thread since the work blocks current thread. This
+/// is used before the TUI starts, like parsing a big file, so that the terminal doesn't
+/// appear frozen.
+pub fn run<T>(cfg: &Config, message: &str, work: impl FnOnce() -> T) -> T {
+    if !io::stderr().is_terminal() {
+        return work();
+    }
+
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let message = message.to_string();
+    let frames: &'static [&str] = if cfg.ui.ascii_only {
+        &ASCII_FRAMES
+    } else {
+        &FRAMES
+    };
+    let spinner = thread::spawn(move || {
+        if done_rx.recv_timeout(SHOW_DELAY) != Err(RecvTimeoutError::Timeout) {
+            return;
+        }
+        let mut stderr = io::stderr();
+        for frame in frames.iter().cycle() {
+            let _ = write!(stderr, "\r{frame} {message}");
+            let _ = stderr.flush();
+            if done_rx.recv_timeout(FRAME_INTERVAL) != Err(RecvTimeoutError::Timeout) {
+                break;
+            }
+        }
+        // Clear the spinner line.
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    });
+
+    let result = work();
+    drop(done_tx);
+    let _ = spinner.join();
+    result
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// The data saved in the state file, shared by all the documents.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateData {
+    /// The notes of the documents, the key is the absolute path of the document, the
+    /// value maps the item paths to notes.
+    #[serde(default)]
+    notes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// The notes attached to the items of current document.
+#[derive(Debug, Default)]
+pub struct Notes {
+    /// The state file and the key of current document in it. `None` means the notes
+    /// are kept in memory only, like the data read from stdin.
+    save_to: Option<(PathBuf, String)>,
+
+    notes: BTreeMap<String, String>,
+}
+
+impl Notes {
+    /// Load the notes of the document at `path` from the state file.
+    pub fn load(cfg: &Config, path: &str) -> Result<Self> {
+        let file = match get_state_file(cfg) {
+            Some(file) => file,
+            None => return Ok(Self::default()),
+        };
+        let doc =
+            fs::canonicalize(path).with_context(|| format!("get absolute path for '{path}'"))?;
+        let doc = format!("{}", doc.display());
+
+        let mut data = read_state(&file)?;
+        let notes = data.notes.remove(&doc).unwrap_or_default();
+        Ok(Self {
+            save_to: Some((file, doc)),
+            notes,
+        })
+    }
+
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.notes.get(path).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.notes.contains_key(path)
+    }
+
+    /// Set the note of the item and save it to the state file, empty note means to
+    /// remove it.
+    pub fn set(&mut self, path: String, note: String) -> Result<()> {
+        if note.is_empty() {
+            self.notes.remove(&path);
+        } else {
+            self.notes.insert(path, note);
+        }
+
+        let (file, doc) = match self.save_to.as_ref() {
+            Some(save_to) => save_to,
+            None => return Ok(()),
+        };
+
+        // The state file might be changed by other sessions, read it again so that we
+        // only overwrite the notes of current document.
+        let mut data = read_state(file)?;
+        if self.notes.is_empty() {
+            data.notes.remove(doc);
+        } else {
+            data.notes.insert(doc.clone(), self.notes.clone());
+        }
+        write_state(file, &data)
+    }
+}
+
+fn get_state_file(cfg: &Config) -> Option<PathBuf> {
+    if !cfg.state.file.is_empty() {
+        return Some(PathBuf::from(&cfg.state.file));
+    }
+    let dir = dirs::data_dir()?;
+    Some(dir.join("otree").join("state.json"))
+}
+
+fn read_state(file: &Path) -> Result<StateData> {
+    let data = match fs::read_to_string(file) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(StateData::default()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("read state file '{}'", file.display()))
+        }
+    };
+    match serde_json::from_str(&data) {
+        Ok(data) => Ok(data),
+        Err(err) => bail!("parse state file '{}': {err}", file.display()),
+    }
+}
+
+fn write_state(file: &Path, data: &StateData) -> Result<()> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("create state directory '{}'", dir.display()))?;
+    }
+    let data = serde_json::to_string_pretty(data).context("encode state data")?;
+    fs::write(file, data).with_context(|| format!("write state file '{}'", file.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_notes() {
+        let dir = env::temp_dir().join(format!("otree_test_notes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let doc = dir.join("doc.json");
+        fs::write(&doc, "{}").unwrap();
+        let doc = format!("{}", doc.display());
+
+        let mut cfg = Config::default();
+        cfg.state.file = format!("{}", dir.join("state").join("state.json").display());
+
+        let mut notes = Notes::load(&cfg, &doc).unwrap();
+        assert!(notes.get("a/b").is_none());
+        notes
+            .set(String::from("a/b"), String::from("checked"))
+            .unwrap();
+        notes.set(String::from("c"), String::from("todo")).unwrap();
+        notes.set(String::from("c"), String::new()).unwrap();
+
+        let notes = Notes::load(&cfg, &doc).unwrap();
+        assert_eq!(notes.get("a/b"), Some("checked"));
+        assert!(!notes.contains("c"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
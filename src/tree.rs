@@ -1,35 +1,99 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::rc::Rc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use once_cell::unsync::OnceCell;
+use ratatui::style::Style;
 use ratatui::text::{Line, Span, Text};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use tui_tree_widget::TreeItem;
+use unicode_width::UnicodeWidthStr;
 
 use crate::config::Config;
-use crate::parse::{ContentType, Parser, SyntaxToken};
+use crate::mask::{self, Masks};
+use crate::openapi;
+use crate::parse::{ContentType, Parser, ScalarStyles, SyntaxToken};
+use crate::render::Renderers;
 
+/// The parsed document as the items of the tree widget, with the values and the
+/// highlighted data of each item. The items are identified by their paths, which are the
+/// escaped keys joined by `/`, like `spec/containers/0/image`.
 pub struct Tree<'a> {
-    pub parser: Rc<Box<dyn Parser>>,
+    pub(crate) parser: Rc<Box<dyn Parser>>,
+    /// The styles of the scalars in the document, shared by the rebuilt trees.
+    styles: Rc<ScalarStyles>,
 
-    pub items: Vec<TreeItem<'static, String>>,
-    pub values: HashMap<String, Rc<ItemValue>>,
+    pub(crate) items: Vec<TreeItem<'static, String>>,
+    pub(crate) values: HashMap<String, Rc<ItemValue>>,
+
+    /// The number of nodes in the tree.
+    pub(crate) nodes: usize,
+    /// The approximate memory usage of the tree, in bytes. This is estimated during
+    /// building, it is not accurate but good enough to know how heavy the tree is.
+    pub(crate) memory: usize,
+
+    /// Display the object fields in alphabetical order rather than document order. This
+    /// only affects the display, the values are kept as is.
+    pub(crate) sort_keys: bool,
+    /// Mask the sensitive values, this is turned off by the `reveal_masked` action.
+    pub(crate) mask: bool,
+    pub(crate) root_array: bool,
+    /// The root is a scalar value, shown as a single `root` item.
+    pub(crate) root_scalar: bool,
+    /// The identifiers of the root items in document order, the items can be sorted.
+    root_ids: Vec<String>,
+
+    /// The identifiers of the array item fields whose values differ across the items,
+    /// computed for the first use.
+    differences: OnceCell<HashSet<String>>,
+
+    /// Show the smart labels of OpenAPI, see [`crate::openapi::label`].
+    openapi: bool,
+
+    renderers: Renderers,
+    masks: Masks,
+
+    /// The identifiers of the root items that failed to parse, their values are the
+    /// error messages. See [`Parser::parse_partial`].
+    errors: HashSet<String>,
 
     cfg: &'a Config,
 }
 
+/// The value of a tree item.
 pub struct ItemValue {
     pub name: String,
     pub value: Value,
 
-    pub data: Data,
+    pub(crate) data: Data,
+
+    /// The display width of the item text in the tree overview.
+    pub(crate) text_width: usize,
+    pub(crate) columns: ItemColumns,
+
+    /// The value is masked in the tree overview and data block.
+    pub masked: bool,
+}
+
+/// The display widths of the parts in the item text, used to align the columns.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ItemColumns {
+    /// The width before the name, like the icon.
+    pub name_offset: usize,
+    pub name_width: usize,
+    /// Zero means the type label is hidden.
+    pub type_width: usize,
 }
 
 pub struct Data {
     pub display: Display,
     pub columns: usize,
     pub rows: usize,
+
+    /// The width of each indentation level, used to draw the indent guides.
+    indent_width: usize,
 }
 
 pub enum Display {
@@ -48,17 +112,86 @@ enum FieldType {
 }
 
 impl<'a> Tree<'a> {
+    /// Parse the data to the tree, the config must be parsed by [`Config::parse`] first.
     pub fn parse(cfg: &'a Config, data: &str, content_type: ContentType) -> Result<Self> {
-        let parser = content_type.new_parser();
-        let value = parser.parse(data)?;
-        Ok(Self::from_value(cfg, value, Rc::new(parser)))
+        let parser = content_type.new_parser(cfg);
+        let (value, styles, errors) = if cfg.data.partial_parse {
+            parser.parse_partial(data)?
+        } else {
+            let (value, styles) = parser.parse_styles(data)?;
+            (value, styles, vec![])
+        };
+        let errors = errors.into_iter().map(|idx| idx.to_string()).collect();
+        Self::build(
+            cfg,
+            value,
+            Rc::new(styles),
+            Rc::new(parser),
+            cfg.tree.sort_keys,
+            cfg.mask.enable,
+            errors,
+        )
+    }
+
+    /// Build the tree of the value, the `styles` are the ones returned by
+    /// [`Parser::parse_styles`] with the value, or the default if there is none.
+    pub fn from_value(
+        cfg: &'a Config,
+        value: Value,
+        styles: ScalarStyles,
+        parser: Rc<Box<dyn Parser>>,
+        sort_keys: bool,
+        mask: bool,
+    ) -> Result<Self> {
+        let styles = Rc::new(styles);
+        Self::build(cfg, value, styles, parser, sort_keys, mask, HashSet::new())
+    }
+
+    /// Build the tree of the item at the path, which is a part of this document, with
+    /// the same parser and options.
+    pub fn subtree(&self, path: &str, value: Value) -> Result<Self> {
+        let styles = self.get_styles(path).cloned().unwrap_or_default();
+        Self::build(
+            self.cfg,
+            value,
+            Rc::new(styles),
+            self.get_parser(),
+            self.sort_keys,
+            self.mask,
+            HashSet::new(),
+        )
     }
 
-    pub fn from_value(cfg: &'a Config, value: Value, parser: Rc<Box<dyn Parser>>) -> Self {
+    fn build(
+        cfg: &'a Config,
+        value: Value,
+        styles: Rc<ScalarStyles>,
+        parser: Rc<Box<dyn Parser>>,
+        sort_keys: bool,
+        mask: bool,
+        errors: HashSet<String>,
+    ) -> Result<Self> {
         let mut tree = Self {
             parser,
+            styles,
             items: vec![],
             values: HashMap::new(),
+            nodes: 0,
+            memory: 0,
+            sort_keys,
+            mask,
+            root_array: matches!(value, Value::Array(_)),
+            root_scalar: !matches!(value, Value::Array(_) | Value::Object(_)),
+            root_ids: vec![],
+            differences: OnceCell::new(),
+            openapi: cfg.tree.openapi_labels
+                && openapi::is_openapi(
+                    value.get("openapi").or_else(|| value.get("swagger")),
+                    value.get("paths"),
+                ),
+            renderers: Renderers::new(cfg),
+            masks: Masks::new(cfg),
+            errors,
             cfg,
         };
 
@@ -66,165 +199,456 @@ impl<'a> Tree<'a> {
         // `root` item in the tree.
         let items: Vec<TreeItem<String>> = match value {
             Value::Array(arr) => {
-                let mut items = Vec::with_capacity(arr.len());
+                let len = arr.len();
+                let mut items = Vec::with_capacity(len);
                 for (idx, value) in arr.into_iter().enumerate() {
-                    let item = tree.build_item(vec![], idx.to_string(), value);
+                    let label = cfg.tree.index_name(idx, len);
+                    let item =
+                        tree.build_item(vec![], idx.to_string(), Some(label), value, false)?;
                     items.push(item);
                 }
                 items
             }
             Value::Object(obj) => {
+                tree.root_ids = obj.keys().cloned().collect();
                 let mut items = Vec::with_capacity(obj.len());
-                for (field, value) in obj {
-                    let item = tree.build_item(vec![], field, value);
+                for (field, value) in tree.object_fields(obj) {
+                    let item = tree.build_item(vec![], field, None, value, false)?;
                     items.push(item);
                 }
                 items
             }
             _ => {
-                vec![tree.build_item(vec![], String::from("root"), value)]
+                vec![tree.build_item(vec![], String::from("root"), None, value, false)?]
             }
         };
+        if tree.root_ids.is_empty() {
+            tree.root_ids = items.iter().map(|item| item.identifier().clone()).collect();
+        }
         tree.items = items;
-        tree
+        Ok(tree)
+    }
+
+    /// Build the same tree again with another key order or masking. The identifiers are
+    /// not changed, so the tree state can still be used with the new tree.
+    pub fn rebuild(&self, sort_keys: bool, mask: bool) -> Result<Self> {
+        let mut values = self.root_ids.iter().filter_map(|id| {
+            self.values
+                .get(escape_key(id).as_ref())
+                .map(|value| (id.clone(), value.value.clone()))
+        });
+        let root = if self.root_array {
+            Value::Array(values.map(|(_, value)| value).collect())
+        } else if self.root_scalar {
+            // The scalar root is the only `root` item.
+            values.next().map_or(Value::Null, |(_, value)| value)
+        } else {
+            Value::Object(values.collect())
+        };
+        let errors = self.errors.clone();
+        let styles = Rc::clone(&self.styles);
+        Self::build(
+            self.cfg,
+            root,
+            styles,
+            self.get_parser(),
+            sort_keys,
+            mask,
+            errors,
+        )
+    }
+
+    /// The identifies of all the items, in document order.
+    pub fn identifies(&self) -> Vec<String> {
+        fn walk(items: &[TreeItem<String>], parent: &str, identifies: &mut Vec<String>) {
+            for item in items {
+                let key = escape_key(item.identifier());
+                let identify = if parent.is_empty() {
+                    key.into_owned()
+                } else {
+                    format!("{parent}/{key}")
+                };
+                identifies.push(identify.clone());
+                walk(item.children(), &identify, identifies);
+            }
+        }
+
+        let mut identifies = Vec::with_capacity(self.values.len());
+        walk(&self.items, "", &mut identifies);
+        identifies
+    }
+
+    /// The identifies of the items whose names or scalar values contain the keyword,
+    /// ignoring case. The masked values are not searched.
+    pub fn find(&self, keyword: &str) -> Vec<String> {
+        let keyword = keyword.to_lowercase();
+        self.identifies()
+            .into_iter()
+            .filter(|identify| {
+                let item = match self.values.get(identify) {
+                    Some(item) => item,
+                    None => return false,
+                };
+                if item.name.to_lowercase().contains(&keyword) {
+                    return true;
+                }
+                if item.masked {
+                    return false;
+                }
+                let value = match &item.value {
+                    Value::String(s) => Cow::Borrowed(s.as_str()),
+                    Value::Number(num) => Cow::Owned(num.to_string()),
+                    Value::Bool(b) => Cow::Owned(b.to_string()),
+                    _ => return false,
+                };
+                value.to_lowercase().contains(&keyword)
+            })
+            .collect()
+    }
+
+    /// The identifies of the items matching the path pattern, like `/spec/*/image`. The
+    /// `*` matches any key and `**` matches any number of keys, including none.
+    pub fn query(&self, expr: &str) -> Vec<String> {
+        fn matches(pattern: &[&str], keys: &[&str]) -> bool {
+            match pattern.split_first() {
+                None => keys.is_empty(),
+                Some((&"**", rest)) => (0..=keys.len()).any(|idx| matches(rest, &keys[idx..])),
+                Some((part, rest)) => match keys.split_first() {
+                    Some((key, keys)) => (*part == "*" || part == key) && matches(rest, keys),
+                    None => false,
+                },
+            }
+        }
+
+        let pattern: Vec<&str> = expr.split('/').filter(|part| !part.is_empty()).collect();
+        self.identifies()
+            .into_iter()
+            .filter(|identify| {
+                let keys: Vec<&str> = identify.split('/').collect();
+                matches(&pattern, &keys)
+            })
+            .collect()
+    }
+
+    /// The JSON type name and a short preview of the item, for the machine readable
+    /// output. The scalars are previewed as JSON and the masked ones as the mask symbol.
+    pub fn get_preview(&self, path: &str) -> Option<(&'static str, String)> {
+        const MAX_PREVIEW_WIDTH: usize = 40;
+
+        let item = self.values.get(path)?;
+        let (field_type, _) = FieldType::describe(self.cfg, &item.value);
+        let preview = match &item.value {
+            Value::Array(arr) => {
+                let unit = if arr.len() > 1 { "items" } else { "item" };
+                format!("{} {unit}", arr.len())
+            }
+            Value::Object(obj) => {
+                let unit = if obj.len() > 1 { "fields" } else { "field" };
+                format!("{} {unit}", obj.len())
+            }
+            _ if item.masked => self.cfg.mask.symbol.clone(),
+            value => {
+                let mut preview = serde_json::to_string(value).unwrap_or_default();
+                if let Some((idx, _)) = preview.char_indices().nth(MAX_PREVIEW_WIDTH) {
+                    preview.truncate(idx);
+                    preview.push_str(&self.cfg.tree.ellipsis);
+                }
+                preview
+            }
+        };
+        Some((field_type.json_name(), preview))
+    }
+
+    /// The number of the root items that failed to parse.
+    pub fn get_errors(&self) -> usize {
+        self.errors.len()
     }
 
     pub fn get_value(&self, path: &str) -> Option<Rc<ItemValue>> {
         self.values.get(path).cloned()
     }
 
+    /// The value with the sensitive fields inside masked, `None` if there is nothing to
+    /// mask or masking is turned off.
+    pub fn get_masked_value(&self, path: &str, value: &Value) -> Option<Value> {
+        if !self.mask {
+            return None;
+        }
+        self.masks.apply(self.cfg, path, value)
+    }
+
+    /// Mask the value as if it is at the path, for the values not built into the tree,
+    /// like the ones to compare with. `None` if there is nothing to mask.
+    pub fn mask_value_at(&self, path: &str, value: &Value) -> Option<Value> {
+        if !self.mask {
+            return None;
+        }
+        if self.masks.matches_path(path) {
+            return Some(mask::mask_value(self.cfg, value));
+        }
+        self.masks.apply(self.cfg, path, value)
+    }
+
     pub fn get_parser(&self) -> Rc<Box<dyn Parser>> {
         Rc::clone(&self.parser)
     }
 
+    /// The styles of the scalars inside the item, see [`ScalarStyles`].
+    fn get_styles(&self, path: &str) -> Option<&ScalarStyles> {
+        if self.root_scalar {
+            // The only `root` item is the document itself.
+            return Some(&self.styles);
+        }
+        let keys: Vec<_> = path.split('/').map(unescape_key).collect();
+        self.styles.get(&keys)
+    }
+
+    /// Write the value of the item with the parser, the scalars are written in the same
+    /// styles as the source, like the TOML datetimes.
+    pub fn value_to_string(&self, path: &str, value: &Value) -> String {
+        let default = ScalarStyles::default();
+        let styles = self.get_styles(path).unwrap_or(&default);
+        self.parser.to_string(value, styles)
+    }
+
+    pub fn is_difference(&self, id: &str) -> bool {
+        self.differences
+            .get_or_init(|| self.collect_differences())
+            .contains(id)
+    }
+
+    fn collect_differences(&self) -> HashSet<String> {
+        let mut differences = HashSet::new();
+        if self.root_array {
+            let items: Vec<_> = self
+                .items
+                .iter()
+                .filter_map(|item| {
+                    let id = escape_key(item.identifier()).into_owned();
+                    self.values.get(&id).map(|value| (id, &value.value))
+                })
+                .collect();
+            mark_differences(items, &mut differences);
+        }
+        for (id, value) in self.values.iter() {
+            if let Value::Array(arr) = &value.value {
+                let items = arr
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| (format!("{id}/{idx}"), item))
+                    .collect();
+                mark_differences(items, &mut differences);
+            }
+        }
+        differences
+    }
+
+    /// Build the item with its children. The `label` is shown as the name in the tree
+    /// overview if present, like the formatted array indexes, the identifiers always use
+    /// the raw `name`.
     fn build_item(
         &mut self,
         parent: Vec<String>,
         name: String,
+        label: Option<String>,
         value: Value,
-    ) -> TreeItem<'static, String> {
-        let path = if parent.is_empty() {
-            name.clone()
-        } else {
-            format!("{}/{name}", parent.join("/"))
-        };
+        masked: bool,
+    ) -> Result<TreeItem<'static, String>> {
+        self.nodes += 1;
+        let max_nodes = self.cfg.data.max_nodes;
+        if max_nodes > 0 && self.nodes > max_nodes {
+            bail!("the data has too many nodes, we limit the maximum number to {max_nodes} to avoid running out of memory. HINT: You can use command line arg `--max-nodes` or config option `data.max_nodes` to modify this limitation");
+        }
+
+        let mut path = join_identify(&parent);
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(&escape_key(&name));
 
         let raw_value = value.clone();
         let raw_name = name.clone();
-        let (item, value) = match value {
-            Value::Null => (
-                TreeItem::new_leaf(
-                    raw_name.clone(),
-                    self.build_item_text(name, FieldType::Null, Cow::Borrowed("null")),
-                ),
-                ItemValue {
-                    name: raw_name,
-                    value: raw_value,
-                    data: Data::null(self.cfg),
-                },
-            ),
-            Value::String(s) => {
-                let description = format!("= {s:?}");
-                let text = self.build_item_text(name, FieldType::Str, Cow::Owned(description));
-                (
-                    TreeItem::new_leaf(raw_name.clone(), text),
-                    ItemValue {
-                        name: raw_name,
-                        value: raw_value,
-                        data: Data::string(self.cfg, s),
-                    },
-                )
-            }
+        // The children of the masked item are masked as well.
+        let masked = self.mask && (masked || self.masks.matches(&path, &name));
+        let (field_type, mut description) = FieldType::describe(self.cfg, &value);
+        let mut description_style = self.cfg.colors.tree.value.style;
+        // The `null`, `""` and blank strings look alike in the tree, give them distinct
+        // descriptions and styles.
+        match &value {
+            Value::Null => description_style = self.cfg.colors.tree.null.style,
             Value::Number(num) => {
-                let description = format!("= {num}");
-                let text = self.build_item_text(name, FieldType::Num, Cow::Owned(description));
-                (
-                    TreeItem::new_leaf(raw_name.clone(), text),
-                    ItemValue {
-                        name: raw_name,
-                        value: raw_value,
-                        data: Data::number(self.cfg, num.to_string()),
-                    },
-                )
+                let style = self.cfg.colors.values.number_style(&num.to_string());
+                description_style = description_style.patch(style);
             }
             Value::Bool(b) => {
-                let description = if b { "= true" } else { "= false" };
-                let text = self.build_item_text(name, FieldType::Bool, Cow::Borrowed(description));
-                (
-                    TreeItem::new_leaf(raw_name.clone(), text),
-                    ItemValue {
-                        name: raw_name,
-                        value: raw_value,
-                        data: Data::bool(self.cfg, b),
-                    },
-                )
+                let style = self.cfg.colors.values.bool_style(*b);
+                description_style = description_style.patch(style);
+            }
+            Value::String(s) if s.is_empty() => {
+                description = Cow::Borrowed("(empty string)");
+                description_style = self.cfg.colors.tree.empty.style;
+            }
+            Value::String(s) if s.trim().is_empty() => {
+                let count = s.chars().count();
+                let unit = if count > 1 { "chars" } else { "char" };
+                description = Cow::Owned(format!("(whitespace, {count} {unit})"));
+                description_style = self.cfg.colors.tree.empty.style;
+            }
+            Value::Array(arr) if arr.is_empty() => {
+                description = Cow::Owned(self.cfg.tree.empty_placeholder.clone());
+                description_style = self.cfg.colors.tree.empty.style;
+            }
+            Value::Object(obj) if obj.is_empty() => {
+                description = Cow::Owned(self.cfg.tree.empty_placeholder.clone());
+                description_style = self.cfg.colors.tree.empty.style;
+            }
+            _ => {}
+        }
+        if self.openapi && !masked {
+            if let Some(label) = openapi::label(&parent, &name, &value) {
+                description = Cow::Owned(label);
+            }
+        }
+        if masked && !matches!(value, Value::Array(_) | Value::Object(_)) {
+            description = Cow::Owned(format!("= {}", self.cfg.mask.symbol));
+            description_style = self.cfg.colors.tree.value.style;
+        }
+        if parent.is_empty() && self.errors.contains(&name) {
+            if let Value::String(message) = &value {
+                description = Cow::Owned(format!("parse error: {message}"));
+                description_style = self.cfg.colors.tree.error.style;
+            }
+        }
+        let (text, columns) = self.build_item_text(
+            label.unwrap_or(name),
+            field_type,
+            description,
+            description_style,
+        );
+        let renderer = self.renderers.get(&path, &raw_name);
+        let (children, mut data) = match value {
+            _ if masked && !matches!(value, Value::Array(_) | Value::Object(_)) => {
+                let symbol = Cow::Owned(self.cfg.mask.symbol.clone());
+                (None, Data::raw(self.cfg, symbol))
             }
+            Value::Null => (None, Data::null(self.cfg)),
+            Value::String(s) => (None, Data::string(self.cfg, s)),
+            Value::Number(num) => (None, Data::number(self.cfg, num.to_string())),
+            Value::Bool(b) => (None, Data::bool(self.cfg, b)),
             Value::Array(arr) => {
-                let description = format!(
-                    "[ {} {} ]",
-                    arr.len(),
-                    if arr.len() > 1 { "items" } else { "item" }
-                );
-                let text = self.build_item_text(name, FieldType::Arr, Cow::Owned(description));
-                let data = if self.cfg.data.disable_highlight {
-                    Data::raw(Cow::Owned(self.parser.to_string(&raw_value)))
-                } else {
-                    Data::highlight(self.parser.syntax_highlight(&raw_value))
-                };
+                let data = self.build_data(&path, &raw_value, masked);
 
-                let mut children = Vec::with_capacity(arr.len());
+                let len = arr.len();
+                let mut children = Vec::with_capacity(len);
                 for (idx, item) in arr.into_iter().enumerate() {
                     let mut child_parent = parent.to_vec();
                     child_parent.push(raw_name.clone());
 
-                    let child = self.build_item(child_parent, idx.to_string(), item);
+                    let label = self.cfg.tree.index_name(idx, len);
+                    let child =
+                        self.build_item(child_parent, idx.to_string(), Some(label), item, masked)?;
                     children.push(child);
                 }
 
-                (
-                    TreeItem::new(raw_name.clone(), text, children).unwrap(),
-                    ItemValue {
-                        name: raw_name,
-                        value: raw_value,
-                        data,
-                    },
-                )
+                (Some(children), data)
             }
             Value::Object(obj) => {
-                let description = format!(
-                    "{{ {} {} }}",
-                    obj.len(),
-                    if obj.len() > 1 { "fields" } else { "field" }
-                );
-                let text = self.build_item_text(name, FieldType::Obj, Cow::Owned(description));
-                let data = if self.cfg.data.disable_highlight {
-                    Data::raw(Cow::Owned(self.parser.to_string(&raw_value)))
-                } else {
-                    Data::highlight(self.parser.syntax_highlight(&raw_value))
-                };
+                let data = self.build_data(&path, &raw_value, masked);
 
                 let mut children = Vec::with_capacity(obj.len());
-                for (field, item) in obj {
+                for (field, item) in self.object_fields(obj) {
                     let mut child_parent = parent.to_vec();
                     child_parent.push(raw_name.clone());
 
-                    let child = self.build_item(child_parent, field, item);
+                    let child = self.build_item(child_parent, field, None, item, masked)?;
                     children.push(child);
                 }
-                (
-                    TreeItem::new(raw_name.clone(), text, children).unwrap(),
-                    ItemValue {
-                        name: raw_name,
-                        value: raw_value,
-                        data,
-                    },
-                )
+
+                (Some(children), data)
             }
         };
 
+        let renderer = renderer.filter(|_| !masked);
+        if let Some(tokens) = renderer.and_then(|renderer| renderer.render(self.cfg, &raw_value)) {
+            data.annotate(self.cfg, tokens);
+        }
+
+        let text_width = text.width();
+        let item = match children {
+            // The empty objects and arrays are leaves, there is nothing to expand.
+            Some(children) if !children.is_empty() => {
+                TreeItem::new(raw_name.clone(), text, children)
+                    .with_context(|| format!("build tree item '{path}'"))?
+            }
+            _ => TreeItem::new_leaf(raw_name.clone(), text),
+        };
+        let value = ItemValue {
+            name: raw_name,
+            value: raw_value,
+            data,
+            text_width,
+            columns,
+            masked,
+        };
+
+        // The path is stored twice, as the key of values and the identifier of item.
+        self.memory += value.memory_size() + path.len() * 2;
+
         let value = Rc::new(value);
         self.values.insert(path, value);
-        item
+        Ok(item)
+    }
+
+    fn build_data(&self, path: &str, value: &Value, masked: bool) -> Data {
+        let masked_value;
+        let value = if masked {
+            masked_value = mask::mask_value(self.cfg, value);
+            &masked_value
+        } else if self.mask {
+            match self.masks.apply(self.cfg, path, value) {
+                Some(value) => {
+                    masked_value = value;
+                    &masked_value
+                }
+                None => value,
+            }
+        } else {
+            value
+        };
+
+        let sorted;
+        let value = if self.sort_keys {
+            sorted = sort_value(value);
+            &sorted
+        } else {
+            value
+        };
+
+        if self.cfg.data.disable_highlight {
+            Data::raw(self.cfg, Cow::Owned(self.value_to_string(path, value)))
+        } else {
+            let default = ScalarStyles::default();
+            let styles = self.get_styles(path).unwrap_or(&default);
+            let mut tokens = self.parser.syntax_highlight(value, styles);
+            for token in tokens.iter_mut() {
+                if let SyntaxToken::Name(s) | SyntaxToken::String(s) | SyntaxToken::Section(s) =
+                    token
+                {
+                    if let Cow::Owned(sanitized) = sanitize(self.cfg, s) {
+                        *s = sanitized;
+                    }
+                }
+            }
+            Data::highlight(tokens, self.parser.indent_width())
+        }
+    }
+
+    fn object_fields(&self, obj: Map<String, Value>) -> Vec<(String, Value)> {
+        let mut fields: Vec<_> = obj.into_iter().collect();
+        if self.sort_keys {
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        fields
     }
 
     fn build_item_text(
@@ -232,116 +656,744 @@ impl<'a> Tree<'a> {
         name: String,
         field_type: FieldType,
         description: Cow<'static, str>,
-    ) -> Text<'static> {
+        description_style: Style,
+    ) -> (Text<'static>, ItemColumns) {
         // TODO: We can share field type to save memory.
-        let (type_str, type_style) = match field_type {
-            FieldType::Null => (
-                self.cfg.types.null.clone(),
-                self.cfg.colors.tree.type_null.style,
-            ),
-            FieldType::Num => (
-                self.cfg.types.num.clone(),
-                self.cfg.colors.tree.type_num.style,
-            ),
-            FieldType::Bool => (
-                self.cfg.types.bool.clone(),
-                self.cfg.colors.tree.type_bool.style,
-            ),
-            FieldType::Str => (
-                self.cfg.types.str.clone(),
-                self.cfg.colors.tree.type_str.style,
-            ),
-            FieldType::Obj => (
-                self.cfg.types.obj.clone(),
-                self.cfg.colors.tree.type_obj.style,
-            ),
-            FieldType::Arr => (
-                self.cfg.types.arr.clone(),
-                self.cfg.colors.tree.type_arr.style,
-            ),
+        let type_str = field_type.name(self.cfg);
+        let type_style = match field_type {
+            FieldType::Null => self.cfg.colors.tree.type_null.style,
+            FieldType::Num => self.cfg.colors.tree.type_num.style,
+            FieldType::Bool => self.cfg.colors.tree.type_bool.style,
+            FieldType::Str => self.cfg.colors.tree.type_str.style,
+            FieldType::Obj => self.cfg.colors.tree.type_obj.style,
+            FieldType::Arr => self.cfg.colors.tree.type_arr.style,
         };
-        let line = Line::from(vec![
-            Span::styled(name, self.cfg.colors.tree.name.style),
-            Span::raw(" "),
-            Span::styled(type_str, type_style),
-            Span::raw(" "),
-            Span::styled(description, self.cfg.colors.tree.value.style),
-        ]);
-        Text::from(line)
+        let name = match sanitize(self.cfg, &name) {
+            Cow::Borrowed(_) => name,
+            Cow::Owned(name) => name,
+        };
+        let mut spans = Vec::with_capacity(7);
+        let mut columns = ItemColumns {
+            name_width: name.width(),
+            ..Default::default()
+        };
+        if self.cfg.tree.show_icons {
+            let icon = field_type.icon(self.cfg);
+            if !icon.is_empty() {
+                columns.name_offset = icon.width() + 1;
+                spans.push(Span::styled(icon.to_string(), type_style));
+                spans.push(Span::raw(" "));
+            }
+        }
+        spans.push(Span::styled(name, self.cfg.colors.tree.name.style));
+        spans.push(Span::raw(" "));
+        if self.cfg.tree.show_types {
+            let type_str: String = if self.cfg.tree.short_types {
+                type_str.chars().take(1).collect()
+            } else {
+                type_str.to_string()
+            };
+            columns.type_width = type_str.width();
+            spans.push(Span::styled(type_str, type_style));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(description, description_style));
+        (Text::from(Line::from(spans)), columns)
+    }
+}
+
+impl FieldType {
+    fn describe(cfg: &Config, value: &Value) -> (Self, Cow<'static, str>) {
+        let separator = cfg.tree.thousands_separator.as_str();
+        match value {
+            Value::Null => (Self::Null, Cow::Borrowed("null")),
+            Value::String(s) => (Self::Str, Cow::Owned(format!("= {s:?}"))),
+            Value::Number(num) => {
+                let num = num.to_string();
+                let description = format!("= {}", group_digits(&num, separator));
+                (Self::Num, Cow::Owned(description))
+            }
+            Value::Bool(b) => {
+                let description = if *b { "= true" } else { "= false" };
+                (Self::Bool, Cow::Borrowed(description))
+            }
+            Value::Array(arr) => {
+                let description = format!(
+                    "[ {} {} ]",
+                    group_digits(&arr.len().to_string(), separator),
+                    if arr.len() > 1 { "items" } else { "item" }
+                );
+                (Self::Arr, Cow::Owned(description))
+            }
+            Value::Object(obj) => {
+                let description = format!(
+                    "{{ {} {} }}",
+                    group_digits(&obj.len().to_string(), separator),
+                    if obj.len() > 1 { "fields" } else { "field" }
+                );
+                (Self::Obj, Cow::Owned(description))
+            }
+        }
+    }
+
+    fn icon(self, cfg: &Config) -> &str {
+        let icons = &cfg.types.icons;
+        match self {
+            Self::Null => &icons.null,
+            Self::Num => &icons.num,
+            Self::Bool => &icons.bool,
+            Self::Str => &icons.str,
+            Self::Obj => &icons.obj,
+            Self::Arr => &icons.arr,
+        }
+    }
+
+    fn json_name(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Num => "number",
+            Self::Bool => "boolean",
+            Self::Str => "string",
+            Self::Obj => "object",
+            Self::Arr => "array",
+        }
+    }
+
+    fn name(self, cfg: &Config) -> &str {
+        match self {
+            Self::Null => &cfg.types.null,
+            Self::Num => &cfg.types.num,
+            Self::Bool => &cfg.types.bool,
+            Self::Str => &cfg.types.str,
+            Self::Obj => &cfg.types.obj,
+            Self::Arr => &cfg.types.arr,
+        }
+    }
+}
+
+/// Compare the fields of the objects at the same position of the array items, the
+/// fields missing in some of the items or having different values are marked. The
+/// nested objects are compared recursively, so that the exact fields are marked.
+fn mark_differences(items: Vec<(String, &Value)>, differences: &mut HashSet<String>) {
+    if items.len() < 2 {
+        return;
+    }
+    let objects: Vec<_> = items
+        .iter()
+        .filter_map(|(id, value)| value.as_object().map(|obj| (id, obj)))
+        .collect();
+    if objects.len() < items.len() {
+        return;
+    }
+
+    let mut keys: Vec<&String> = vec![];
+    for (_, obj) in objects.iter() {
+        for key in obj.keys() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    for key in keys {
+        let fields: Vec<_> = objects
+            .iter()
+            .filter_map(|(id, obj)| {
+                obj.get(key)
+                    .map(|value| (format!("{id}/{}", escape_key(key)), value))
+            })
+            .collect();
+        let same =
+            fields.len() == objects.len() && fields.windows(2).all(|pair| pair[0].1 == pair[1].1);
+        if same {
+            continue;
+        }
+        for (id, _) in fields.iter() {
+            differences.insert(id.clone());
+        }
+        mark_differences(fields, differences);
+    }
+}
+
+/// Replace the control characters except the line breaks, so that the raw escape
+/// sequences in values cannot corrupt the TUI. The tabs are expanded to spaces, since
+/// the terminal cells have no width for them. With `data.show_invisible`, the spaces
+/// and zero-width characters are replaced with visible symbols too.
+fn sanitize<'b>(cfg: &Config, s: &'b str) -> Cow<'b, str> {
+    let show_invisible = cfg.data.show_invisible;
+    if !s.chars().any(|ch| {
+        (ch.is_control() && ch != '\n') || (show_invisible && invisible_symbol(ch).is_some())
+    }) {
+        return Cow::Borrowed(s);
+    }
+    let mut sanitized = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\n' => sanitized.push(ch),
+            '\t' if show_invisible => sanitized.push_str("→   "),
+            '\t' => sanitized.push_str("    "),
+            ch if ch.is_control() => sanitized.push_str(&cfg.data.control_replacement),
+            ch if show_invisible => sanitized.push(invisible_symbol(ch).unwrap_or(ch)),
+            ch => sanitized.push(ch),
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+/// The visible symbol of the whitespace or zero-width character.
+fn invisible_symbol(ch: char) -> Option<char> {
+    match ch {
+        ' ' => Some('·'),
+        '\u{a0}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => Some('⍽'),
+        '\u{ad}'
+        | '\u{200b}'..='\u{200f}'
+        | '\u{2028}'..='\u{202e}'
+        | '\u{2060}'..='\u{2064}'
+        | '\u{feff}' => Some('◌'),
+        _ => None,
+    }
+}
+
+/// Group the digits of the integer part with the separator, like `1,234,567.89`. The
+/// numbers with exponents are kept as they are.
+pub fn group_digits<'b>(num: &'b str, separator: &str) -> Cow<'b, str> {
+    if separator.is_empty() || num.contains(['e', 'E']) {
+        return Cow::Borrowed(num);
+    }
+    let (sign, unsigned) = match num.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", num),
+    };
+    let (integer, fraction) = match unsigned.find('.') {
+        Some(idx) => unsigned.split_at(idx),
+        None => (unsigned, ""),
+    };
+    if integer.len() <= 3 || !integer.bytes().all(|b| b.is_ascii_digit()) {
+        return Cow::Borrowed(num);
+    }
+
+    let mut grouped = String::from(sign);
+    for (idx, ch) in integer.chars().enumerate() {
+        if idx > 0 && (integer.len() - idx) % 3 == 0 {
+            grouped.push_str(separator);
+        }
+        grouped.push(ch);
+    }
+    grouped.push_str(fraction);
+    Cow::Owned(grouped)
+}
+
+/// Escape the key to be a part of identify, like [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901),
+/// `~` is escaped to `~0` and `/` is escaped to `~1`. So that the keys containing `/` are
+/// not ambiguous with the nested keys.
+pub fn escape_key(key: &str) -> Cow<'_, str> {
+    if !key.contains(['~', '/']) {
+        return Cow::Borrowed(key);
+    }
+    Cow::Owned(key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// The reverse of [`escape_key`].
+pub fn unescape_key(key: &str) -> Cow<'_, str> {
+    if !key.contains('~') {
+        return Cow::Borrowed(key);
+    }
+    Cow::Owned(key.replace("~1", "/").replace("~0", "~"))
+}
+
+/// Join the keys of an item to its identify, the keys are escaped by [`escape_key`].
+pub fn join_identify(keys: &[String]) -> String {
+    let keys: Vec<_> = keys.iter().map(|key| escape_key(key)).collect();
+    keys.join("/")
+}
+
+fn sort_value(value: &Value) -> Value {
+    match value {
+        Value::Array(arr) => Value::Array(arr.iter().map(sort_value).collect()),
+        Value::Object(obj) => {
+            let mut fields: Vec<_> = obj.iter().collect();
+            fields.sort_by_key(|(field, _)| *field);
+            let obj = fields
+                .into_iter()
+                .map(|(field, value)| (field.clone(), sort_value(value)))
+                .collect();
+            Value::Object(obj)
+        }
+        _ => value.clone(),
+    }
+}
+
+impl ItemValue {
+    /// The item text in the tree overview without styles.
+    pub fn plain_text(&self, cfg: &Config) -> String {
+        let (field_type, description) = FieldType::describe(cfg, &self.value);
+        format!("{} {} {description}", self.name, field_type.name(cfg))
+    }
+
+    fn memory_size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.name.len()
+            + Self::value_memory_size(&self.value)
+            + self.data.memory_size()
+    }
+
+    fn value_memory_size(value: &Value) -> usize {
+        let children_size = match value {
+            Value::String(s) => s.len(),
+            Value::Array(arr) => arr.iter().map(Self::value_memory_size).sum(),
+            Value::Object(obj) => obj
+                .iter()
+                .map(|(field, value)| {
+                    mem::size_of::<String>() + field.len() + Self::value_memory_size(value)
+                })
+                .sum(),
+            _ => 0,
+        };
+        mem::size_of::<Value>() + children_size
     }
 }
 
 impl Data {
-    pub fn render(&self, cfg: &Config) -> Text {
+    fn memory_size(&self) -> usize {
+        match &self.display {
+            Display::Raw(text) => text.len(),
+            Display::Highlight(tokens) => tokens.iter().map(SyntaxToken::memory_size).sum(),
+        }
+    }
+
+    /// Get the offsets where each line starts, bytes for raw text and token indexes
+    /// for highlighted tokens. Used by [`Data::render_lines`] to locate the lines
+    /// without scanning the whole data.
+    pub fn line_offsets(&self) -> Vec<usize> {
+        let mut offsets = vec![0];
+        match &self.display {
+            Display::Raw(text) => {
+                offsets.extend(text.match_indices('\n').map(|(idx, _)| idx + 1));
+            }
+            Display::Highlight(tokens) => offsets.extend(
+                tokens
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, token)| matches!(token, SyntaxToken::Break))
+                    .map(|(idx, _)| idx + 1),
+            ),
+        }
+        offsets
+    }
+
+    /// Render the lines in range `[start, end)` only, the `offsets` must be returned by
+    /// [`Data::line_offsets`].
+    pub fn render_lines(
+        &self,
+        cfg: &Config,
+        offsets: &[usize],
+        start: usize,
+        end: usize,
+    ) -> Text<'_> {
+        let begin = match offsets.get(start) {
+            Some(begin) => *begin,
+            None => return Text::default(),
+        };
         match &self.display {
-            Display::Highlight(tokens) => SyntaxToken::render(cfg, tokens),
-            Display::Raw(text) => Text::from(text.as_ref()),
+            Display::Highlight(tokens) => {
+                let end = offsets.get(end).copied().unwrap_or(tokens.len());
+                SyntaxToken::render(cfg, &tokens[begin..end], self.indent_width)
+            }
+            Display::Raw(text) => {
+                let end = offsets.get(end).copied().unwrap_or(text.len());
+                Text::from(&text[begin..end])
+            }
+        }
+    }
+
+    /// The plain text of the lines in range `[start, end)`, like [`Data::render_lines`].
+    pub fn plain_lines(&self, offsets: &[usize], start: usize, end: usize) -> String {
+        let begin = match offsets.get(start) {
+            Some(begin) => *begin,
+            None => return String::new(),
+        };
+        let text = match &self.display {
+            Display::Highlight(tokens) => {
+                let end = offsets.get(end).copied().unwrap_or(tokens.len());
+                SyntaxToken::plain(&tokens[begin..end])
+            }
+            Display::Raw(text) => {
+                let end = offsets.get(end).copied().unwrap_or(text.len());
+                text[begin..end].to_string()
+            }
+        };
+        // Without the line break of the last line.
+        match text.strip_suffix('\n') {
+            Some(text) => text.to_string(),
+            None => text,
+        }
+    }
+
+    /// Append the tokens from a renderer after the value.
+    fn annotate(&mut self, cfg: &Config, tokens: Vec<SyntaxToken>) {
+        match &mut self.display {
+            Display::Highlight(data) => {
+                data.push(SyntaxToken::Indent(1));
+                data.extend(tokens);
+                (self.rows, self.columns) = SyntaxToken::get_size(data);
+            }
+            Display::Raw(text) => {
+                let text = format!("{text} {}", SyntaxToken::plain(&tokens));
+                *self = Self::raw(cfg, Cow::Owned(text));
+            }
         }
     }
 
-    fn raw(text: Cow<'static, str>) -> Self {
+    fn raw(cfg: &Config, text: Cow<'static, str>) -> Self {
+        let text = match sanitize(cfg, &text) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(sanitized) => Cow::Owned(sanitized),
+        };
         let lines: Vec<_> = text.lines().collect();
-        let long_line = lines.iter().max_by_key(|line| line.len());
         let rows = lines.len();
-        let columns = long_line.map_or(0, |line| line.len());
+        let columns = lines.iter().map(|line| line.width()).max().unwrap_or(0);
         Self {
             display: Display::Raw(text),
             rows,
             columns,
+            indent_width: 0,
         }
     }
 
-    fn highlight(tokens: Vec<SyntaxToken>) -> Self {
+    fn highlight(tokens: Vec<SyntaxToken>, indent_width: usize) -> Self {
         let (rows, columns) = SyntaxToken::get_size(&tokens);
         Self {
             display: Display::Highlight(tokens),
             rows,
             columns,
+            indent_width,
         }
     }
 
     fn null(cfg: &Config) -> Self {
         if cfg.data.disable_highlight {
-            Self::raw(Cow::Borrowed(""))
+            Self::raw(cfg, Cow::Borrowed(""))
         } else {
-            Self::highlight(vec![SyntaxToken::Null("null")])
+            Self::highlight(vec![SyntaxToken::Null("null")], 0)
         }
     }
 
     fn string(cfg: &Config, s: String) -> Self {
         if cfg.data.disable_highlight {
-            Self::raw(Cow::Owned(s))
+            Self::raw(cfg, Cow::Owned(s))
         } else {
             let lines = s.lines().collect::<Vec<_>>();
             if lines.len() > 1 {
                 let mut tokens = Vec::with_capacity(lines.len() * 2);
                 for (idx, line) in lines.iter().enumerate() {
-                    tokens.push(SyntaxToken::String(line.to_string()));
+                    tokens.push(SyntaxToken::String(sanitize(cfg, line).into_owned()));
                     if idx != lines.len() - 1 {
                         tokens.push(SyntaxToken::Break);
                     }
                 }
-                return Self::highlight(tokens);
+                return Self::highlight(tokens, 0);
             }
 
-            Self::highlight(vec![SyntaxToken::String(s)])
+            let s = match sanitize(cfg, &s) {
+                Cow::Borrowed(_) => s,
+                Cow::Owned(sanitized) => sanitized,
+            };
+            Self::highlight(vec![SyntaxToken::String(s)], 0)
         }
     }
 
     fn number(cfg: &Config, num: String) -> Self {
         if cfg.data.disable_highlight {
-            Self::raw(Cow::Owned(num))
+            Self::raw(cfg, Cow::Owned(num))
         } else {
-            Self::highlight(vec![SyntaxToken::Number(num)])
+            Self::highlight(vec![SyntaxToken::Number(num)], 0)
         }
     }
 
     fn bool(cfg: &Config, b: bool) -> Self {
         let b = if b { "true" } else { "false" };
         if cfg.data.disable_highlight {
-            Self::raw(Cow::Borrowed(b))
+            Self::raw(cfg, Cow::Borrowed(b))
         } else {
-            Self::highlight(vec![SyntaxToken::Bool(b)])
+            Self::highlight(vec![SyntaxToken::Bool(b)], 0)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render_lines() {
+        let mut cfg = Config::default();
+        let value = Value::String(String::from("l0\nl1\nl2\nl3"));
+        for disable_highlight in [false, true] {
+            cfg.data.disable_highlight = disable_highlight;
+            let data = Data::string(&cfg, value.as_str().unwrap().to_string());
+            let offsets = data.line_offsets();
+            assert_eq!(offsets.len(), 4);
+
+            let lines: Vec<String> = data
+                .render_lines(&cfg, &offsets, 1, 3)
+                .lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect();
+            assert_eq!(lines, vec!["l1", "l2"]);
+
+            assert_eq!(data.render_lines(&cfg, &offsets, 3, 10).lines.len(), 1);
+            assert!(data.render_lines(&cfg, &offsets, 5, 10).lines.is_empty());
+
+            assert_eq!(data.plain_lines(&offsets, 1, 3), "l1\nl2");
+            assert_eq!(data.plain_lines(&offsets, 3, 10), "l3");
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let cfg = Config::default();
+        assert!(matches!(sanitize(&cfg, "plain\ntext"), Cow::Borrowed(_)));
+        assert_eq!(
+            sanitize(&cfg, "\u{1b}[31mred\tx\r"),
+            "\u{fffd}[31mred    x\u{fffd}"
+        );
+
+        let mut cfg = Config::default();
+        cfg.data.show_invisible = true;
+        assert!(matches!(sanitize(&cfg, "plain\ntext"), Cow::Borrowed(_)));
+        assert_eq!(sanitize(&cfg, "a b\tc\u{a0}d\u{200b}"), "a·b→   c⍽d◌");
+    }
+
+    #[test]
+    fn test_group_digits() {
+        let test_cases = [
+            ("1234567", ",", "1,234,567"),
+            ("-1234.5678", "_", "-1_234.5678"),
+            ("123", ",", "123"),
+            ("123456", " ", "123 456"),
+            ("1.5e10", ",", "1.5e10"),
+            ("1234567", "", "1234567"),
+        ];
+        for (num, separator, expect) in test_cases {
+            assert_eq!(group_digits(num, separator), expect);
+        }
+
+        let mut cfg = Config::default();
+        cfg.tree.thousands_separator = String::from(",");
+        let data = r#"{"n": 1234567, "arr": [1, 2]}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+        assert_eq!(
+            tree.get_value("n").unwrap().plain_text(&cfg),
+            "n num = 1,234,567"
+        );
+    }
+
+    #[test]
+    fn test_escape_key() {
+        let data = r#"{"a/b": {"c": 1}, "a": {"b/c": 2}, "~x": 3}"#;
+        let cfg = Config::default();
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+        assert_eq!(tree.get_value("a~1b/c").unwrap().value, json!(1));
+        assert_eq!(tree.get_value("a/b~1c").unwrap().value, json!(2));
+        assert_eq!(tree.get_value("~0x").unwrap().value, json!(3));
+        assert!(tree.get_value("a/b/c").is_none());
+
+        let keys = [String::from("a/b"), String::from("~c")];
+        let identify = join_identify(&keys);
+        assert_eq!(identify, "a~1b/~0c");
+        let unescaped: Vec<_> = identify.split('/').map(unescape_key).collect();
+        assert_eq!(unescaped, keys);
+    }
+
+    #[test]
+    fn test_index_name() {
+        let mut cfg = Config::default();
+        cfg.tree.index_base = 1;
+        cfg.tree.index_padding = true;
+
+        let arr: Vec<_> = (0..10).map(|idx| json!({"items": [idx, idx]})).collect();
+        let data = Value::Array(arr).to_string();
+        let tree = Tree::parse(&cfg, &data, ContentType::Json).unwrap();
+        // The identifiers keep the raw indexes, like the JSON Pointer paths.
+        assert!(tree.get_value("0").is_some());
+        assert!(tree.get_value("9/items/1").is_some());
+        assert!(tree.get_value("10").is_none());
+        assert!(tree.get_value("01").is_none());
+        let item = tree.get_value("0").unwrap();
+        assert_eq!(item.name, "0");
+        // "01 obj { 1 field }"
+        assert_eq!(item.columns.name_width, 2);
+        assert_eq!(
+            tree.find("items"),
+            (0..10)
+                .map(|idx| format!("{idx}/items"))
+                .collect::<Vec<_>>()
+        );
+
+        cfg.tree.index_padding = false;
+        assert_eq!(cfg.tree.index_name(8, 100), "9");
+        cfg.tree.index_base = 0;
+        assert_eq!(cfg.tree.index_name(8, 100), "8");
+    }
+
+    #[test]
+    fn test_empty_placeholder() {
+        let mut cfg = Config::default();
+        cfg.tree.empty_placeholder = String::from("-");
+        let data = r#"{"obj": {}, "arr": [], "items": [1]}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+
+        let leaves: Vec<_> = tree
+            .items
+            .iter()
+            .map(|item| item.children().is_empty())
+            .collect();
+        assert_eq!(leaves, [true, true, false]);
+        // "obj obj -"
+        assert_eq!(tree.get_value("obj").unwrap().text_width, 9);
+
+        let data = r#"{"a": "", "b": "  ", "c": null}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+        // "a str (empty string)"
+        assert_eq!(tree.get_value("a").unwrap().text_width, 20);
+        // "b str (whitespace, 2 chars)"
+        assert_eq!(tree.get_value("b").unwrap().text_width, 27);
+    }
+
+    #[test]
+    fn test_renderers() {
+        let mut cfg = Config::default();
+        cfg.renderers
+            .insert(String::from("/spec/timeout"), String::from("duration"));
+        cfg.renderers
+            .insert(String::from("size"), String::from("bytes"));
+        let data = r#"{"spec": {"timeout": 90, "size": 2048}, "timeout": 90}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+
+        let plain = |path: &str| {
+            let data = &tree.get_value(path).unwrap().data;
+            data.plain_lines(&data.line_offsets(), 0, data.rows)
+        };
+        assert_eq!(plain("spec/timeout"), "90 (1m 30s)");
+        assert_eq!(plain("spec/size"), "2048 (2 KiB)");
+        assert_eq!(plain("timeout"), "90");
+
+        cfg.data.disable_highlight = true;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+        let data = &tree.get_value("spec/timeout").unwrap().data;
+        assert_eq!(data.plain_lines(&data.line_offsets(), 0, 1), "90 (1m 30s)");
+        assert_eq!(data.columns, 11);
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut cfg = Config::default();
+        cfg.mask.enable = true;
+        let data = r#"{"db": {"password": "abc", "user": "otree"}, "secrets": {"key": 1}}"#;
+        let tree = Tree::parse(&cfg, data, ContentType::Json).unwrap();
+
+        let plain = |tree: &Tree, path: &str| {
+            let data = &tree.get_value(path).unwrap().data;
+            data.plain_lines(&data.line_offsets(), 0, data.rows)
+        };
+        assert_eq!(plain(&tree, "db/password"), "•••");
+        assert_eq!(plain(&tree, "secrets/key"), "•••");
+        assert!(plain(&tree, "db").contains("\"password\": \"•••\""));
+        assert!(!plain(&tree, "db").contains("abc"));
+        assert!(tree.get_value("secrets/key").unwrap().masked);
+        assert!(!tree.get_value("db/user").unwrap().masked);
+
+        let tree = tree.rebuild(false, false).unwrap();
+        assert_eq!(plain(&tree, "db/password"), "abc");
+        assert!(!tree.get_value("secrets/key").unwrap().masked);
+    }
+
+    #[test]
+    fn test_partial_parse() {
+        let mut cfg = Config::default();
+        let data = "{\"id\": 1}\n{\"id\": \n{\"id\": 3}\n";
+        assert!(Tree::parse(&cfg, data, ContentType::Jsonl).is_err());
+
+        cfg.data.partial_parse = true;
+        let tree = Tree::parse(&cfg, data, ContentType::Jsonl).unwrap();
+        assert_eq!(tree.get_errors(), 1);
+        assert_eq!(tree.get_value("0/id").unwrap().value, json!(1));
+        let error = tree.get_value("1").unwrap();
+        assert!(error.value.as_str().unwrap().starts_with("line 2: "));
+        assert_eq!(tree.get_value("2/id").unwrap().value, json!(3));
+        assert_eq!(tree.rebuild(true, false).unwrap().get_errors(), 1);
+
+        let data = "name: a\n---\nname: [b\n--- # the last\nname: c\n";
+        let tree = Tree::parse(&cfg, data, ContentType::Yaml).unwrap();
+        assert_eq!(tree.get_errors(), 1);
+        assert_eq!(tree.get_value("0/name").unwrap().value, json!("a"));
+        assert!(tree.get_value("1").unwrap().value.is_string());
+        assert_eq!(tree.get_value("2/name").unwrap().value, json!("c"));
+
+        assert!(Tree::parse(&cfg, "{\"id\": \n", ContentType::Jsonl).is_err());
+        assert!(Tree::parse(&cfg, "name: [b\n", ContentType::Yaml).is_err());
+    }
+
+    #[test]
+    fn test_scalar_styles() {
+        let cfg = Config::default();
+        let data = "[server]\nstarted = 2024-01-01T00:00:00Z\nnote = \"2024-01-01T00:00:00Z\"\n";
+        let tree = Tree::parse(&cfg, data, ContentType::Toml).unwrap();
+        let expect = "started = 2024-01-01T00:00:00Z\nnote = \"2024-01-01T00:00:00Z\"\n";
+        let server = tree.get_value("server").unwrap();
+        assert_eq!(tree.value_to_string("server", &server.value), expect);
+        let started = tree.get_value("server/started").unwrap();
+        let result = tree.value_to_string("server/started", &started.value);
+        assert_eq!(result, "2024-01-01T00:00:00Z\n");
+
+        // The styles belong to the document, not the shared parser.
+        tree.get_parser().parse_styles("other = 1").unwrap();
+        let tree = tree.rebuild(true, false).unwrap();
+        assert_eq!(tree.value_to_string("server", &server.value), expect);
+
+        let subtree = tree.subtree("server", server.value.clone()).unwrap();
+        let result = subtree.parser.to_string(&server.value, &subtree.styles);
+        assert_eq!(result, expect);
+        let started = subtree.get_value("started").unwrap();
+        let result = subtree.value_to_string("started", &started.value);
+        assert_eq!(result, "2024-01-01T00:00:00Z\n");
+    }
+
+    #[test]
+    fn test_mark_differences() {
+        let value = json!([
+            {"name": "a", "port": 80, "spec": {"replicas": 1, "image": "nginx"}},
+            {"name": "b", "port": 80, "spec": {"replicas": 2, "image": "nginx"}},
+            {"name": "c", "port": 80, "spec": {"replicas": 1, "image": "nginx"}, "debug": true}
+        ]);
+        let items = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (idx.to_string(), item))
+            .collect();
+
+        let mut differences = HashSet::new();
+        mark_differences(items, &mut differences);
+
+        let mut differences: Vec<_> = differences.into_iter().collect();
+        differences.sort();
+        let expect = [
+            "0/name",
+            "0/spec",
+            "0/spec/replicas",
+            "1/name",
+            "1/spec",
+            "1/spec/replicas",
+            "2/debug",
+            "2/name",
+            "2/spec",
+            "2/spec/replicas",
+        ];
+        assert_eq!(differences, expect);
+    }
+}
@@ -1,22 +1,38 @@
-use std::io::Stdout;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use crossterm::event::{Event, KeyEvent, MouseButton, MouseEventKind};
-use ratatui::backend::CrosstermBackend;
+use anyhow::{bail, Context, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
 use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::text::{Line, Text};
 use ratatui::{Frame, Terminal};
 use serde_json::Value;
 
-use crate::clipboard::write_clipboard;
-use crate::config::keys::Action;
-use crate::config::{Config, LayoutDirection};
+use crate::aggregate::Aggregate;
+use crate::clipboard::{read_clipboard, write_clipboard};
+use crate::cmd::PrintSelection;
+use crate::config::keys::{Action, KeyContext};
+use crate::config::{Config, HookOutput, LayoutDirection, LayoutMode};
+use crate::diff::{self, Diff};
 use crate::edit::Edit;
+use crate::export;
+use crate::hook::{self, HookRunner};
+use crate::mask::Masks;
+use crate::openapi;
+use crate::pager::Pager;
+use crate::parse::{self, ContentType, ScalarStyles};
+use crate::schema::Schema;
+use crate::state::Notes;
 use crate::tree::Tree;
 use crate::ui::data_block::DataBlock;
 use crate::ui::footer::{Footer, FooterText};
 use crate::ui::header::{Header, HeaderContext};
+use crate::ui::palette::{Palette, PaletteResult};
 use crate::ui::popup::{Popup, PopupLevel};
+use crate::ui::prompt::{Prompt, PromptResult};
 use crate::ui::tree_overview::TreeOverview;
+use crate::ui::Backend;
 
 enum Refresh {
     /// Update the TUI
@@ -26,7 +42,11 @@ enum Refresh {
     /// Quit the TUI and return to the shell
     Quit,
     /// Quit the TUI and edit text
-    Edit(Edit),
+    Edit(Box<Edit>),
+    /// Quit the TUI and show text in pager
+    Pager(Box<Pager>),
+    /// Export the next rendered frame to a file
+    Export,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -34,6 +54,8 @@ enum ElementInFocus {
     TreeOverview,
     DataBlock,
     Popup,
+    Palette,
+    Prompt,
     None,
 }
 
@@ -56,6 +78,8 @@ pub struct App<'a> {
 
     layout_direction: LayoutDirection,
     layout_tree_size: u16,
+    /// Show the data block hidden by `layout.mode`.
+    preview: bool,
 
     header: Option<Header<'a>>,
     header_area: Rect,
@@ -64,22 +88,109 @@ pub struct App<'a> {
     footer: Option<Footer<'a>>,
     footer_area: Rect,
     skip_footer: bool,
-    copy_message: Option<String>,
+    footer_message: Option<String>,
 
     popup: Popup<'a>,
     before_popup_focus: ElementInFocus,
+
+    palette: Palette<'a>,
+    before_palette_focus: ElementInFocus,
+
+    prompt: Prompt<'a>,
+    before_prompt_focus: ElementInFocus,
+    prompt_kind: Option<PromptKind>,
+
+    notes: Notes,
+
+    truncated: Option<Truncated>,
+
+    pins: Vec<Pin>,
+
+    watch: Option<Watch>,
+
+    hooks: HookRunner,
+    last_selected: Option<String>,
+    /// The selected item waiting for the `on_select` hook, with the time it was selected.
+    pending_select_hook: Option<(String, Instant)>,
+
+    /// The time and position of the last click, to detect double click.
+    last_click: Option<(Instant, u16, u16)>,
+
+    /// The path shown in popup by the `show_path` action, copied by the copy actions.
+    popup_path: Option<String>,
+
+    /// In picker mode, Enter confirms the selected item and Esc cancels.
+    pick: bool,
+    picked: bool,
+
+    /// The terminal title to set while the TUI is shown.
+    title: Option<String>,
+}
+
+/// What the text input in the prompt is used for.
+enum PromptKind {
+    /// Edit the note of the item at the path.
+    Note(String),
+    /// Compare the selected item with the file.
+    DiffFile,
+    /// Aggregate the field of the array items.
+    Aggregate,
+    /// Confirm copying the large text to the clipboard, or write it to a file.
+    Copy(Action, String),
+}
+
+/// The leaf value pinned to the header by the `pin_value` action.
+struct Pin {
+    path: String,
+    name: String,
+    /// `None` means the path is no longer in the reloaded data.
+    value: Option<String>,
+    /// Shown as the mask symbol when the values are masked.
+    sensitive: bool,
+}
+
+/// Run the command again at the interval to reload the tree, see `--command`.
+struct Watch {
+    cmd: String,
+    interval: Duration,
+    next_run: Instant,
+    runner: HookRunner,
+    /// The hash of the last output, the tree is not rebuilt when it is not changed.
+    last_hash: u64,
+    /// The limitation of the output size in bytes, like `data.max_data_size`.
+    max_size: usize,
+}
+
+/// The data is too large and only the first part of it is loaded.
+struct Truncated {
+    data: String,
+    loaded: usize,
+    step: usize,
 }
 
 pub(super) enum ShowResult {
-    Edit(Edit),
+    Edit(Box<Edit>),
+    Pager(Box<Pager>),
     Quit,
 }
 
 impl<'a> App<'a> {
     const HEADER_HEIGHT: u16 = 1;
     const FOOTER_HEIGHT: u16 = 1;
+    /// Wake up at this rate to run the debounced hook and to receive the output of the
+    /// running hooks.
+    const TICK_RATE: Duration = Duration::from_millis(50);
+    /// The max time to handle a burst of events before drawing.
+    const FRAME_BUDGET: Duration = Duration::from_millis(16);
+    const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
 
     pub fn new(cfg: &'a Config, tree: Tree<'a>) -> Self {
+        let footer_message = match tree.get_errors() {
+            0 => None,
+            errors => Some(format!(
+                "{errors} part(s) failed to parse, shown as error items"
+            )),
+        };
         let footer = if cfg.footer.disable {
             None
         } else {
@@ -95,15 +206,33 @@ impl<'a> App<'a> {
             data_block_area: Rect::default(),
             layout_direction: cfg.layout.direction,
             layout_tree_size: cfg.layout.tree_size,
+            preview: false,
             header: None,
             header_area: Rect::default(),
             skip_header: false,
             footer,
             footer_area: Rect::default(),
             skip_footer: false,
-            copy_message: None,
+            footer_message,
             popup: Popup::new(cfg),
             before_popup_focus: ElementInFocus::None,
+            palette: Palette::new(cfg),
+            before_palette_focus: ElementInFocus::None,
+            prompt: Prompt::new(cfg),
+            before_prompt_focus: ElementInFocus::None,
+            prompt_kind: None,
+            notes: Notes::default(),
+            truncated: None,
+            pins: vec![],
+            watch: None,
+            hooks: HookRunner::new(),
+            last_selected: None,
+            pending_select_hook: None,
+            last_click: None,
+            popup_path: None,
+            pick: false,
+            picked: false,
+            title: None,
         }
     }
 
@@ -111,51 +240,192 @@ impl<'a> App<'a> {
         self.header = Some(Header::new(self.cfg, ctx));
     }
 
-    pub(super) fn show(
-        &mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    ) -> Result<ShowResult> {
+    /// Mark the tree as truncated, it only contains the first `loaded` bytes of the
+    /// `data`. The `load_more` action will load another `step` bytes each time.
+    pub fn set_truncated(&mut self, data: String, loaded: usize, step: usize) {
+        if let Some(header) = self.header.as_mut() {
+            header.set_data_size(loaded, data.len());
+        }
+        self.truncated = Some(Truncated { data, loaded, step });
+    }
+
+    /// Run the command every `interval` and reload the tree with its output, like
+    /// `watch`. The `data` is the first output the tree is built from, the outputs
+    /// larger than `max_size` bytes are rejected. The failures are shown in the footer
+    /// and the old tree is kept.
+    pub fn set_watch(&mut self, cmd: String, interval: Duration, data: &str, max_size: usize) {
+        self.watch = Some(Watch {
+            cmd,
+            interval,
+            next_run: Instant::now() + interval,
+            runner: HookRunner::new(),
+            last_hash: hash_data(data),
+            max_size,
+        });
+    }
+
+    pub fn set_notes(&mut self, notes: Notes) {
+        self.notes = notes;
+    }
+
+    /// Show the message in the footer on startup, unless there is one already, like the
+    /// parse errors.
+    pub fn set_footer_message(&mut self, message: String) {
+        self.footer_message.get_or_insert(message);
+    }
+
+    pub fn set_title(&mut self, source: &str) {
+        self.title = Some(format!("otree – {source}"));
+    }
+
+    pub(super) fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn set_pick(&mut self) {
+        self.pick = true;
+    }
+
+    /// Whether the picker is quit by confirming an item, rather than cancelled.
+    pub fn is_picked(&self) -> bool {
+        self.picked
+    }
+
+    /// The selection printed on exit, `None` means the picker is cancelled.
+    pub fn get_selection(&self, output: PrintSelection) -> Option<String> {
+        if self.pick && !self.picked {
+            return None;
+        }
+        match output {
+            PrintSelection::Key => self.get_selected_key(),
+            PrintSelection::Path => self.get_selected_path(),
+            PrintSelection::Value => self.get_selected_value(),
+        }
+    }
+
+    /// The key of the selected item, the index for array items.
+    fn get_selected_key(&self) -> Option<String> {
+        let identify = self.tree_overview.get_selected()?;
+        let item = self.tree_overview.get_value(identify.as_str())?;
+        Some(item.name.clone())
+    }
+
+    /// The path of the selected item, printed on exit for shell integration.
+    fn get_selected_path(&self) -> Option<String> {
+        let path = self.tree_overview.get_selected_path()?;
+        Some(format!("/{path}"))
+    }
+
+    /// The value of the selected item, the strings are not quoted.
+    fn get_selected_value(&self) -> Option<String> {
+        let identify = self.tree_overview.get_selected()?;
+        let item = self.tree_overview.get_value(identify.as_str())?;
+        match &item.value {
+            Value::String(s) => Some(s.clone()),
+            value => Some(self.tree_overview.value_to_string(&identify, value)),
+        }
+    }
+
+    pub fn run_start_hook(&mut self, data: &str) {
+        if self.cfg.hooks.on_start.is_empty() {
+            return;
+        }
+        self.hooks
+            .spawn(self.cfg.hooks.on_start.clone(), Some(data.to_string()));
+    }
+
+    pub(super) fn show(&mut self, terminal: &mut Terminal<Backend>) -> Result<ShowResult> {
         terminal.draw(|frame| self.draw(frame))?;
 
         loop {
-            let refresh = match crossterm::event::read()? {
-                Event::Key(key) => self.on_key(key),
-                Event::Mouse(mouse) => match mouse.kind {
-                    MouseEventKind::Down(MouseButton::Left) => {
-                        self.on_click(mouse.column, mouse.row)
-                    }
-                    MouseEventKind::ScrollUp => {
-                        self.on_scroll(ScrollDirection::Up, mouse.column, mouse.row)
-                    }
-                    MouseEventKind::ScrollDown => {
-                        self.on_scroll(ScrollDirection::Down, mouse.column, mouse.row)
+            let ticking = self.pending_select_hook.is_some()
+                || self.hooks.is_running()
+                || self.watch.is_some();
+            if ticking && !crossterm::event::poll(Self::TICK_RATE)? {
+                if self.on_hook_tick() {
+                    terminal.draw(|frame| self.draw(frame))?;
+                }
+                continue;
+            }
+
+            // Handle all the pending events before drawing, so that a burst of events,
+            // like fast scrolling, only causes one redraw.
+            let deadline = Instant::now() + Self::FRAME_BUDGET;
+            let mut update = false;
+            loop {
+                match self.on_event(crossterm::event::read()?) {
+                    Refresh::Update => update = true,
+                    Refresh::Skip => {}
+                    Refresh::Export => {
+                        self.export_view(terminal)?;
+                        update = false;
                     }
-                    _ => Refresh::Skip,
-                },
-                // When resize happens, we need to redraw the widgets to fit the new size
-                Event::Resize(_, _) => Refresh::Update,
-                Event::FocusGained => self.on_focus_changed(true),
-                Event::FocusLost => self.on_focus_changed(false),
-                _ => Refresh::Skip,
-            };
+                    Refresh::Edit(edit) => return Ok(ShowResult::Edit(edit)),
+                    Refresh::Pager(pager) => return Ok(ShowResult::Pager(pager)),
+                    Refresh::Quit => return Ok(ShowResult::Quit),
+                }
+                if Instant::now() >= deadline || !crossterm::event::poll(Duration::ZERO)? {
+                    break;
+                }
+            }
 
-            match refresh {
-                Refresh::Update => {
-                    terminal.draw(|frame| self.draw(frame))?;
+            if update {
+                terminal.draw(|frame| self.draw(frame))?;
+            }
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> Refresh {
+        match event {
+            Event::Key(key) => self.on_key(key),
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => self.on_click(mouse.column, mouse.row),
+                MouseEventKind::ScrollUp => {
+                    self.on_scroll(ScrollDirection::Up, mouse.column, mouse.row)
+                }
+                MouseEventKind::ScrollDown => {
+                    self.on_scroll(ScrollDirection::Down, mouse.column, mouse.row)
                 }
-                Refresh::Skip => continue,
-                Refresh::Edit(edit) => return Ok(ShowResult::Edit(edit)),
-                Refresh::Quit => return Ok(ShowResult::Quit),
+                _ => Refresh::Skip,
+            },
+            // When resize happens, we need to redraw the widgets to fit the new size
+            Event::Resize(_, _) => Refresh::Update,
+            Event::FocusGained => self.on_focus_changed(true),
+            Event::FocusLost => self.on_focus_changed(false),
+            _ => Refresh::Skip,
+        }
+    }
+
+    fn export_view(&mut self, terminal: &mut Terminal<Backend>) -> Result<()> {
+        let frame = terminal.draw(|frame| self.draw(frame))?;
+        match export::export_view(self.cfg, frame.buffer) {
+            Ok(path) => self.footer_message = Some(format!("exported view to {path}")),
+            Err(err) => {
+                let message = format!("Failed to export view: {err:#}");
+                self.popup(message, PopupLevel::Error);
             }
         }
+        terminal.draw(|frame| self.draw(frame))?;
+        Ok(())
     }
 
     fn draw(&mut self, frame: &mut Frame) {
         self.refresh_area(frame);
 
         let selected = self.tree_overview.get_selected();
-        if let Some(id) = selected {
+        if !self.cfg.hooks.on_select.is_empty() && selected != self.last_selected {
+            self.pending_select_hook = selected.clone().map(|id| (id, Instant::now()));
+            self.last_selected.clone_from(&selected);
+        }
+        if self.data_block_area.is_empty() {
+            // Hidden by the layout mode, update it when it is shown.
+            self.data_block.reset();
+        } else if let Some(id) = selected {
             if let Some(item) = self.tree_overview.get_value(id.as_str()) {
+                if self.cfg.data.show_breadcrumbs {
+                    let path = self.tree_overview.get_selected_path();
+                    self.data_block.set_breadcrumb(path);
+                }
                 self.data_block.update_item(id, item, self.data_block_area);
             } else {
                 let text = format!("Cannot find data for '{}'", id);
@@ -165,19 +435,21 @@ impl<'a> App<'a> {
             self.data_block.reset();
         }
 
-        if let Some(header) = self.header.as_ref() {
+        let pins = self.get_pins_text();
+        if let Some(header) = self.header.as_mut() {
+            header.set_memory(self.tree_overview.get_memory());
+            header.set_pins(pins);
             if !self.skip_header {
                 header.draw(frame, self.header_area);
             }
         }
 
-        if let Some(footer) = self.footer.as_ref() {
-            let text = match self.copy_message.take() {
+        if let Some(footer) = self.footer.as_mut() {
+            let text = match self.footer_message.take() {
                 Some(message) => FooterText::Message(message),
                 None => {
-                    let roots = self.tree_overview.get_root_identifies();
-                    let identify = self.tree_overview.get_selected();
-                    if roots.is_empty() && identify.is_none() {
+                    let (roots, identify) = self.tree_overview.get_display_identify();
+                    if roots.is_empty() && identify.is_empty() {
                         FooterText::None
                     } else {
                         FooterText::Identify(roots, identify)
@@ -197,27 +469,47 @@ impl<'a> App<'a> {
         }
 
         let tree_focus = matches!(self.focus, ElementInFocus::TreeOverview);
-        self.tree_overview
-            .draw(frame, self.tree_overview_area, tree_focus);
+        if let Err(err) =
+            self.tree_overview
+                .draw(frame, self.tree_overview_area, tree_focus, &self.notes)
+        {
+            let text = format!("Failed to draw tree overview: {err:#}");
+            self.popup(text, PopupLevel::Error);
+        }
 
-        let data_focus = matches!(self.focus, ElementInFocus::DataBlock);
-        self.data_block
-            .draw(frame, self.data_block_area, data_focus);
+        if !self.data_block_area.is_empty() {
+            let data_focus = matches!(self.focus, ElementInFocus::DataBlock);
+            self.data_block
+                .draw(frame, self.data_block_area, data_focus);
+        }
 
-        if matches!(self.focus, ElementInFocus::Popup) {
-            self.popup.draw(frame);
+        match self.focus {
+            ElementInFocus::Popup => self.popup.draw(frame),
+            ElementInFocus::Palette => self.palette.draw(frame),
+            ElementInFocus::Prompt => self.prompt.draw(frame),
+            _ => {}
         }
     }
 
     fn popup(&mut self, text: String, level: PopupLevel) {
-        self.popup.set_data(text, level);
-        if !matches!(self.focus, ElementInFocus::Popup | ElementInFocus::None) {
-            self.before_popup_focus = self.focus;
+        self.popup_text(Text::from(text), level);
+    }
+
+    fn popup_text(&mut self, text: Text<'static>, level: PopupLevel) {
+        self.popup.set_text(text, level);
+        self.popup_path = None;
+        match self.focus {
+            ElementInFocus::Popup | ElementInFocus::None => {}
+            // The popup replaces the palette or prompt, e.g. the executed action failed.
+            ElementInFocus::Palette => self.before_popup_focus = self.before_palette_focus,
+            ElementInFocus::Prompt => self.before_popup_focus = self.before_prompt_focus,
+            _ => self.before_popup_focus = self.focus,
         }
         self.focus = ElementInFocus::Popup;
     }
 
     fn disable_popup(&mut self) {
+        self.popup_path = None;
         if !matches!(
             self.before_popup_focus,
             ElementInFocus::Popup | ElementInFocus::None
@@ -229,6 +521,324 @@ impl<'a> App<'a> {
         self.focus = ElementInFocus::TreeOverview;
     }
 
+    fn open_palette(&mut self) -> Refresh {
+        if !matches!(
+            self.focus,
+            ElementInFocus::TreeOverview | ElementInFocus::DataBlock
+        ) {
+            return Refresh::Skip;
+        }
+        self.palette.reset();
+        self.before_palette_focus = self.focus;
+        self.focus = ElementInFocus::Palette;
+        Refresh::Update
+    }
+
+    fn show_operations(&mut self) -> Refresh {
+        let version = self
+            .tree_overview
+            .get_value("openapi")
+            .or_else(|| self.tree_overview.get_value("swagger"));
+        let paths = self.tree_overview.get_value("paths");
+        let paths = match paths {
+            Some(paths)
+                if openapi::is_openapi(version.as_ref().map(|v| &v.value), Some(&paths.value)) =>
+            {
+                paths
+            }
+            _ => {
+                self.footer_message = Some(String::from("not an OpenAPI document"));
+                return Refresh::Update;
+            }
+        };
+
+        let items: Vec<_> = openapi::operations(&paths.value)
+            .into_iter()
+            .map(|operation| {
+                let label = operation.label();
+                let keys = vec![String::from("paths"), operation.path, operation.method];
+                (label, keys)
+            })
+            .collect();
+        if items.is_empty() {
+            self.footer_message = Some(String::from("no operation in the document"));
+            return Refresh::Update;
+        }
+
+        if !matches!(
+            self.focus,
+            ElementInFocus::TreeOverview | ElementInFocus::DataBlock
+        ) {
+            return Refresh::Skip;
+        }
+        self.palette
+            .open_jumps(format!("operations ({})", items.len()), items);
+        self.before_palette_focus = self.focus;
+        self.focus = ElementInFocus::Palette;
+        Refresh::Update
+    }
+
+    fn show_root_history(&mut self) -> Refresh {
+        let roots = self.tree_overview.get_root_history();
+        if roots.is_empty() {
+            self.footer_message = Some(String::from("no earlier root to go back to"));
+            return Refresh::Update;
+        }
+
+        if !matches!(
+            self.focus,
+            ElementInFocus::TreeOverview | ElementInFocus::DataBlock
+        ) {
+            return Refresh::Skip;
+        }
+        self.palette
+            .open_roots(format!("root history ({})", roots.len()), roots);
+        self.before_palette_focus = self.focus;
+        self.focus = ElementInFocus::Palette;
+        Refresh::Update
+    }
+
+    fn close_palette(&mut self) {
+        self.focus = self.before_palette_focus;
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind, title: String, text: String) -> Refresh {
+        let focus = match self.focus {
+            ElementInFocus::TreeOverview | ElementInFocus::DataBlock => self.focus,
+            // The action can be executed by the palette, which is already closed.
+            _ => return Refresh::Skip,
+        };
+        self.prompt.open(title, text);
+        self.prompt_kind = Some(kind);
+        self.before_prompt_focus = focus;
+        self.focus = ElementInFocus::Prompt;
+        Refresh::Update
+    }
+
+    fn edit_note(&mut self) -> Refresh {
+        let path = match self.tree_overview.get_selected_path() {
+            Some(path) => path,
+            None => return Refresh::Skip,
+        };
+        let note = self.notes.get(&path).unwrap_or_default().to_string();
+        let title = format!("note for '{path}'");
+        self.open_prompt(PromptKind::Note(path), title, note)
+    }
+
+    fn show_note(&mut self) -> Refresh {
+        let path = match self.tree_overview.get_selected_path() {
+            Some(path) => path,
+            None => return Refresh::Skip,
+        };
+        match self.notes.get(&path) {
+            Some(note) => {
+                let text = format!("{path}:\n\n{note}");
+                self.popup(text, PopupLevel::Info);
+            }
+            None => self.footer_message = Some(format!("no note for '{path}'")),
+        }
+        Refresh::Update
+    }
+
+    fn save_note(&mut self, path: String, note: String) {
+        let message = if note.is_empty() {
+            format!("removed note for '{path}'")
+        } else {
+            format!("saved note for '{path}'")
+        };
+        if let Err(err) = self.notes.set(path, note) {
+            let message = format!("Failed to save note: {err:#}");
+            self.popup(message, PopupLevel::Error);
+            return;
+        }
+        self.footer_message = Some(message);
+    }
+
+    fn on_prompt_result(&mut self, result: PromptResult) -> Refresh {
+        match result {
+            PromptResult::Update => Refresh::Update,
+            PromptResult::Skip => Refresh::Skip,
+            PromptResult::Cancel => {
+                self.prompt_kind = None;
+                self.focus = self.before_prompt_focus;
+                Refresh::Update
+            }
+            PromptResult::Submit(text) => {
+                self.focus = self.before_prompt_focus;
+                match self.prompt_kind.take() {
+                    Some(PromptKind::Note(path)) => self.save_note(path, text),
+                    Some(PromptKind::DiffFile) if !text.is_empty() => {
+                        let data = fs::read_to_string(&text)
+                            .with_context(|| format!("read file '{text}'"));
+                        self.show_diff(data, &text);
+                    }
+                    Some(PromptKind::Aggregate) => self.show_aggregate(&text),
+                    Some(PromptKind::Copy(action, data)) => match text.to_lowercase().as_str() {
+                        "y" | "yes" => self.copy_text(action, data),
+                        "f" | "file" => self.copy_to_file(data),
+                        _ => self.footer_message = Some(String::from("copy cancelled")),
+                    },
+                    _ => {}
+                }
+                Refresh::Update
+            }
+        }
+    }
+
+    fn show_aggregate(&mut self, field: &str) {
+        let mut aggregate = Aggregate::new(field, &self.cfg.tree.thousands_separator);
+        // The distinct values are listed in the report, so the masked ones are hidden.
+        let visit = |id: &str, item: &Value| match self.tree_overview.mask_value_at(id, item) {
+            Some(item) => aggregate.add(&item),
+            None => aggregate.add(item),
+        };
+        let path = match self.tree_overview.visit_array(visit) {
+            Some(path) => path,
+            None => return,
+        };
+        let report = format!("aggregate '{path}':\n\n{}", aggregate.report());
+        self.popup(report, PopupLevel::Info);
+    }
+
+    /// Infer the schema of the selected item, or the root array if nothing is selected.
+    fn infer_schema(&self) -> Option<(String, Schema)> {
+        if let Some(path) = self.tree_overview.get_selected_path() {
+            let id = self.tree_overview.get_selected()?;
+            let item = self.tree_overview.get_value(&id)?;
+            let schema = match self.tree_overview.mask_value_at(&id, &item.value) {
+                Some(value) => Schema::infer(&value),
+                None => Schema::infer(&item.value),
+            };
+            return Some((path, schema));
+        }
+
+        // The examples in the schema must not show the masked values.
+        let mut schema = Schema::default();
+        let path = self.tree_overview.visit_array(|id, item| {
+            match self.tree_overview.mask_value_at(id, item) {
+                Some(item) => schema.add_array_item(&item),
+                None => schema.add_array_item(item),
+            }
+        })?;
+        Some((path, schema))
+    }
+
+    /// Compare the selected item with the `data`, the data is parsed with current
+    /// parser first, and falls back to detect its content type.
+    fn show_diff(&mut self, data: Result<String>, source: &str) {
+        let (path, item) = match self.tree_overview.get_selected() {
+            Some(id) => match self.tree_overview.get_value(&id) {
+                Some(item) => (id, item),
+                None => return,
+            },
+            None => return,
+        };
+
+        let value = data.and_then(|data| {
+            let parser = self.tree_overview.get_parser();
+            parser
+                .parse(&data)
+                .or_else(|err| match ContentType::detect(self.cfg, &data) {
+                    Some(content_type) => content_type.new_parser(self.cfg).parse(&data),
+                    None => Err(err),
+                })
+        });
+        let value = match value {
+            Ok(value) => value,
+            Err(err) => {
+                let message = format!("Failed to load data to compare: {err:#}");
+                self.popup(message, PopupLevel::Error);
+                return;
+            }
+        };
+
+        let diffs = diff::diff_values(&item.value, &value);
+        if diffs.is_empty() {
+            self.footer_message = Some(format!("'{path}' is the same as {source}"));
+            return;
+        }
+
+        let added = self.cfg.colors.popup.diff_added.style;
+        let removed = self.cfg.colors.popup.diff_removed.style;
+        let show_path = |sub_path: &str| match (path.as_str(), sub_path) {
+            (path, "") => path.to_string(),
+            (path, sub_path) => format!("{path}/{sub_path}"),
+        };
+        // The values are compared before masking, so that the changes of the masked
+        // values are still reported, but not shown.
+        let show_value = |sub_path: &str, value: &Value| {
+            let masked = self
+                .tree_overview
+                .mask_value_at(&show_path(sub_path), value);
+            serde_json::to_string(masked.as_ref().unwrap_or(value)).unwrap_or_default()
+        };
+
+        let mut lines = vec![
+            Line::from(format!("'{path}' (-) compared with {source} (+):")),
+            Line::default(),
+        ];
+        for diff in diffs {
+            match diff {
+                Diff::Added(sub_path, value) => lines.push(Line::styled(
+                    format!(
+                        "+ {}: {}",
+                        show_path(&sub_path),
+                        show_value(&sub_path, value)
+                    ),
+                    added,
+                )),
+                Diff::Removed(sub_path, value) => lines.push(Line::styled(
+                    format!(
+                        "- {}: {}",
+                        show_path(&sub_path),
+                        show_value(&sub_path, value)
+                    ),
+                    removed,
+                )),
+                Diff::Changed(sub_path, old, new) => {
+                    lines.push(Line::from(format!("~ {}", show_path(&sub_path))));
+                    let (old, new) = (show_value(&sub_path, old), show_value(&sub_path, new));
+                    lines.push(Line::styled(format!("  - {old}"), removed));
+                    lines.push(Line::styled(format!("  + {new}"), added));
+                }
+            }
+        }
+        self.popup_text(Text::from(lines), PopupLevel::Info);
+    }
+
+    fn on_palette_result(&mut self, result: PaletteResult) -> Refresh {
+        match result {
+            PaletteResult::Update => Refresh::Update,
+            PaletteResult::Skip => Refresh::Skip,
+            PaletteResult::Close => {
+                self.close_palette();
+                Refresh::Update
+            }
+            PaletteResult::Jump(keys) => {
+                self.close_palette();
+                if self.tree_overview.select_keys(keys) {
+                    self.focus = ElementInFocus::TreeOverview;
+                }
+                Refresh::Update
+            }
+            PaletteResult::Root(depth) => {
+                self.close_palette();
+                if self.tree_overview.reset_root(depth) {
+                    self.focus = ElementInFocus::TreeOverview;
+                }
+                Refresh::Update
+            }
+            PaletteResult::Execute(action) => {
+                self.close_palette();
+                match self.on_action(action) {
+                    // The palette is closed, the TUI should be updated anyway.
+                    Refresh::Skip => Refresh::Update,
+                    refresh => refresh,
+                }
+            }
+        }
+    }
+
     fn refresh_area(&mut self, frame: &Frame) {
         let tree_size = self.layout_tree_size;
         let data_size = 100_u16.saturating_sub(tree_size);
@@ -273,7 +883,7 @@ impl<'a> App<'a> {
                     self.skip_footer = false;
                     self.footer_area = Rect {
                         height: Self::FOOTER_HEIGHT,
-                        y: height,
+                        y: main_area.y + height - Self::FOOTER_HEIGHT,
                         ..main_area
                     };
                     Rect {
@@ -285,6 +895,15 @@ impl<'a> App<'a> {
             None => main_area,
         };
 
+        if !self.is_data_block_visible() {
+            self.tree_overview_area = main_area;
+            self.data_block_area = Rect::default();
+            if matches!(self.focus, ElementInFocus::DataBlock) {
+                self.focus = ElementInFocus::TreeOverview;
+            }
+            return;
+        }
+
         match self.layout_direction {
             LayoutDirection::Vertical => {
                 let vertical = Layout::vertical([
@@ -293,6 +912,20 @@ impl<'a> App<'a> {
                 ]);
                 [self.tree_overview_area, self.data_block_area] = vertical.areas(main_area);
             }
+            LayoutDirection::Horizontal if self.cfg.layout.auto_size => {
+                let width = main_area.width as usize;
+                let min = width * Config::MIN_LAYOUT_TREE_SIZE as usize / 100;
+                let max = width * tree_size as usize / 100;
+                let tree_width = self
+                    .tree_overview
+                    .get_content_width()
+                    .clamp(min, max.max(min));
+                let horizontal = Layout::horizontal([
+                    Constraint::Length(tree_width as u16),
+                    Constraint::Fill(1),
+                ]);
+                [self.tree_overview_area, self.data_block_area] = horizontal.areas(main_area);
+            }
             LayoutDirection::Horizontal => {
                 let horizontal = Layout::horizontal([
                     Constraint::Percentage(tree_size),
@@ -303,22 +936,114 @@ impl<'a> App<'a> {
         }
     }
 
+    fn is_data_block_visible(&self) -> bool {
+        if self.preview {
+            return true;
+        }
+        match self.cfg.layout.mode {
+            LayoutMode::Split => true,
+            LayoutMode::TreeOnly => false,
+            LayoutMode::Auto => self
+                .tree_overview
+                .get_selected()
+                .and_then(|id| self.tree_overview.get_value(&id))
+                .is_some_and(|item| match &item.value {
+                    // The empty objects and arrays are leaves in the tree as well.
+                    Value::Array(arr) => arr.is_empty(),
+                    Value::Object(obj) => obj.is_empty(),
+                    _ => true,
+                }),
+        }
+    }
+
     fn can_switch_to_data_block(&self) -> bool {
+        if !self.is_data_block_visible() {
+            return false;
+        }
         match self.focus {
             ElementInFocus::TreeOverview => self.tree_overview.get_selected().is_some(),
             ElementInFocus::None => true,
             ElementInFocus::DataBlock => false,
-            ElementInFocus::Popup => false,
+            ElementInFocus::Popup | ElementInFocus::Palette | ElementInFocus::Prompt => false,
         }
     }
 
     fn on_key(&mut self, key: KeyEvent) -> Refresh {
-        let action = self.cfg.keys.get_key_action(key);
-        if action.is_none() {
-            return Refresh::Skip;
+        if matches!(self.focus, ElementInFocus::Palette | ElementInFocus::Prompt)
+            && key.code == KeyCode::Char('v')
+            && key.modifiers == KeyModifiers::CONTROL
+        {
+            return self.paste_clipboard();
+        }
+        if matches!(self.focus, ElementInFocus::Palette) {
+            // The palette takes the raw keys, so that all characters can be typed.
+            let result = self.palette.on_key(key);
+            return self.on_palette_result(result);
+        }
+        if matches!(self.focus, ElementInFocus::Prompt) {
+            let result = self.prompt.on_key(key);
+            return self.on_prompt_result(result);
+        }
+
+        if self.pick {
+            if let Some(refresh) = self.on_pick_key(key) {
+                return refresh;
+            }
+        }
+
+        let context = match self.focus {
+            ElementInFocus::TreeOverview => Some(KeyContext::Tree),
+            ElementInFocus::DataBlock => Some(KeyContext::DataBlock),
+            ElementInFocus::Popup => Some(KeyContext::Popup),
+            _ => None,
+        };
+        let action = match self.cfg.keys.get_key_action(key, context) {
+            Some(action) => action,
+            None => return Refresh::Skip,
+        };
+        self.on_action(action)
+    }
+
+    /// Paste the system clipboard text into the palette or prompt input. The error is
+    /// shown in the footer rather than a popup, so that the input keeps the focus.
+    fn paste_clipboard(&mut self) -> Refresh {
+        let text = match read_clipboard() {
+            Ok(text) => text,
+            Err(err) => {
+                self.footer_message = Some(format!("Failed to paste from clipboard: {err:#}"));
+                return Refresh::Update;
+            }
+        };
+        if matches!(self.focus, ElementInFocus::Palette) {
+            let result = self.palette.paste(&text);
+            self.on_palette_result(result)
+        } else {
+            let result = self.prompt.paste(&text);
+            self.on_prompt_result(result)
+        }
+    }
+
+    /// The Enter and Esc in picker mode, they take precedence over the key bindings
+    /// outside the popup.
+    fn on_pick_key(&mut self, key: KeyEvent) -> Option<Refresh> {
+        if !key.modifiers.is_empty() {
+            return None;
+        }
+        match self.focus {
+            ElementInFocus::TreeOverview | ElementInFocus::DataBlock | ElementInFocus::None => {}
+            _ => return None,
+        }
+        match key.code {
+            KeyCode::Enter if self.tree_overview.get_selected().is_some() => {
+                self.picked = true;
+                Some(Refresh::Quit)
+            }
+            KeyCode::Esc => Some(Refresh::Quit),
+            _ => None,
         }
-        let action = action.unwrap();
+    }
 
+    fn on_action(&mut self, action: Action) -> Refresh {
         match action {
             Action::Quit => Refresh::Quit,
             Action::Switch => match self.focus {
@@ -379,53 +1104,500 @@ impl<'a> App<'a> {
                     Some(edit) => edit,
                     None => return Refresh::Skip,
                 };
-                Refresh::Edit(edit)
+                Refresh::Edit(Box::new(edit))
+            }
+            Action::OpenSubtree => self.open_subtree(),
+            Action::ShowPath => self.show_path(),
+            Action::OpenPager => {
+                let pager = match self.build_pager() {
+                    Some(pager) => pager,
+                    None => return Refresh::Skip,
+                };
+                Refresh::Pager(Box::new(pager))
             }
-            Action::CopyName | Action::CopyValue => {
+            Action::CopyName
+            | Action::CopyValue
+            | Action::CopyTree
+            | Action::CopyRawValue
+            | Action::CopyJsonString
+            | Action::CopyShellString => {
                 let text = match self.get_copy_text(action) {
                     Some(text) => text,
                     None => return Refresh::Skip,
                 };
 
-                if let Err(err) = write_clipboard(&text) {
-                    let message = format!("Failed to copy text to clipboard: {err:#}");
-                    self.popup(message, PopupLevel::Error);
-                    return Refresh::Update;
+                let confirm_size = self.cfg.export.confirm_copy_size * 1024 * 1024;
+                if confirm_size > 0 && text.len() > confirm_size {
+                    let size = humansize::format_size(text.len(), humansize::BINARY);
+                    let title = format!("copy {size}? y: clipboard, f: file");
+                    return self.open_prompt(PromptKind::Copy(action, text), title, String::new());
                 }
 
-                let size = humansize::format_size(text.len(), humansize::BINARY);
-                let copy_message = format!("copied {size} data to system clipboard");
-                self.copy_message = Some(copy_message);
+                self.copy_text(action, text);
                 Refresh::Update
             }
-            _ => {
-                // These actions are handled by the focused widget
-                if match self.focus {
-                    ElementInFocus::TreeOverview => self.tree_overview.on_key(action),
-                    ElementInFocus::DataBlock => self.data_block.on_key(action),
-                    ElementInFocus::Popup => self.popup.on_key(action),
-                    ElementInFocus::None => false,
-                } {
-                    Refresh::Update
-                } else {
-                    Refresh::Skip
+            Action::ExportView => Refresh::Export,
+            Action::CommandPalette => self.open_palette(),
+            Action::DiffClipboard => {
+                if self.tree_overview.get_selected().is_none() {
+                    return Refresh::Skip;
                 }
+                self.show_diff(read_clipboard(), "clipboard");
+                Refresh::Update
             }
-        }
-    }
-
-    fn on_click(&mut self, column: u16, row: u16) -> Refresh {
-        if matches!(self.focus, ElementInFocus::Popup) {
+            Action::DiffFile => {
+                if self.tree_overview.get_selected().is_none() {
+                    return Refresh::Skip;
+                }
+                let title = String::from("compare with file");
+                self.open_prompt(PromptKind::DiffFile, title, String::new())
+            }
+            Action::Aggregate => {
+                if self.tree_overview.visit_array(|_, _| {}).is_none() {
+                    self.footer_message = Some(String::from("aggregate only works for arrays"));
+                    return Refresh::Update;
+                }
+                let title = String::from("field to aggregate, like `user/name`, empty for items");
+                self.open_prompt(PromptKind::Aggregate, title, String::new())
+            }
+            Action::ShowSchema => {
+                let (path, schema) = match self.infer_schema() {
+                    Some(schema) => schema,
+                    None => return Refresh::Skip,
+                };
+                let text = format!("schema of '{path}':\n\n{}", schema.render());
+                self.popup(text, PopupLevel::Info);
+                Refresh::Update
+            }
+            Action::ExportSchema => {
+                let (_, schema) = match self.infer_schema() {
+                    Some(schema) => schema,
+                    None => return Refresh::Skip,
+                };
+                match export::export_schema(self.cfg, &schema.to_json_schema()) {
+                    Ok(path) => self.footer_message = Some(format!("exported schema to {path}")),
+                    Err(err) => {
+                        let message = format!("Failed to export schema: {err:#}");
+                        self.popup(message, PopupLevel::Error);
+                    }
+                }
+                Refresh::Update
+            }
+            Action::MarkDifferences => {
+                let message = if self.tree_overview.toggle_mark_differences() {
+                    "marking fields that differ across array items"
+                } else {
+                    "stopped marking differences"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            Action::RootHistory => self.show_root_history(),
+            Action::ShowOperations => self.show_operations(),
+            Action::ToggleEscape => {
+                let message = if self.data_block.toggle_escape() {
+                    "showing escaped strings"
+                } else {
+                    "showing raw strings"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            Action::ToggleHex => {
+                let message = if self.data_block.toggle_hex() {
+                    "showing binary strings as hex dump"
+                } else {
+                    "stopped showing hex dump"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            Action::SelectLines => {
+                if !matches!(self.focus, ElementInFocus::DataBlock) {
+                    if !self.can_switch_to_data_block() {
+                        return Refresh::Skip;
+                    }
+                    self.focus = ElementInFocus::DataBlock;
+                }
+                let message = if self.data_block.toggle_selection() {
+                    "selecting lines, move with up/down and copy with copy_value"
+                } else {
+                    "stopped selecting lines"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            Action::EditNote => self.edit_note(),
+            Action::ShowNote => self.show_note(),
+            Action::LoadMore => self.load_more(),
+            Action::SortKeys => {
+                if let Err(err) = self.tree_overview.toggle_sort_keys() {
+                    let message = format!("Failed to sort keys: {err:#}");
+                    self.popup(message, PopupLevel::Error);
+                    return Refresh::Update;
+                }
+                // The data of the selected item is changed, force to update it.
+                self.data_block.reset();
+
+                let message = if self.tree_overview.is_sort_keys() {
+                    "sorted keys alphabetically"
+                } else {
+                    "keys in document order"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            Action::TogglePreview => {
+                if matches!(self.cfg.layout.mode, LayoutMode::Split) {
+                    return Refresh::Skip;
+                }
+                self.preview = !self.preview;
+                Refresh::Update
+            }
+            Action::PinValue => self.pin_value(),
+            Action::RevealMasked => {
+                if !self.cfg.mask.enable {
+                    return Refresh::Skip;
+                }
+                if self.cfg.mask.redact {
+                    self.footer_message = Some(String::from("cannot reveal values in redact mode"));
+                    return Refresh::Update;
+                }
+                if let Err(err) = self.tree_overview.toggle_mask() {
+                    let message = format!("Failed to reveal values: {err:#}");
+                    self.popup(message, PopupLevel::Error);
+                    return Refresh::Update;
+                }
+                self.data_block.reset();
+
+                let message = if self.tree_overview.is_masked() {
+                    "masked sensitive values"
+                } else {
+                    "revealed sensitive values"
+                };
+                self.footer_message = Some(String::from(message));
+                Refresh::Update
+            }
+            _ => {
+                // These actions are handled by the focused widget
+                if match self.focus {
+                    ElementInFocus::TreeOverview => self.tree_overview.on_key(action),
+                    ElementInFocus::DataBlock => self.data_block.on_key(action),
+                    ElementInFocus::Popup => self.popup.on_key(action),
+                    ElementInFocus::Palette | ElementInFocus::Prompt | ElementInFocus::None => {
+                        false
+                    }
+                } {
+                    Refresh::Update
+                } else {
+                    Refresh::Skip
+                }
+            }
+        }
+    }
+
+    /// Show the full path of the selected item in popup, which may be cut in footer.
+    fn show_path(&mut self) -> Refresh {
+        let path = match self.tree_overview.get_selected_path() {
+            Some(path) => path,
+            None => return Refresh::Skip,
+        };
+        // The copied path keeps the raw indexes, like the JSON Pointer.
+        let keys: Vec<_> = path.split('/').collect();
+        let mut text = format!("/{}", self.tree_overview.display_keys(&keys).join("/"));
+        if let Some(key) = self.cfg.keys.get_action_keys(Action::CopyName).first() {
+            text.push_str(&format!("\n\npress `{key}` to copy the path"));
+        }
+        self.popup(text, PopupLevel::Info);
+        self.popup_path = Some(path);
+        Refresh::Update
+    }
+
+    /// Replace the document with the selected subtree, like opening it in a new otree.
+    fn open_subtree(&mut self) -> Refresh {
+        let identify = match self.tree_overview.get_selected() {
+            Some(identify) => identify,
+            None => return Refresh::Skip,
+        };
+        let item = match self.tree_overview.get_value(&identify) {
+            Some(item) => item,
+            None => return Refresh::Skip,
+        };
+        if !matches!(item.value, Value::Object(_) | Value::Array(_)) {
+            self.footer_message = Some(String::from("only objects and arrays can be opened"));
+            return Refresh::Update;
+        }
+        let path = self
+            .tree_overview
+            .get_selected_path()
+            .unwrap_or_else(|| identify.clone());
+
+        let size = self
+            .tree_overview
+            .value_to_string(&identify, &item.value)
+            .len();
+        let tree = match self
+            .tree_overview
+            .build_subtree(&identify, item.value.clone())
+        {
+            Ok(tree) => tree,
+            Err(err) => {
+                let message = format!("Failed to open subtree: {err:#}");
+                self.popup(message, PopupLevel::Error);
+                return Refresh::Update;
+            }
+        };
+        self.tree_overview.open_tree(tree);
+        self.data_block.reset();
+        self.focus = ElementInFocus::TreeOverview;
+
+        // The rest of the data cannot be loaded into the subtree, and the notes are
+        // attached to the paths in the whole document.
+        self.truncated = None;
+        self.notes = Notes::default();
+
+        if let Some(header) = self.header.as_mut() {
+            header.set_subtree(&path, size);
+        }
+        self.footer_message = Some(format!("opened '{path}' as a new document"));
+        Refresh::Update
+    }
+
+    fn load_more(&mut self) -> Refresh {
+        let truncated = match self.truncated.as_mut() {
+            Some(truncated) => truncated,
+            None => return Refresh::Skip,
+        };
+
+        let total = truncated.data.len();
+        let loaded = parse::truncate(&truncated.data, truncated.loaded + truncated.step);
+        if loaded <= truncated.loaded {
+            // The next line is too long to fit into the step, load it entirely.
+            let end = truncated.data[truncated.loaded..]
+                .find('\n')
+                .map_or(total, |pos| truncated.loaded + pos + 1);
+            return self.load_to(end);
+        }
+
+        self.load_to(loaded)
+    }
+
+    /// Replace the document with the value, the selection and opened items are kept.
+    fn set_value(&mut self, value: Value, styles: ScalarStyles) -> Result<()> {
+        let parser = self.tree_overview.get_parser();
+        let sort_keys = self.tree_overview.is_sort_keys();
+        let mask = self.tree_overview.is_masked();
+        let tree = Tree::from_value(self.cfg, value, styles, parser, sort_keys, mask)?;
+        self.tree_overview.reload_tree(tree)?;
+        self.refresh_pins();
+        Ok(())
+    }
+
+    fn load_to(&mut self, loaded: usize) -> Refresh {
+        let truncated = match self.truncated.as_mut() {
+            Some(truncated) => truncated,
+            None => return Refresh::Skip,
+        };
+        let parser = self.tree_overview.get_parser();
+        let (value, styles) = match parser.parse_styles(&truncated.data[..loaded]) {
+            Ok(document) => document,
+            Err(err) => {
+                let message = format!("Failed to load more data: {err:#}");
+                self.popup(message, PopupLevel::Error);
+                return Refresh::Update;
+            }
+        };
+
+        let total = truncated.data.len();
+        if let Err(err) = self.set_value(value, styles) {
+            let message = format!("Failed to load more data: {err:#}");
+            self.popup(message, PopupLevel::Error);
+            return Refresh::Update;
+        }
+
+        // Only updated after the new tree is built, the failed loading can be retried.
+        if let Some(truncated) = self.truncated.as_mut() {
+            truncated.loaded = loaded;
+        }
+        if let Some(header) = self.header.as_mut() {
+            header.set_data_size(loaded, total);
+        }
+
+        let percent = loaded * 100 / total;
+        let loaded_size = humansize::format_size(loaded, humansize::BINARY);
+        let total_size = humansize::format_size(total, humansize::BINARY);
+        self.footer_message = Some(format!(
+            "loaded {loaded_size} of {total_size} data ({percent}%)"
+        ));
+
+        if loaded >= total {
+            self.truncated = None;
+        }
+        Refresh::Update
+    }
+
+    /// Returns true if the TUI needs to be updated.
+    fn on_hook_tick(&mut self) -> bool {
+        if let Some((id, selected_at)) = self.pending_select_hook.as_ref() {
+            if selected_at.elapsed() >= Duration::from_millis(self.cfg.hooks.debounce) {
+                if let Some(cmd) = self.build_select_hook(id) {
+                    self.hooks.spawn(cmd, None);
+                }
+                self.pending_select_hook = None;
+            }
+        }
+
+        let mut update = self.on_watch_tick();
+        while let Some(result) = self.hooks.try_recv() {
+            let output = match result {
+                Ok(output) => output,
+                Err(err) => {
+                    self.popup(format!("Hook failed: {err:#}"), PopupLevel::Error);
+                    update = true;
+                    continue;
+                }
+            };
+
+            let output = output.trim();
+            if output.is_empty() {
+                continue;
+            }
+            match self.cfg.hooks.output {
+                HookOutput::Footer if self.footer.is_some() => {
+                    let line = output.lines().next().unwrap_or_default();
+                    self.footer_message = Some(line.to_string());
+                }
+                _ => self.popup(output.to_string(), PopupLevel::Info),
+            }
+            update = true;
+        }
+        update
+    }
+
+    /// Run the watch command when it is due, and reload the tree with its output.
+    /// Returns true if the TUI needs to be updated.
+    fn on_watch_tick(&mut self) -> bool {
+        let Some(watch) = self.watch.as_mut() else {
+            return false;
+        };
+        if !watch.runner.is_running() && Instant::now() >= watch.next_run {
+            watch.runner.spawn(watch.cmd.clone(), None);
+        }
+        let result = match watch.runner.try_recv() {
+            Some(result) => result,
+            None => return false,
+        };
+        // The interval starts after the command finishes, so that a slow command will
+        // not be run again immediately.
+        watch.next_run = Instant::now() + watch.interval;
+
+        let max_size = watch.max_size;
+        let last_hash = watch.last_hash;
+        let result = result.and_then(|data| {
+            let hash = hash_data(&data);
+            if hash == last_hash {
+                return Ok(None);
+            }
+            self.reload(&data, max_size)?;
+            Ok(Some(hash))
+        });
+        match result {
+            Ok(Some(hash)) => {
+                if let Some(watch) = self.watch.as_mut() {
+                    watch.last_hash = hash;
+                }
+                true
+            }
+            Ok(None) => false,
+            Err(err) => {
+                self.footer_message = Some(format!("Failed to reload: {err:#}"));
+                true
+            }
+        }
+    }
+
+    /// Replace the document with the reloaded data, it is loaded entirely like on
+    /// startup.
+    fn reload(&mut self, data: &str, max_size: usize) -> Result<()> {
+        if data.len() > max_size {
+            let size = humansize::format_size(data.len(), humansize::BINARY);
+            let max_size = humansize::format_size(max_size, humansize::BINARY);
+            bail!("the data size {size} exceeds the limitation {max_size}");
+        }
+        let (value, styles) = self.tree_overview.get_parser().parse_styles(data)?;
+        self.set_value(value, styles)?;
+
+        // The rest of the old data must not be loaded into the new document.
+        self.truncated = None;
+        if let Some(header) = self.header.as_mut() {
+            header.set_data_size(data.len(), data.len());
+        }
+        Ok(())
+    }
+
+    fn build_select_hook(&self, id: &str) -> Option<String> {
+        let item = self.tree_overview.get_value(id)?;
+        let value = match &item.value {
+            Value::String(s) => s.clone(),
+            value => self.tree_overview.value_to_string(id, value),
+        };
+
+        let cmd = hook::render_command(&self.cfg.hooks.on_select, |name| match name {
+            "path" => Some(id.to_string()),
+            "name" => Some(item.name.clone()),
+            "value" => Some(value.trim_end().to_string()),
+            _ => None,
+        });
+        Some(cmd)
+    }
+
+    fn on_click(&mut self, column: u16, row: u16) -> Refresh {
+        if matches!(self.focus, ElementInFocus::Popup) {
             self.popup.disable();
             return Refresh::Update;
         }
+        if matches!(self.focus, ElementInFocus::Palette) {
+            self.close_palette();
+            return Refresh::Update;
+        }
+        if matches!(self.focus, ElementInFocus::Prompt) {
+            return Refresh::Skip;
+        }
+
+        let now = Instant::now();
+        let double_click = matches!(self.last_click, Some((last, last_column, last_row))
+            if last_column == column && last_row == row
+                && now.duration_since(last) < Self::DOUBLE_CLICK_INTERVAL);
+        // The third click starts a new double click.
+        self.last_click = if double_click {
+            None
+        } else {
+            Some((now, column, row))
+        };
 
         if Self::get_row_inside(column, row, self.tree_overview_area).is_some() {
-            self.tree_overview.on_click(column, row);
+            self.tree_overview.on_click(column, row, double_click);
             self.focus = ElementInFocus::TreeOverview;
             return Refresh::Update;
         }
 
+        let link = self
+            .footer
+            .as_ref()
+            .and_then(|footer| footer.get_link(column, row));
+        if let Some(depth) = link {
+            return if self.tree_overview.select_ancestor(depth) {
+                self.focus = ElementInFocus::TreeOverview;
+                Refresh::Update
+            } else {
+                Refresh::Skip
+            };
+        }
+        let in_footer = self.footer_area.contains(Position { x: column, y: row });
+        if self.footer.is_some() && !self.skip_footer && in_footer {
+            return self.show_path();
+        }
+
         if Self::get_row_inside(column, row, self.data_block_area).is_some() {
             return if self.can_switch_to_data_block() {
                 self.focus = ElementInFocus::DataBlock;
@@ -446,6 +1618,13 @@ impl<'a> App<'a> {
 
             return Refresh::Skip;
         }
+        if matches!(self.focus, ElementInFocus::Palette) {
+            let result = self.palette.on_scroll(direction);
+            return self.on_palette_result(result);
+        }
+        if matches!(self.focus, ElementInFocus::Prompt) {
+            return Refresh::Skip;
+        }
 
         let update = if Self::get_row_inside(column, row, self.tree_overview_area).is_some() {
             self.tree_overview.on_scroll(direction)
@@ -498,10 +1677,30 @@ impl<'a> App<'a> {
         }
     }
 
+    fn build_pager(&self) -> Option<Pager> {
+        let identify = self.tree_overview.get_selected()?;
+        let item = self.tree_overview.get_value(identify.as_str())?;
+
+        let offsets = item.data.line_offsets();
+        let text = item.data.render_lines(self.cfg, &offsets, 0, offsets.len());
+        Some(Pager::new(self.cfg, export::text_to_ansi(&text)))
+    }
+
     fn build_edit(&self) -> Option<Edit> {
         let identify = self.tree_overview.get_selected()?;
         let item = self.tree_overview.get_value(identify.as_str())?;
 
+        // The editor is outside of the TUI, but still on the screen.
+        if item.masked {
+            return Some(Edit::new(
+                self.cfg,
+                identify,
+                self.cfg.mask.symbol.clone(),
+                "txt",
+                1,
+            ));
+        }
+
         let simple_value = match &item.value {
             Value::String(s) => Some(s.clone()),
             Value::Null => Some(String::from("null")),
@@ -510,17 +1709,155 @@ impl<'a> App<'a> {
             _ => None,
         };
 
+        // Let the editor open the file at the first line displayed in the data block.
+        let line = self.data_block.get_vertical_scroll() + 1;
+
         if let Some(simple_value) = simple_value {
-            return Some(Edit::new(self.cfg, identify, simple_value, "txt"));
+            return Some(Edit::new(self.cfg, identify, simple_value, "txt", line));
         }
 
-        let parser = self.tree_overview.get_parser();
-        let data = parser.to_string(&item.value);
-        let extension = parser.extension();
-        Some(Edit::new(self.cfg, identify, data, extension))
+        let data = match self.tree_overview.get_masked_value(&identify, &item.value) {
+            Some(value) => self.tree_overview.value_to_string(&identify, &value),
+            None => self.tree_overview.value_to_string(&identify, &item.value),
+        };
+        let extension = self.tree_overview.get_parser().extension();
+        Some(Edit::new(self.cfg, identify, data, extension, line))
+    }
+
+    /// Pin the selected leaf value to the header, or unpin it if it is pinned already.
+    /// The pins are kept by the full paths, so they still work after changing root.
+    fn pin_value(&mut self) -> Refresh {
+        let (Some(identify), Some(path)) = (
+            self.tree_overview.get_selected(),
+            self.tree_overview.get_selected_path(),
+        ) else {
+            return Refresh::Skip;
+        };
+        if let Some(idx) = self.pins.iter().position(|pin| pin.path == path) {
+            self.pins.remove(idx);
+            self.footer_message = Some(format!("unpinned '/{path}'"));
+            return Refresh::Update;
+        }
+        let Some(item) = self.tree_overview.get_value(&identify) else {
+            return Refresh::Skip;
+        };
+
+        let Some(value) = self.get_pin_value(&item.value) else {
+            self.footer_message = Some(String::from("only the leaf values can be pinned"));
+            return Refresh::Update;
+        };
+        self.pins.push(Pin {
+            name: item.name.clone(),
+            value: Some(value),
+            sensitive: item.masked || self.is_selected_sensitive(),
+            path: path.clone(),
+        });
+        self.footer_message = Some(format!("pinned '/{path}' to header"));
+        Refresh::Update
+    }
+
+    /// The short text of the value shown in the header, `None` if it cannot be pinned.
+    fn get_pin_value(&self, value: &Value) -> Option<String> {
+        const MAX_PIN_WIDTH: usize = 20;
+
+        let mut value = match value {
+            Value::Array(_) | Value::Object(_) => return None,
+            Value::String(s) => s.clone(),
+            value => serde_json::to_string(value).unwrap_or_default(),
+        };
+        if let Some((idx, _)) = value.char_indices().nth(MAX_PIN_WIDTH) {
+            value.truncate(idx);
+            value.push_str(&self.cfg.tree.ellipsis);
+        }
+        Some(value)
+    }
+
+    /// Look up the pinned paths in the new tree after the data is reloaded.
+    fn refresh_pins(&mut self) {
+        let mut pins = std::mem::take(&mut self.pins);
+        for pin in pins.iter_mut() {
+            pin.value = self
+                .tree_overview
+                .get_root_value(&pin.path)
+                .and_then(|item| self.get_pin_value(&item.value));
+        }
+        self.pins = pins;
+    }
+
+    fn get_pins_text(&self) -> String {
+        let masked = self.tree_overview.is_masked();
+        let pins: Vec<_> = self
+            .pins
+            .iter()
+            .map(|pin| {
+                let value = match pin.value.as_ref() {
+                    Some(_) if masked && pin.sensitive => &self.cfg.mask.symbol,
+                    Some(value) => value,
+                    None => "(missing)",
+                };
+                format!("{}={value}", pin.name)
+            })
+            .collect();
+        pins.join(", ")
+    }
+
+    /// Whether the selected item is, or contains, the values to mask. The copied text
+    /// is not masked, so the user is warned.
+    fn is_selected_sensitive(&self) -> bool {
+        let masks = Masks::new(self.cfg);
+        if masks.is_empty() {
+            return false;
+        }
+        let Some(identify) = self.tree_overview.get_selected() else {
+            return false;
+        };
+        let Some(item) = self.tree_overview.get_value(&identify) else {
+            return false;
+        };
+        masks.is_sensitive(&identify, &item.value)
+    }
+
+    fn copy_text(&mut self, action: Action, text: String) {
+        if let Err(err) = write_clipboard(&text) {
+            let message = format!("Failed to copy text to clipboard: {err:#}");
+            self.popup(message, PopupLevel::Error);
+            return;
+        }
+
+        self.data_block.clear_selection();
+
+        let size = humansize::format_size(text.len(), humansize::BINARY);
+        let mut message = format!("copied {size} data to system clipboard");
+        if !matches!(action, Action::CopyName) && self.is_selected_sensitive() {
+            message.push_str(", WARNING: it contains sensitive values");
+        }
+        self.footer_message = Some(message);
+    }
+
+    fn copy_to_file(&mut self, text: String) {
+        match export::export_copy(self.cfg, text) {
+            Ok(path) => self.footer_message = Some(format!("wrote copied data to {path}")),
+            Err(err) => {
+                let message = format!("Failed to write copied data: {err:#}");
+                self.popup(message, PopupLevel::Error);
+            }
+        }
     }
 
     fn get_copy_text(&self, action: Action) -> Option<String> {
+        if matches!(self.focus, ElementInFocus::Popup) {
+            return self.popup_path.as_ref().map(|path| format!("/{path}"));
+        }
+        if matches!(action, Action::CopyValue) && matches!(self.focus, ElementInFocus::DataBlock) {
+            if let Some(text) = self.data_block.get_selected_text() {
+                return Some(text);
+            }
+        }
+
+        if matches!(action, Action::CopyTree) {
+            return self.tree_overview.get_outline();
+        }
+
         let identify = self.tree_overview.get_selected()?;
         let item = self.tree_overview.get_value(identify.as_str())?;
 
@@ -528,8 +1865,930 @@ impl<'a> App<'a> {
             return Some(item.name.clone());
         }
 
-        let parser = self.tree_overview.get_parser();
-        let data = parser.to_string(&item.value);
-        Some(data)
+        let data = match &item.value {
+            Value::String(s) if !matches!(action, Action::CopyValue) => s.clone(),
+            value => self.tree_overview.value_to_string(&identify, value),
+        };
+        match action {
+            Action::CopyJsonString => serde_json::to_string(&data).ok(),
+            Action::CopyShellString => Some(hook::shell_quote(&data)),
+            _ => Some(data),
+        }
+    }
+}
+
+fn hash_data(data: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::MouseEvent;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::config::colors::Color;
+
+    const DATA: &str = r#"{"z": 1, "a": {"y": 2, "b": 3}, "m": [{"q": 1, "c": 2}]}"#;
+
+    /// Drive the app with the events and render it to a headless terminal, like
+    /// [`App::show`] without reading the real terminal.
+    struct Harness<'a> {
+        app: App<'a>,
+        terminal: Terminal<TestBackend>,
+    }
+
+    impl<'a> Harness<'a> {
+        fn new(cfg: &'a Config, data: &str) -> Self {
+            let tree = Tree::parse(cfg, data, ContentType::Json).unwrap();
+            let mut harness = Self {
+                app: App::new(cfg, tree),
+                terminal: Terminal::new(TestBackend::new(80, 20)).unwrap(),
+            };
+            harness.draw();
+            harness
+        }
+
+        fn draw(&mut self) {
+            self.terminal.draw(|frame| self.app.draw(frame)).unwrap();
+        }
+
+        /// Wait for the watch command to finish, returns whether the app is updated.
+        fn wait_watch(&mut self) -> bool {
+            let next_run = self.app.watch.as_ref().unwrap().next_run;
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let update = self.app.on_hook_tick();
+                if self.app.watch.as_ref().unwrap().next_run != next_run {
+                    self.draw();
+                    return update;
+                }
+                assert!(Instant::now() < deadline, "the watch command is not run");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        fn event(&mut self, event: Event) -> Refresh {
+            let refresh = self.app.on_event(event);
+            if matches!(refresh, Refresh::Update) {
+                self.draw();
+            }
+            refresh
+        }
+
+        fn press(&mut self, code: KeyCode) -> Refresh {
+            self.event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+        }
+
+        fn keys(&mut self, keys: &str) {
+            for ch in keys.chars() {
+                self.press(KeyCode::Char(ch));
+            }
+        }
+
+        fn click(&mut self, column: u16, row: u16) -> Refresh {
+            self.event(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            }))
+        }
+
+        fn screen(&self) -> Vec<String> {
+            let buffer = self.terminal.backend().buffer();
+            let width = buffer.area.width as usize;
+            buffer
+                .content()
+                .chunks(width)
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .collect()
+        }
+
+        fn find_row(&self, text: &str) -> Option<usize> {
+            self.screen().iter().position(|row| row.contains(text))
+        }
+    }
+
+    fn new_config() -> Config {
+        let mut cfg = Config::default();
+        cfg.parse().unwrap();
+        cfg
+    }
+
+    #[test]
+    fn test_select_and_change_root() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        assert_eq!(harness.find_row("z num = 1"), Some(1));
+
+        harness.keys("jj");
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+        assert!(harness.find_row(r#""y": 2,"#).is_some());
+
+        harness.keys("r");
+        assert_eq!(harness.app.tree_overview.get_root_identifies(), ["a"]);
+        assert_eq!(harness.find_row("y num = 2"), Some(1));
+        assert!(harness.find_row("z num = 1").is_none());
+
+        harness.press(KeyCode::Esc);
+        assert!(harness.app.tree_overview.get_root_identifies().is_empty());
+        assert_eq!(harness.find_row("z num = 1"), Some(1));
+
+        harness.keys("q");
+        assert!(matches!(harness.press(KeyCode::Char('q')), Refresh::Quit));
+    }
+
+    #[test]
+    fn test_switch_focus() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        // Nothing is selected, the data block cannot be focused.
+        assert!(matches!(harness.press(KeyCode::Tab), Refresh::Skip));
+
+        harness.keys("jj");
+        harness.press(KeyCode::Tab);
+        assert!(matches!(harness.app.focus, ElementInFocus::DataBlock));
+        // The moves scroll the data block instead of the tree.
+        harness.keys("j");
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+
+        harness.press(KeyCode::Tab);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+    }
+
+    #[test]
+    fn test_focus_symbol() {
+        let mut cfg = new_config();
+        cfg.ui.focus_symbol = String::from("*");
+        let mut harness = Harness::new(&cfg, DATA);
+        let row = &harness.screen()[0];
+        assert!(row.contains("* Tree Overview"), "{row}");
+        assert!(!row.contains("* Data Block"), "{row}");
+
+        harness.keys("j");
+        harness.press(KeyCode::Tab);
+        let row = &harness.screen()[0];
+        assert!(!row.contains("* Tree Overview"), "{row}");
+        assert!(row.contains("* Data Block"), "{row}");
+    }
+
+    #[test]
+    fn test_selected_symbol() {
+        let mut cfg = new_config();
+        cfg.tree.selected_symbol = String::from("> ");
+        cfg.tree.selected_highlight = false;
+        cfg.tree.node_closed_symbol = String::from("+ ");
+        let mut harness = Harness::new(&cfg, DATA);
+        assert!(harness.find_row("+ a obj").is_some());
+
+        harness.keys("jj");
+        assert!(harness.find_row("> + a obj").is_some());
+        assert!(harness.find_row("    z num = 1").is_some());
+        let row = harness.find_row("> + a obj").unwrap();
+        let buffer = harness.terminal.backend().buffer();
+        assert_eq!(buffer[(1, row as u16)].style(), buffer[(1, 1)].style());
+    }
+
+    #[test]
+    fn test_root_history() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("R");
+        assert!(!matches!(harness.app.focus, ElementInFocus::Palette));
+
+        harness.keys("jjjrjr");
+        assert_eq!(harness.app.tree_overview.get_root_identifies(), ["m", "0"]);
+
+        harness.keys("R");
+        assert!(matches!(harness.app.focus, ElementInFocus::Palette));
+        let palette = harness.find_row("root history (2)").unwrap();
+        let latest = harness.find_row("/m    depth 1").unwrap();
+        assert!(palette < latest && latest < harness.find_row("root  depth 0").unwrap());
+
+        harness.keys("root");
+        harness.press(KeyCode::Enter);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+        assert!(harness.app.tree_overview.get_root_identifies().is_empty());
+        assert_eq!(harness.find_row("z num = 1"), Some(1));
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let mut cfg = new_config();
+        cfg.data.show_breadcrumbs = true;
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jjjrj");
+        let row = harness.find_row("m › 0").unwrap();
+        assert_eq!(row, 1);
+        assert!(harness.screen()[row + 2].contains(r#""q": 1,"#));
+
+        harness.press(KeyCode::Esc);
+        harness.press(KeyCode::Esc);
+        assert!(harness.find_row("m › 0").is_none());
+    }
+
+    #[test]
+    fn test_remember_scroll() {
+        let items: Vec<_> = (0..50).collect();
+        let data = serde_json::json!({"a": items, "b": items}).to_string();
+        let scroll_after_switch = |remember| {
+            let mut cfg = new_config();
+            cfg.data.remember_scroll = remember;
+            let mut harness = Harness::new(&cfg, &data);
+            harness.keys("j");
+            harness.press(KeyCode::Tab);
+            harness.keys("jjj");
+            assert_eq!(harness.app.data_block.get_vertical_scroll(), 3);
+            harness.press(KeyCode::Tab);
+            harness.keys("jk");
+            harness.app.data_block.get_vertical_scroll()
+        };
+        assert_eq!(scroll_after_switch(true), 3);
+        assert_eq!(scroll_after_switch(false), 0);
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let mut cfg = Config::default();
+        cfg.ui.ascii_only = true;
+        cfg.data.indent_guides = true;
+        cfg.parse().unwrap();
+        assert_eq!(cfg.tree.node_closed_symbol, "> ");
+
+        let items: Vec<_> = (0..50).map(|idx| format!("{idx:0>60}")).collect();
+        let data = serde_json::json!({"a": {"b": items}, "c": null}).to_string();
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("jl");
+        for row in harness.screen() {
+            assert!(row.is_ascii(), "{row}");
+        }
+    }
+
+    #[test]
+    fn test_scalar_root() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, r#""hello""#);
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("root")
+        );
+        let row = harness.find_row("root str").unwrap();
+        assert!(harness.screen()[row].contains(r#""hello""#));
+
+        // Rebuilding keeps the scalar root.
+        harness.app.tree_overview.toggle_sort_keys().unwrap();
+        let item = harness.app.tree_overview.get_value("root").unwrap();
+        assert_eq!(item.value, "hello");
+    }
+
+    #[test]
+    fn test_popup() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jji");
+        assert!(matches!(harness.app.focus, ElementInFocus::Popup));
+        assert!(harness.find_row("schema of 'a'").is_some());
+
+        // The keys are taken by the popup.
+        harness.keys("j");
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+
+        harness.press(KeyCode::Esc);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+        assert!(harness.find_row("schema of 'a'").is_none());
+    }
+
+    #[test]
+    fn test_change_layout() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        assert_eq!(harness.find_row("Data Block"), Some(0));
+
+        let tree_width = harness.app.tree_overview_area.width;
+        harness.keys("[");
+        assert!(harness.app.tree_overview_area.width > tree_width);
+        harness.keys("]]");
+        assert!(harness.app.tree_overview_area.width < tree_width);
+
+        harness.keys("v");
+        let row = harness.find_row("Data Block").unwrap();
+        assert!(row > 0, "{row}");
+    }
+
+    #[test]
+    fn test_layout_mode() {
+        let mut cfg = new_config();
+        cfg.layout.mode = LayoutMode::Auto;
+        let mut harness = Harness::new(&cfg, DATA);
+        assert!(harness.find_row("Data Block").is_none());
+
+        // The `z` is a leaf.
+        harness.keys("j");
+        assert_eq!(harness.find_row("Data Block"), Some(0));
+        harness.keys("j");
+        assert!(harness.find_row("Data Block").is_none());
+        assert_eq!(harness.app.tree_overview_area.width, 80);
+
+        harness.keys("w");
+        assert_eq!(harness.find_row("Data Block"), Some(0));
+
+        cfg.layout.mode = LayoutMode::TreeOnly;
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("j");
+        assert!(harness.find_row("Data Block").is_none());
+        harness.press(KeyCode::Tab);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+    }
+
+    #[test]
+    fn test_auto_size() {
+        let mut cfg = new_config();
+        cfg.layout.auto_size = true;
+        let mut harness = Harness::new(&cfg, DATA);
+        let width = harness.app.tree_overview_area.width;
+        // The node symbol, borders and scrollbar take 5 columns.
+        assert_eq!(width as usize, "a obj { 2 fields }".len() + 5);
+
+        // Expand `m` and its first item.
+        harness.keys("jjj");
+        harness.press(KeyCode::Enter);
+        harness.keys("j");
+        harness.press(KeyCode::Enter);
+        assert!(harness.app.tree_overview_area.width > width);
+        assert_eq!(
+            harness.app.tree_overview_area.width + harness.app.data_block_area.width,
+            80
+        );
+    }
+
+    #[test]
+    fn test_expand_levels() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jjjE");
+        assert!(harness.find_row("q num = 1").is_some());
+
+        // The nested items are expanded as well.
+        let data = r#"{"a": {"b": {"c": {"d": {"e": 1}}}}}"#;
+        let mut harness = Harness::new(&cfg, data);
+        harness.keys("jlj");
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a/b")
+        );
+        harness.keys("E");
+        assert!(harness.find_row("d obj").is_some());
+        assert!(harness.find_row("e num = 1").is_none());
+    }
+
+    #[test]
+    fn test_select_sibling() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        let selected = |harness: &Harness| harness.app.tree_overview.get_selected();
+
+        // Open `a` and skip its children.
+        harness.keys("jj");
+        harness.press(KeyCode::Enter);
+        harness.keys("J");
+        assert_eq!(selected(&harness).as_deref(), Some("m"));
+        assert!(matches!(harness.press(KeyCode::Char('J')), Refresh::Skip));
+        harness.keys("K{");
+        assert_eq!(selected(&harness).as_deref(), Some("z"));
+        harness.keys("}");
+        assert_eq!(selected(&harness).as_deref(), Some("m"));
+
+        harness.keys("Kj}");
+        assert_eq!(selected(&harness).as_deref(), Some("a/b"));
+        harness.keys("{");
+        assert_eq!(selected(&harness).as_deref(), Some("a/y"));
+    }
+
+    #[test]
+    fn test_select_kind() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        let mut select = |action: Action| {
+            harness.app.on_action(action);
+            harness.draw();
+            harness.app.tree_overview.get_selected()
+        };
+
+        assert_eq!(select(Action::NextObject).as_deref(), Some("a"));
+        // The items in the closed ones are selected as well.
+        assert_eq!(select(Action::NextObject).as_deref(), Some("m/0"));
+        assert_eq!(select(Action::PrevLeaf).as_deref(), Some("a/b"));
+        assert_eq!(select(Action::NextArray).as_deref(), Some("m"));
+        assert_eq!(select(Action::NextLeaf).as_deref(), Some("m/0/q"));
+        assert_eq!(select(Action::PrevArray).as_deref(), Some("m"));
+        assert_eq!(select(Action::PrevArray).as_deref(), Some("m"));
+    }
+
+    #[test]
+    fn test_click() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        let row = harness.find_row("a obj").unwrap() as u16;
+
+        harness.click(5, row);
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+        assert!(harness.find_row("y num = 2").is_none());
+
+        // The double click toggles the item.
+        harness.click(5, row);
+        assert!(harness.find_row("y num = 2").is_some());
+    }
+
+    #[test]
+    fn test_show_path() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jj");
+        harness.press(KeyCode::Enter);
+        harness.keys("jf");
+        assert!(matches!(harness.app.focus, ElementInFocus::Popup));
+        assert!(harness.find_row("press `y` to copy the path").is_some());
+        let text = harness.app.get_copy_text(Action::CopyName);
+        assert_eq!(text.as_deref(), Some("/a/y"));
+
+        harness.press(KeyCode::Esc);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+
+        // Click the footer outside of the path parts.
+        harness.click(70, 19);
+        assert!(matches!(harness.app.focus, ElementInFocus::Popup));
+    }
+
+    #[test]
+    fn test_index_display() {
+        let mut cfg = new_config();
+        cfg.tree.index_base = 1;
+        cfg.tree.index_padding = true;
+        let items: Vec<_> = (0..10).map(|idx| serde_json::json!({"q": idx})).collect();
+        let data = serde_json::json!({"list": items}).to_string();
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("jrj");
+        assert!(harness.screen()[19].starts_with(" /list   /01 "));
+
+        harness.keys("f");
+        assert!(harness.find_row("/list/01").is_some());
+        // The copied path keeps the raw indexes.
+        let text = harness.app.get_copy_text(Action::CopyName);
+        assert_eq!(text.as_deref(), Some("/list/0"));
+    }
+
+    #[test]
+    fn test_confirm_copy() {
+        let dir = std::env::temp_dir().join(format!("otree_test_copy_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut cfg = new_config();
+        cfg.export.confirm_copy_size = 1;
+        cfg.export.dir = format!("{}", dir.display());
+
+        let data = format!(r#"{{"big": "{}"}}"#, "x".repeat(1024 * 1024));
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("jY");
+        assert!(matches!(harness.app.focus, ElementInFocus::Prompt));
+        assert!(harness.find_row("y: clipboard, f: file").is_some());
+
+        harness.press(KeyCode::Esc);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+
+        harness.keys("Yf");
+        harness.press(KeyCode::Enter);
+        assert!(harness.find_row("wrote copied data to").is_some());
+        let files: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let path = files[0].as_ref().unwrap().path();
+        assert_eq!(fs::read_to_string(path).unwrap().len(), 1024 * 1024 + 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_copy_string() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, r#"{"s": "it's \"a\"\n", "n": 1}"#);
+        harness.keys("j");
+        let copy = |action| harness.app.get_copy_text(action).unwrap();
+        assert_eq!(copy(Action::CopyValue), r#""it's \"a\"\n""#);
+        assert_eq!(copy(Action::CopyRawValue), "it's \"a\"\n");
+        assert_eq!(copy(Action::CopyJsonString), r#""it's \"a\"\n""#);
+        assert_eq!(copy(Action::CopyShellString), "'it'\\''s \"a\"\n'");
+
+        harness.keys("j");
+        let copy = |action| harness.app.get_copy_text(action).unwrap();
+        assert_eq!(copy(Action::CopyRawValue), "1");
+        assert_eq!(copy(Action::CopyJsonString), r#""1""#);
+    }
+
+    #[test]
+    fn test_pin_value() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        let ctx = HeaderContext::new(None, ContentType::Json, false, DATA.len());
+        harness.app.set_header(ctx);
+        harness.keys("j+");
+        assert!(harness.screen()[0].contains("(json) - 56 B - z=1"));
+
+        harness.keys("j+");
+        assert!(harness
+            .find_row("only the leaf values can be pinned")
+            .is_some());
+
+        harness.keys("k+");
+        assert!(!harness.screen()[0].contains("z=1"));
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jj");
+        assert_eq!(harness.find_row("z num = 1"), Some(1));
+        assert_eq!(harness.find_row("a obj"), Some(2));
+
+        harness.keys("s");
+        assert_eq!(harness.find_row("a obj"), Some(1));
+        assert_eq!(harness.find_row("m arr"), Some(2));
+        assert_eq!(harness.find_row("z num = 1"), Some(3));
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+        // The data block is updated with the sorted value.
+        let (b, y) = (harness.find_row(r#""b": 3"#), harness.find_row(r#""y": 2"#));
+        assert!(b.unwrap() < y.unwrap());
+
+        harness.keys("s");
+        assert_eq!(harness.find_row("z num = 1"), Some(1));
+        assert_eq!(harness.find_row("a obj"), Some(2));
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("a")
+        );
+        let (b, y) = (harness.find_row(r#""b": 3"#), harness.find_row(r#""y": 2"#));
+        assert!(y.unwrap() < b.unwrap());
+    }
+
+    #[test]
+    fn test_select_lines() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jjV");
+        assert!(matches!(harness.app.focus, ElementInFocus::DataBlock));
+        let copied = |harness: &Harness| harness.app.get_copy_text(Action::CopyValue).unwrap();
+        assert_eq!(copied(&harness), "{");
+
+        harness.keys("j");
+        assert_eq!(copied(&harness), "{\n  \"y\": 2,");
+
+        // The cursor stays in the lines of the value.
+        harness.keys("Gjj");
+        assert_eq!(copied(&harness), "{\n  \"y\": 2,\n  \"b\": 3\n}");
+        harness.keys("gkk");
+        assert_eq!(copied(&harness), "{");
+
+        // Stop selecting, the whole value is copied.
+        harness.keys("V");
+        assert_eq!(copied(&harness), "{\n  \"y\": 2,\n  \"b\": 3\n}");
+
+        // The selection starts from the first visible line, and can go upwards.
+        let items: Vec<_> = (0..50).collect();
+        let data = serde_json::json!({"a": items}).to_string();
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("j");
+        harness.press(KeyCode::Tab);
+        harness.keys("jjjVk");
+        assert_eq!(harness.app.data_block.get_vertical_scroll(), 2);
+        assert_eq!(copied(&harness), "  1,\n  2,");
+    }
+
+    #[test]
+    fn test_tree_ellipsis() {
+        let cfg = new_config();
+        let data = r#"{"ascii": "abcdefghijklmnopqrstuvwxyz", "wide": "一二三四五六七八九十一二三四五六七八九十", "short": "中文"}"#;
+        let harness = Harness::new(&cfg, data);
+        let buffer = harness.terminal.backend().buffer();
+        let right = buffer
+            .content()
+            .iter()
+            .position(|cell| cell.symbol() == "┓")
+            .unwrap() as u16;
+
+        assert_eq!(buffer[(right - 1, 1)].symbol(), "…");
+        assert!(harness.screen()[1].contains("abcdefghijklmn…"));
+        // The wide character cut by the ellipsis is cleared rather than left half.
+        assert_eq!(buffer[(right - 1, 2)].symbol(), "…");
+        assert_eq!(buffer[(right - 2, 2)].symbol(), " ");
+        assert!(!harness.screen()[3].contains('…'));
+    }
+
+    #[test]
+    fn test_align_columns() {
+        let mut cfg = new_config();
+        cfg.tree.align_columns = true;
+        let data = r#"{"id": 1, "name": "x", "tags": [], "a_name_wider_than_half": null}"#;
+        let harness = Harness::new(&cfg, data);
+        let screen = harness.screen();
+        assert!(screen[1].starts_with("┃  id   num  = 1 "));
+        assert!(screen[2].starts_with("┃  name str  = \"x\" "));
+        assert!(screen[3].starts_with("┃  tags arr  (empty) "));
+        // The name wider than half of the tree overview is not aligned, but its type
+        // label still widens the type column.
+        assert!(screen[4].starts_with("┃  a_name_wider_than_half null"));
+
+        cfg.tree.align_columns = false;
+        let harness = Harness::new(&cfg, data);
+        assert!(harness.screen()[1].starts_with("┃  id num = 1 "));
+    }
+
+    #[test]
+    fn test_null_and_blank_styles() {
+        let cfg = new_config();
+        let data = r#"{"a": "", "b": "  ", "c": null, "d": "x"}"#;
+        let harness = Harness::new(&cfg, data);
+        let screen = harness.screen();
+        let assert_style = |y: usize, text: &str, expect: &Color| {
+            let idx = screen[y].rfind(text).unwrap();
+            let x = screen[y][..idx].chars().count() as u16;
+            let style = harness.terminal.backend().buffer()[(x, y as u16)].style();
+            assert_eq!(style.fg, expect.style.fg, "{text}");
+            assert!(style.add_modifier.contains(expect.style.add_modifier));
+        };
+
+        let colors = &cfg.colors.tree;
+        assert_style(1, "(empty string)", &colors.empty);
+        assert_style(2, "(whitespace, 2 chars)", &colors.empty);
+        assert_style(3, "null", &colors.null);
+        assert_style(4, "= \"x\"", &colors.value);
+        assert_ne!(colors.null.style, colors.value.style);
+        assert_ne!(colors.empty.style, colors.value.style);
+    }
+
+    #[test]
+    fn test_data_block_cache() {
+        let mut cfg = new_config();
+        cfg.mask.enable = true;
+        let items: Vec<_> = (0..200).collect();
+        let data = serde_json::json!({"a": {"token": "secret", "items": items}}).to_string();
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("j");
+        assert!(harness.find_row(r#""token": "•••","#).is_some());
+
+        // Both toggles reset the data block, the cached lines must not be reused.
+        harness.keys("*");
+        assert!(harness.find_row(r#""token": "secret","#).is_some());
+        harness.keys("s");
+        // The sorted keys put the long items first.
+        assert!(harness.find_row(r#""items": ["#).is_some());
+        assert!(harness.find_row("secret").is_none());
+        harness.keys("s*");
+        assert!(harness.find_row(r#""token": "•••","#).is_some());
+
+        // Scrolling out of the rendered range renders the lines again.
+        harness.press(KeyCode::Tab);
+        harness.keys("G");
+        assert!(harness.find_row("    199").is_some());
+        harness.keys("g");
+        assert!(harness.find_row(r#""token": "•••","#).is_some());
+    }
+
+    #[test]
+    fn test_masked_reports() {
+        let mut cfg = new_config();
+        cfg.mask.enable = true;
+        let data =
+            r#"{"users": [{"name": "a", "password": "p1"}, {"name": "b", "password": "p2"}]}"#;
+        let mut harness = Harness::new(&cfg, data);
+        harness.keys("j");
+
+        harness.app.show_aggregate("password");
+        harness.draw();
+        assert!(harness.find_row("•••").is_some());
+        assert!(harness.find_row("p1").is_none());
+        harness.press(KeyCode::Esc);
+
+        let other = r#"[{"name": "a", "password": "p1"}, {"name": "c", "password": "p3"}]"#;
+        harness.app.show_diff(Ok(other.to_string()), "file");
+        harness.draw();
+        assert!(harness.find_row("~ users/1/name").is_some());
+        assert!(harness.find_row("~ users/1/password").is_some());
+        assert!(harness.find_row("p2").is_none());
+        assert!(harness.find_row("p3").is_none());
+        assert!(harness.find_row("\"c\"").is_some());
+    }
+
+    #[test]
+    fn test_startup_footer_message() {
+        let mut cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness
+            .app
+            .set_footer_message(String::from("Failed to load notes: bad state"));
+        harness.draw();
+        assert!(harness.screen()[19].starts_with("Failed to load notes: bad state"));
+
+        // The parse errors are more important.
+        cfg.data.partial_parse = true;
+        let tree = Tree::parse(&cfg, "{\"a\": 1}\n{\n", ContentType::Jsonl).unwrap();
+        let mut app = App::new(&cfg, tree);
+        app.set_footer_message(String::from("Failed to load notes: bad state"));
+        assert!(app.footer_message.unwrap().contains("failed to parse"));
+    }
+
+    #[test]
+    fn test_load_more_failed() {
+        let mut cfg = new_config();
+        cfg.data.max_nodes = 3;
+        let data = "{\"a\": 1}\n{\"b\": 2}\n{\"c\": 3}\n";
+        let tree = Tree::parse(&cfg, &data[..9], ContentType::Jsonl).unwrap();
+        let mut harness = Harness {
+            app: App::new(&cfg, tree),
+            terminal: Terminal::new(TestBackend::new(80, 20)).unwrap(),
+        };
+        let ctx = HeaderContext::new(None, ContentType::Jsonl, false, 9);
+        harness.app.set_header(ctx);
+        harness.app.set_truncated(String::from(data), 9, 9);
+
+        harness.keys("m");
+        assert!(harness.find_row("too many nodes").is_some());
+        assert_eq!(harness.app.truncated.as_ref().unwrap().loaded, 9);
+        assert!(harness.screen()[0].contains("9 B of 27 B, truncated"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_watch() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("j");
+        let cmd = String::from(r#"echo '{"z": 5}'"#);
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), DATA, usize::MAX);
+
+        assert!(harness.wait_watch());
+        assert!(harness.find_row("z num = 5").is_some());
+        assert!(harness.find_row("a obj").is_none());
+        assert_eq!(
+            harness.app.tree_overview.get_selected().as_deref(),
+            Some("z")
+        );
+
+        let cmd = String::from("exit 1");
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), DATA, usize::MAX);
+        assert!(harness.wait_watch());
+        assert!(harness.find_row("Failed to reload").is_some());
+        assert!(harness.find_row("z num = 5").is_some());
+
+        let cmd = String::from(r#"echo '{"z": 6}'"#);
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), DATA, 4);
+        assert!(harness.wait_watch());
+        assert!(harness.find_row("Failed to reload").is_some());
+        assert!(harness.find_row("z num = 5").is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_watch_pins() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        let ctx = HeaderContext::new(None, ContentType::Json, false, DATA.len());
+        harness.app.set_header(ctx);
+        harness.keys("jjlj+");
+        assert!(harness.screen()[0].contains("y=2"));
+        // The reloaded data is complete, the old truncated data is dropped.
+        harness
+            .app
+            .set_truncated(format!("{DATA}\n{DATA}"), DATA.len(), DATA.len());
+
+        let cmd = String::from(r#"echo '{"a": {"y": "new"}}'"#);
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), DATA, usize::MAX);
+        assert!(harness.wait_watch());
+        assert!(harness.screen()[0].contains("y=new"));
+        assert!(harness.screen()[0].contains("(json) - 20 B - "));
+        assert!(harness.app.truncated.is_none());
+
+        let cmd = String::from(r#"echo '{"a": {}}'"#);
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), DATA, usize::MAX);
+        assert!(harness.wait_watch());
+        assert!(harness.screen()[0].contains("y=(missing)"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_watch_keep_state() {
+        let items: Vec<_> = (0..50).collect();
+        let data = serde_json::json!({"x": {"a": items, "b": 1}}).to_string();
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, &data);
+        harness.keys("jr");
+        assert_eq!(harness.app.tree_overview.get_root_identifies(), ["x"]);
+        harness.keys("j");
+        harness.press(KeyCode::Tab);
+        harness.keys("jjj");
+        harness.press(KeyCode::Tab);
+        let selected = harness.app.tree_overview.get_selected();
+        assert_eq!(harness.app.data_block.get_vertical_scroll(), 3);
+
+        // The same output does not touch the tree.
+        let cmd = format!("echo '{data}'");
+        let output = format!("{data}\n");
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), &output, usize::MAX);
+        assert!(!harness.wait_watch());
+
+        let items: Vec<_> = (100..150).collect();
+        let data = serde_json::json!({"x": {"a": items, "b": 2}}).to_string();
+        let cmd = format!("echo '{data}'");
+        harness
+            .app
+            .set_watch(cmd, Duration::from_millis(1), "", usize::MAX);
+        assert!(harness.wait_watch());
+        assert_eq!(harness.app.tree_overview.get_root_identifies(), ["x"]);
+        assert_eq!(harness.app.tree_overview.get_selected(), selected);
+        assert_eq!(harness.app.data_block.get_vertical_scroll(), 3);
+        assert!(harness.find_row("b num = 2").is_some());
+        assert!(harness.find_row("103,").is_some());
+    }
+
+    #[test]
+    fn test_open_subtree() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("jjjO");
+        assert_eq!(harness.find_row("0 obj"), Some(1));
+        assert!(harness.find_row("z num = 1").is_none());
+
+        // The history is dropped, reset only clears the selection.
+        harness.keys("j");
+        harness.press(KeyCode::Esc);
+        assert_eq!(harness.app.tree_overview.get_selected(), None);
+        assert!(harness.find_row("z num = 1").is_none());
+    }
+
+    #[test]
+    fn test_pick() {
+        let cfg = new_config();
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.app.set_pick();
+
+        // Nothing is selected, the Enter is not confirmed.
+        assert!(matches!(harness.press(KeyCode::Enter), Refresh::Skip));
+        // The Enter confirms rather than toggles, the items are opened by moving right.
+        harness.keys("jjjlj");
+        assert!(matches!(harness.press(KeyCode::Enter), Refresh::Quit));
+        assert!(harness.app.is_picked());
+        let selection = |output| harness.app.get_selection(output);
+        assert_eq!(selection(PrintSelection::Key).as_deref(), Some("0"));
+        assert_eq!(selection(PrintSelection::Path).as_deref(), Some("/m/0"));
+        assert_eq!(
+            selection(PrintSelection::Value).as_deref(),
+            Some("{\n  \"q\": 1,\n  \"c\": 2\n}")
+        );
+
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.app.set_pick();
+        harness.keys("jf");
+        // The popup is closed by Esc, rather than cancelling the picker.
+        harness.press(KeyCode::Esc);
+        assert!(matches!(harness.app.focus, ElementInFocus::TreeOverview));
+        assert!(matches!(harness.press(KeyCode::Esc), Refresh::Quit));
+        assert!(!harness.app.is_picked());
+        // Nothing is printed for the cancelled picker, even if an item is selected.
+        assert_eq!(harness.app.get_selection(PrintSelection::Path), None);
+
+        // Without the picker, the selection is always printed.
+        let mut harness = Harness::new(&cfg, DATA);
+        harness.keys("j");
+        assert_eq!(
+            harness.app.get_selection(PrintSelection::Key).as_deref(),
+            Some("z")
+        );
     }
 }
@@ -1,15 +1,22 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use ratatui::layout::{Alignment, Margin, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::symbols::scrollbar;
+use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
     Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
 };
 use ratatui::Frame;
+use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
 
 use crate::config::keys::Action;
 use crate::config::Config;
-use crate::tree::ItemValue;
+use crate::hex;
+use crate::tree::{self, ItemValue};
 use crate::ui::app::ScrollDirection;
 
 pub(super) struct DataBlock<'a> {
@@ -28,11 +35,42 @@ pub(super) struct DataBlock<'a> {
 
     last_identify: String,
     last_area: Rect,
+
+    /// The line offsets of current item, so that only the visible lines are rendered,
+    /// see [`crate::tree::Data::line_offsets`].
+    line_offsets: Vec<usize>,
+
+    /// The rendered lines of current item, with the range of them. Reused until the
+    /// item is changed or the range is moved out by scrolling or resizing.
+    rendered: Option<(usize, usize, Text<'static>)>,
+
+    /// Show the string values in escaped representation, like `"\u001b[31m"`.
+    escape: bool,
+    /// Show the binary-looking string values as hex dump, see [`crate::hex::get_binary`].
+    hex: bool,
+    /// The escaped or hex dump text of current string value with its style, shown in
+    /// place of the data.
+    alternate: Option<(String, Style)>,
+
+    /// The selected lines, as the line where the selection starts and the cursor line.
+    /// The cursor is moved by the keys instead of scrolling while selecting.
+    selection: Option<(usize, usize)>,
+
+    /// The ancestor names of current item, shown in the first line when
+    /// `data.show_breadcrumbs` is enabled.
+    breadcrumb: Option<String>,
+
+    /// The vertical and horizontal scroll positions of the items left, by identify.
+    /// Only used when `data.remember_scroll` is enabled.
+    scrolls: HashMap<String, (usize, usize)>,
 }
 
 impl<'a> DataBlock<'a> {
     const SCROLL_RETAIN: usize = 5;
 
+    /// The lines rendered above and below the visible area.
+    const RENDER_MARGIN: usize = 10;
+
     pub(super) fn new(cfg: &'a Config) -> Self {
         Self {
             cfg,
@@ -47,10 +85,127 @@ impl<'a> DataBlock<'a> {
             horizontal_scroll_state: ScrollbarState::default(),
             last_identify: String::default(),
             last_area: Rect::default(),
+            line_offsets: vec![],
+            rendered: None,
+            escape: false,
+            hex: false,
+            alternate: None,
+            selection: None,
+            breadcrumb: None,
+            scrolls: HashMap::new(),
+        }
+    }
+
+    /// Update the breadcrumb with the full path of current item, `None` to clear it.
+    pub(super) fn set_breadcrumb(&mut self, path: Option<String>) {
+        self.breadcrumb = path.map(|path| {
+            let names: Vec<_> = path.split('/').map(tree::unescape_key).collect();
+            names.join(super::get_glyph(self.cfg, " › ", " > "))
+        });
+    }
+
+    /// The number of lines that can be shown, without the borders and breadcrumb.
+    fn get_content_height(&self) -> usize {
+        let mut height = self.last_area.height.saturating_sub(2);
+        if self.breadcrumb.is_some() {
+            height = height.saturating_sub(1);
+        }
+        height.max(1) as usize
+    }
+
+    /// Returns true if the selection is started after toggling, it starts from the
+    /// first visible line.
+    pub(super) fn toggle_selection(&mut self) -> bool {
+        if self.selection.take().is_some() || self.item.is_none() {
+            return false;
+        }
+        self.selection = Some((self.vertical_scroll, self.vertical_scroll));
+        true
+    }
+
+    pub(super) fn get_selected_text(&self) -> Option<String> {
+        let (start, end) = self.get_selected_range()?;
+        if let Some((text, _)) = self.alternate.as_ref() {
+            let lines: Vec<_> = text.lines().skip(start).take(end - start).collect();
+            return Some(lines.join("\n"));
+        }
+        let item = self.item.as_ref()?;
+        Some(item.data.plain_lines(&self.line_offsets, start, end))
+    }
+
+    pub(super) fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// The range `[start, end)` of the selected lines.
+    fn get_selected_range(&self) -> Option<(usize, usize)> {
+        let (anchor, cursor) = self.selection?;
+        Some((anchor.min(cursor), anchor.max(cursor) + 1))
+    }
+
+    fn get_rows(&self) -> usize {
+        match self.alternate.as_ref() {
+            Some((text, _)) => text.lines().count(),
+            None => self.item.as_ref().map_or(0, |item| item.data.rows),
+        }
+    }
+
+    fn move_cursor(&mut self, cursor: usize) -> bool {
+        let (anchor, last_cursor) = match self.selection {
+            Some(selection) => selection,
+            None => return false,
+        };
+        let cursor = cursor.min(self.get_rows().saturating_sub(1));
+        if cursor == last_cursor {
+            return false;
         }
+        self.selection = Some((anchor, cursor));
+
+        // Scroll to keep the cursor visible.
+        let height = self.get_content_height();
+        if cursor < self.vertical_scroll {
+            self.scroll_up(self.vertical_scroll - cursor);
+        } else if cursor >= self.vertical_scroll + height {
+            self.scroll_down(cursor + 1 - height - self.vertical_scroll);
+        }
+        true
+    }
+
+    /// Returns true if the escaped representation is shown after toggling.
+    pub(super) fn toggle_escape(&mut self) -> bool {
+        self.escape = !self.escape;
+        // Force to update the item, since its size is changed.
+        self.scrolls.remove(&self.last_identify);
+        self.last_identify = String::default();
+        self.escape
+    }
+
+    /// Returns true if the hex dump is shown after toggling.
+    pub(super) fn toggle_hex(&mut self) -> bool {
+        self.hex = !self.hex;
+        self.scrolls.remove(&self.last_identify);
+        self.last_identify = String::default();
+        self.hex
+    }
+
+    pub(super) fn get_vertical_scroll(&self) -> usize {
+        self.vertical_scroll
     }
 
     pub(super) fn on_key(&mut self, action: Action) -> bool {
+        if let Some((_, cursor)) = self.selection {
+            match action {
+                Action::MoveDown => return self.move_cursor(cursor + 1),
+                Action::MoveUp => return self.move_cursor(cursor.saturating_sub(1)),
+                Action::SelectFirst => return self.move_cursor(0),
+                Action::SelectLast => return self.move_cursor(usize::MAX),
+                Action::Reset => {
+                    self.selection = None;
+                    return true;
+                }
+                _ => {}
+            }
+        }
         match action {
             Action::MoveDown => self.scroll_down(1),
             Action::MoveUp => self.scroll_up(1),
@@ -148,13 +303,41 @@ impl<'a> DataBlock<'a> {
     }
 
     pub(super) fn update_item(&mut self, identify: String, item: Rc<ItemValue>, area: Rect) {
-        if self.last_identify == identify {
+        // The same item with a new value, like after reloading the document, keeps the
+        // scroll position and the selected lines.
+        let reloaded = self.last_identify == identify;
+        if reloaded
+            && self
+                .item
+                .as_ref()
+                .is_some_and(|last| Rc::ptr_eq(last, &item))
+        {
             return;
         }
-
+        let (scroll, selection) = if reloaded {
+            let scroll = (self.vertical_scroll, self.horizontal_scroll);
+            (Some(scroll), self.selection)
+        } else {
+            self.save_scroll();
+            (self.scrolls.get(&identify).copied(), None)
+        };
         self.reset_scroll();
 
-        let rows = item.data.rows + Self::SCROLL_RETAIN;
+        // The hex dump and escaped strings would show the masked value.
+        self.alternate = if item.masked {
+            None
+        } else {
+            self.get_alternate(&item.value)
+        };
+        let (rows, columns) = match self.alternate.as_ref() {
+            Some((text, _)) => (
+                text.lines().count(),
+                text.lines().map(|line| line.width()).max().unwrap_or(0),
+            ),
+            None => (item.data.rows, item.data.columns),
+        };
+
+        let rows = rows + Self::SCROLL_RETAIN;
         if rows > area.height as usize {
             self.can_vertical_scroll = true;
             self.vertical_scroll_last = rows.saturating_sub(area.height as usize);
@@ -163,7 +346,7 @@ impl<'a> DataBlock<'a> {
                 .content_length(self.vertical_scroll_last);
         }
 
-        let columns = item.data.columns + Self::SCROLL_RETAIN;
+        let columns = columns + Self::SCROLL_RETAIN;
         if columns > area.width as usize {
             self.can_horizontal_scroll = true;
             self.horizontal_scroll_last = columns.saturating_sub(area.width as usize);
@@ -172,14 +355,65 @@ impl<'a> DataBlock<'a> {
                 .content_length(self.horizontal_scroll_last);
         }
 
+        if let Some((vertical, horizontal)) = scroll {
+            self.vertical_scroll = vertical.min(self.vertical_scroll_last);
+            self.vertical_scroll_state = self.vertical_scroll_state.position(self.vertical_scroll);
+            self.horizontal_scroll = horizontal.min(self.horizontal_scroll_last);
+            self.horizontal_scroll_state = self
+                .horizontal_scroll_state
+                .position(self.horizontal_scroll);
+        }
+
+        self.line_offsets = item.data.line_offsets();
+        self.rendered = None;
         self.item = Some(item);
+        let rows = self.get_rows();
+        self.selection = selection.filter(|(anchor, cursor)| (*anchor).max(*cursor) < rows);
         self.last_identify = identify;
         self.last_area = area;
     }
 
+    /// Remember the scroll position of current item before leaving it.
+    fn save_scroll(&mut self) {
+        if !self.cfg.data.remember_scroll || self.last_identify.is_empty() {
+            return;
+        }
+        if self.vertical_scroll == 0 && self.horizontal_scroll == 0 {
+            self.scrolls.remove(&self.last_identify);
+            return;
+        }
+        self.scrolls.insert(
+            self.last_identify.clone(),
+            (self.vertical_scroll, self.horizontal_scroll),
+        );
+    }
+
+    fn get_alternate(&self, value: &Value) -> Option<(String, Style)> {
+        let s = match value {
+            Value::String(s) => s,
+            _ => return None,
+        };
+        if self.hex {
+            if let Some(bytes) = hex::get_binary(s) {
+                return Some((hex::dump(&bytes), self.cfg.colors.data.text.style));
+            }
+        }
+        if self.escape {
+            let escaped = serde_json::to_string(s).ok()?;
+            return Some((escaped, self.cfg.colors.data.str.style));
+        }
+        None
+    }
+
     pub(super) fn reset(&mut self) {
+        self.save_scroll();
         self.reset_scroll();
         self.item = None;
+        self.alternate = None;
+        self.line_offsets.clear();
+        self.rendered = None;
+        self.selection = None;
+        self.breadcrumb = None;
         self.last_identify = String::default();
         self.last_area = Rect::default();
     }
@@ -199,35 +433,91 @@ impl<'a> DataBlock<'a> {
     }
 
     pub(super) fn draw(&mut self, frame: &mut Frame, area: Rect, focus: bool) {
-        let (border_style, border_type) = super::get_border_style(
-            &self.cfg.colors.focus_border,
-            &self.cfg.colors.data.border,
-            focus,
-        );
-
+        let (border_style, border_set) =
+            super::get_border_style(self.cfg, &self.cfg.colors.data.border, focus);
+
+        let title = match self.get_selected_range() {
+            Some((start, end)) => format!("Data Block (lines {}-{end} selected)", start + 1),
+            None => String::from("Data Block"),
+        };
+        let title = super::get_title(self.cfg, &title, focus);
         let block = Block::new()
-            .border_type(border_type)
+            .border_set(border_set)
             .borders(Borders::ALL)
             .border_style(border_style)
             .title_alignment(Alignment::Center)
-            .title("Data Block");
-
-        let text = self
-            .item
-            .as_ref()
-            .map(|item| item.data.render(self.cfg))
-            .unwrap_or_default();
-
-        let widget = Paragraph::new(text)
-            .style(self.cfg.colors.data.text.style)
-            .block(block)
-            .scroll((self.vertical_scroll as u16, self.horizontal_scroll as u16));
+            .title(title);
+
+        // Only render the visible lines (with a margin), rendering a large value every
+        // frame is slow. The range is aligned to the margin, so that the rendered lines
+        // can be reused while scrolling in it.
+        let start =
+            (self.vertical_scroll / Self::RENDER_MARGIN).saturating_sub(1) * Self::RENDER_MARGIN;
+        let end = start + area.height as usize + Self::RENDER_MARGIN * 3;
+        let text = match (self.alternate.as_ref(), self.item.as_ref()) {
+            (Some((text, style)), _) => Text::styled(text.as_str(), *style),
+            (None, Some(item)) => {
+                let cached = matches!(self.rendered, Some((cached_start, cached_end, _))
+                    if cached_start == start && cached_end == end);
+                if !cached {
+                    let text = item
+                        .data
+                        .render_lines(self.cfg, &self.line_offsets, start, end);
+                    self.rendered = Some((start, end, into_owned(text)));
+                }
+                let (_, _, text) = self.rendered.as_ref().unwrap();
+                borrow_text(text)
+            }
+            (None, None) => Text::default(),
+        };
+        let vertical_scroll = if self.alternate.is_some() {
+            self.vertical_scroll
+        } else {
+            self.vertical_scroll - start
+        };
+
+        let block = block.style(self.cfg.colors.data.text.style);
+        let mut inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if let Some(breadcrumb) = self.breadcrumb.as_ref() {
+            if inner.height > 1 {
+                let line = Rect { height: 1, ..inner };
+                let ellipsis = super::get_glyph(self.cfg, "…", "~");
+                let widget =
+                    Paragraph::new(truncate_start(breadcrumb, ellipsis, inner.width as usize))
+                        .style(Style::default().add_modifier(Modifier::DIM));
+                frame.render_widget(widget, line);
+                inner.y += 1;
+                inner.height -= 1;
+            }
+        }
 
-        frame.render_widget(widget, area);
+        let widget =
+            Paragraph::new(text).scroll((vertical_scroll as u16, self.horizontal_scroll as u16));
+
+        frame.render_widget(widget, inner);
+
+        if let Some((start, end)) = self.get_selected_range() {
+            let style = self.cfg.colors.data.selected.style;
+            for line in start.max(self.vertical_scroll)..end {
+                let row = line - self.vertical_scroll;
+                if row >= inner.height as usize {
+                    break;
+                }
+                let row_area = Rect {
+                    y: inner.y + row as u16,
+                    height: 1,
+                    ..inner
+                };
+                frame.buffer_mut().set_style(row_area, style);
+            }
+        }
 
         if self.can_vertical_scroll {
             let vertical_scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
                 .symbols(scrollbar::VERTICAL)
+                .thumb_symbol(super::get_glyph(self.cfg, scrollbar::VERTICAL.thumb, "#"))
                 .begin_symbol(None)
                 .track_symbol(None)
                 .end_symbol(None);
@@ -246,7 +536,7 @@ impl<'a> DataBlock<'a> {
             let horizontal_scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
                 .symbols(scrollbar::HORIZONTAL)
                 .begin_symbol(None)
-                .thumb_symbol("🬋")
+                .thumb_symbol(super::get_glyph(self.cfg, "🬋", "="))
                 .track_symbol(None)
                 .end_symbol(None);
 
@@ -261,3 +551,47 @@ impl<'a> DataBlock<'a> {
         }
     }
 }
+
+/// Cut the start of the text to fit the width, the end is more relevant for a path.
+fn truncate_start<'a>(text: &'a str, ellipsis: &str, width: usize) -> Cow<'a, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut start = 0;
+    while text[start..].width() + ellipsis.width() > width {
+        start += text[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    Cow::Owned(format!("{ellipsis}{}", &text[start..]))
+}
+
+fn into_owned(text: Text) -> Text<'static> {
+    let lines: Vec<Line<'static>> = text
+        .lines
+        .into_iter()
+        .map(|line| {
+            let spans: Vec<Span<'static>> = line
+                .spans
+                .into_iter()
+                .map(|span| Span::styled(span.content.into_owned(), span.style))
+                .collect();
+            Line::from(spans).style(line.style)
+        })
+        .collect();
+    Text::from(lines).style(text.style)
+}
+
+fn borrow_text<'b>(text: &'b Text<'static>) -> Text<'b> {
+    let lines: Vec<Line<'b>> = text
+        .lines
+        .iter()
+        .map(|line| {
+            let spans: Vec<Span<'b>> = line
+                .spans
+                .iter()
+                .map(|span| Span::styled(span.content.as_ref(), span.style))
+                .collect();
+            Line::from(spans).style(line.style)
+        })
+        .collect();
+    Text::from(lines).style(text.style)
+}
@@ -3,35 +3,68 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
+use unicode_width::UnicodeWidthStr;
+
 use crate::config::Config;
 
-pub(super) enum FooterText<'a> {
-    Identify(&'a [String], Option<String>),
+pub(super) enum FooterText {
+    /// The root identifies and the escaped parts of the selected identify, for display.
+    Identify(Vec<String>, Vec<String>),
     Message(String),
     None,
 }
 
 pub(super) struct Footer<'a> {
     cfg: &'a Config,
+
+    /// The columns range of each part of the identify drawn, with the depth of the
+    /// part, so that clicking the part selects that ancestor.
+    links: Vec<(u16, u16, usize)>,
+    links_row: u16,
 }
 
 impl<'a> Footer<'a> {
     pub(super) fn new(cfg: &'a Config) -> Self {
-        Self { cfg }
+        Self {
+            cfg,
+            links: vec![],
+            links_row: 0,
+        }
     }
 
-    pub(super) fn draw(&self, frame: &mut Frame, area: Rect, text: FooterText) {
+    /// Get the depth of the identify part at the position, see [`Footer::links`].
+    pub(super) fn get_link(&self, column: u16, row: u16) -> Option<usize> {
+        if row != self.links_row {
+            return None;
+        }
+        self.links
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(&column))
+            .map(|(_, _, depth)| *depth)
+    }
+
+    pub(super) fn draw(&mut self, frame: &mut Frame, area: Rect, text: FooterText) {
+        self.links.clear();
         let line = match text {
             FooterText::Identify(roots, identify) => {
                 let mut spans = Vec::with_capacity(roots.len() * 2 + 1);
-                for root in roots {
+                for root in roots.iter() {
                     let root = format!(" /{root} ");
                     spans.push(Span::styled(root, self.cfg.colors.footer.root.style));
                     spans.push(Span::raw(" "));
                 }
 
-                if let Some(identify) = identify {
-                    let identify = format!(" /{identify} ");
+                if !identify.is_empty() {
+                    let mut column = area.x + Line::from(spans.clone()).width() as u16 + 2;
+                    for (idx, part) in identify.iter().enumerate() {
+                        let width = part.width() as u16;
+                        self.links.push((column, column + width, idx + 1));
+                        // Skip the part and the `/` after it.
+                        column += width + 1;
+                    }
+                    self.links_row = area.y;
+
+                    let identify = format!(" /{} ", identify.join("/"));
                     spans.push(Span::styled(
                         identify,
                         self.cfg.colors.footer.identify.style,
@@ -11,12 +11,20 @@ use crate::parse::ContentType;
 pub struct HeaderContext {
     version: String,
     data_source: Cow<'static, str>,
-    content_type: &'static str,
+    content_type: Cow<'static, str>,
     data_size: String,
+    memory: usize,
+    memory_size: String,
+    pins: String,
 }
 
 impl HeaderContext {
-    pub fn new(source: Option<String>, content_type: ContentType, size: usize) -> Self {
+    pub fn new(
+        source: Option<String>,
+        content_type: ContentType,
+        detected: bool,
+        size: usize,
+    ) -> Self {
         let version = format!("otree {}", env!("CARGO_PKG_VERSION"));
         let source = source.map(Cow::Owned).unwrap_or(Cow::Borrowed("stdin"));
         let content_type = match content_type {
@@ -25,6 +33,11 @@ impl HeaderContext {
             ContentType::Json => "json",
             ContentType::Jsonl => "jsonl",
         };
+        let content_type = if detected {
+            Cow::Owned(format!("{content_type}, detected"))
+        } else {
+            Cow::Borrowed(content_type)
+        };
 
         let data_size = humansize::format_size(size, humansize::BINARY);
 
@@ -33,28 +46,81 @@ impl HeaderContext {
             data_source: source,
             content_type,
             data_size,
+            memory: 0,
+            memory_size: String::new(),
+            pins: String::new(),
         }
     }
 
+    fn set_data_size(&mut self, loaded: usize, total: usize) {
+        let loaded_size = humansize::format_size(loaded, humansize::BINARY);
+        if loaded >= total {
+            self.data_size = loaded_size;
+            return;
+        }
+
+        let total_size = humansize::format_size(total, humansize::BINARY);
+        self.data_size = format!("{loaded_size} of {total_size}, truncated");
+    }
+
     fn format(&self, s: &str) -> String {
         let s = s.replace("{version}", &self.version);
         let s = s.replace("{data_source}", &self.data_source);
-        let s = s.replace("{content_type}", self.content_type);
-        s.replace("{data_size}", &self.data_size)
+        let s = s.replace("{content_type}", &self.content_type);
+        let s = s.replace("{data_size}", &self.data_size);
+        let mut s = s.replace("{memory}", &self.memory_size);
+        if s.contains("{pins}") {
+            return s.replace("{pins}", &self.pins);
+        }
+        if !self.pins.is_empty() {
+            s.push_str(" - ");
+            s.push_str(&self.pins);
+        }
+        s
     }
 }
 
 pub(super) struct Header<'a> {
     cfg: &'a Config,
+    ctx: HeaderContext,
     data: String,
 }
 
 impl<'a> Header<'a> {
     pub(super) fn new(cfg: &'a Config, ctx: HeaderContext) -> Self {
-        Self {
-            cfg,
-            data: ctx.format(&cfg.header.format),
+        let data = ctx.format(&cfg.header.format);
+        Self { cfg, ctx, data }
+    }
+
+    pub(super) fn set_data_size(&mut self, loaded: usize, total: usize) {
+        self.ctx.set_data_size(loaded, total);
+        self.data = self.ctx.format(&self.cfg.header.format);
+    }
+
+    /// The document is replaced with the subtree at `path` of it.
+    pub(super) fn set_subtree(&mut self, path: &str, size: usize) {
+        self.ctx.data_source = Cow::Owned(format!("{}#/{path}", self.ctx.data_source));
+        self.ctx.set_data_size(size, size);
+        self.data = self.ctx.format(&self.cfg.header.format);
+    }
+
+    pub(super) fn set_memory(&mut self, memory: usize) {
+        if self.ctx.memory == memory && !self.ctx.memory_size.is_empty() {
+            return;
+        }
+        self.ctx.memory = memory;
+        let size = humansize::format_size(memory, humansize::BINARY);
+        self.ctx.memory_size = format!("~{size}");
+        self.data = self.ctx.format(&self.cfg.header.format);
+    }
+
+    /// The pinned values, formatted as `name=value` and separated by commas.
+    pub(super) fn set_pins(&mut self, pins: String) {
+        if self.ctx.pins == pins {
+            return;
         }
+        self.ctx.pins = pins;
+        self.data = self.ctx.format(&self.cfg.header.format);
     }
 
     pub(super) fn draw(&self, frame: &mut Frame, area: Rect) {
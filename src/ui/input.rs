@@ -0,0 +1,256 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+/// A single line text input, the keys are handled directly rather than mapped to
+/// actions, so that the user can type any character.
+#[derive(Default)]
+pub(super) struct Input {
+    text: String,
+    /// The cursor position, in bytes.
+    cursor: usize,
+}
+
+impl Input {
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(super) fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Insert the text at the cursor, only the first line is taken since the input has
+    /// one line. Returns true if anything is inserted.
+    pub(super) fn insert(&mut self, text: &str) -> bool {
+        let line = text.lines().next().unwrap_or_default();
+        let line: String = line.chars().filter(|ch| !ch.is_control()).collect();
+        if line.is_empty() {
+            return false;
+        }
+        self.text.insert_str(self.cursor, &line);
+        self.cursor += line.len();
+        true
+    }
+
+    /// Returns true if the text or the cursor is changed.
+    pub(super) fn on_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('u') if ctrl => {
+                if self.cursor == 0 {
+                    return false;
+                }
+                self.text.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('k') if ctrl => {
+                if self.cursor == self.text.len() {
+                    return false;
+                }
+                self.text.truncate(self.cursor);
+            }
+            KeyCode::Char('w') if ctrl => {
+                let start = self.prev_word(char::is_whitespace);
+                if start == self.cursor {
+                    return false;
+                }
+                self.text.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.text.len(),
+            KeyCode::Char('b') if alt => return self.move_to(self.prev_word(is_separator)),
+            KeyCode::Char('f') if alt => return self.move_to(self.next_word(is_separator)),
+            KeyCode::Left if ctrl || alt => return self.move_to(self.prev_word(is_separator)),
+            KeyCode::Right if ctrl || alt => return self.move_to(self.next_word(is_separator)),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(ch) => {
+                self.text.insert(self.cursor, ch);
+                self.cursor += ch.len_utf8();
+            }
+            KeyCode::Backspace => match self.prev_boundary() {
+                Some(prev) => {
+                    self.text.drain(prev..self.cursor);
+                    self.cursor = prev;
+                }
+                None => return false,
+            },
+            KeyCode::Delete => match self.next_boundary() {
+                Some(next) => {
+                    self.text.drain(self.cursor..next);
+                }
+                None => return false,
+            },
+            KeyCode::Left => match self.prev_boundary() {
+                Some(prev) => self.cursor = prev,
+                None => return false,
+            },
+            KeyCode::Right => match self.next_boundary() {
+                Some(next) => self.cursor = next,
+                None => return false,
+            },
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns false if the cursor is not moved, like the other keys.
+    fn move_to(&mut self, cursor: usize) -> bool {
+        if cursor == self.cursor {
+            return false;
+        }
+        self.cursor = cursor;
+        true
+    }
+
+    /// The start of the word before the cursor, the separators right before the cursor
+    /// are skipped first, like readline.
+    fn prev_word(&self, separator: fn(char) -> bool) -> usize {
+        self.text[..self.cursor]
+            .trim_end_matches(separator)
+            .trim_end_matches(|ch| !separator(ch))
+            .len()
+    }
+
+    /// The end of the word after the cursor.
+    fn next_word(&self, separator: fn(char) -> bool) -> usize {
+        let rest = self.text[self.cursor..]
+            .trim_start_matches(separator)
+            .trim_start_matches(|ch| !separator(ch));
+        self.text.len() - rest.len()
+    }
+
+    fn prev_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(idx, _)| idx)
+    }
+
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|ch| self.cursor + ch.len_utf8())
+    }
+
+    /// Draw the text after the `prompt` in one line, and place the terminal cursor at
+    /// the input position. The text is scrolled horizontally to keep the cursor visible.
+    pub(super) fn draw(&self, frame: &mut Frame, area: Rect, prompt: &str, style: Style) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+
+        let prompt_width = prompt.width() as u16;
+        let available = area.width.saturating_sub(prompt_width + 1) as usize;
+
+        let mut start = 0;
+        while self.text[start..self.cursor].width() > available {
+            start += self.text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+
+        let line = Line::from(vec![
+            Span::raw(prompt),
+            Span::styled(&self.text[start..], style),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+
+        let x = area.x + prompt_width + self.text[start..self.cursor].width() as u16;
+        frame.set_cursor_position(Position {
+            x: x.min(area.right().saturating_sub(1)),
+            y: area.y,
+        });
+    }
+}
+
+fn is_separator(ch: char) -> bool {
+    !ch.is_alphanumeric()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn press(input: &mut Input, code: KeyCode) -> bool {
+        input.on_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_on_key() {
+        let mut input = Input::default();
+        for ch in "a中c".chars() {
+            assert!(press(&mut input, KeyCode::Char(ch)));
+        }
+        assert!(press(&mut input, KeyCode::Left));
+        assert!(press(&mut input, KeyCode::Backspace));
+        assert_eq!(input.text(), "ac");
+
+        assert!(press(&mut input, KeyCode::Char('b')));
+        assert_eq!(input.text(), "abc");
+        assert!(press(&mut input, KeyCode::Delete));
+        assert_eq!(input.text(), "ab");
+        assert!(!press(&mut input, KeyCode::Delete));
+
+        assert!(press(&mut input, KeyCode::Home));
+        assert!(!press(&mut input, KeyCode::Backspace));
+        assert!(!input.on_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)));
+
+        assert!(press(&mut input, KeyCode::End));
+        assert!(input.on_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL)));
+        assert_eq!(input.text(), "");
+    }
+
+    #[test]
+    fn test_word_keys() {
+        let alt = |code| KeyEvent::new(code, KeyModifiers::ALT);
+        let ctrl = |code| KeyEvent::new(code, KeyModifiers::CONTROL);
+
+        let mut input = Input::default();
+        input.set_text(String::from("spec.中文 items/0"));
+        assert!(input.on_key(alt(KeyCode::Char('b'))));
+        assert_eq!(&input.text()[input.cursor..], "0");
+        assert!(input.on_key(alt(KeyCode::Char('b'))));
+        assert_eq!(&input.text()[input.cursor..], "items/0");
+        assert!(input.on_key(ctrl(KeyCode::Left)));
+        assert_eq!(&input.text()[input.cursor..], "中文 items/0");
+        assert!(input.on_key(alt(KeyCode::Char('f'))));
+        assert_eq!(&input.text()[input.cursor..], " items/0");
+
+        assert!(input.on_key(ctrl(KeyCode::Char('w'))));
+        assert_eq!(input.text(), " items/0");
+        assert!(!input.on_key(ctrl(KeyCode::Char('w'))));
+        assert!(!input.on_key(alt(KeyCode::Char('b'))));
+
+        assert!(input.on_key(alt(KeyCode::Char('f'))));
+        assert!(input.on_key(ctrl(KeyCode::Char('k'))));
+        assert_eq!(input.text(), " items");
+        assert!(!input.on_key(ctrl(KeyCode::Char('k'))));
+        assert!(!input.on_key(alt(KeyCode::Char('f'))));
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut input = Input::default();
+        input.set_text(String::from("id="));
+        assert!(input.insert("4f1c\t9a\nignored"));
+        assert_eq!(input.text(), "id=4f1c9a");
+        assert!(press(&mut input, KeyCode::Char('!')));
+        assert_eq!(input.text(), "id=4f1c9a!");
+        assert!(!input.insert("\n"));
+        assert!(!input.insert(""));
+    }
+}
@@ -2,51 +2,141 @@ mod app;
 mod data_block;
 mod footer;
 mod header;
+mod input;
+mod palette;
 mod popup;
+mod prompt;
 mod tree_overview;
 
-use std::io::Stdout;
+use std::io::{self, IsTerminal, Write};
+use std::{panic, process, thread};
 
 use anyhow::{Context, Result};
 use app::ShowResult;
-use crossterm::{event, terminal};
+use crossterm::{cursor, event, terminal};
 use ratatui::backend::CrosstermBackend;
 use ratatui::style::Style;
+use ratatui::symbols::border;
 use ratatui::widgets::BorderType;
 use ratatui::Terminal;
 
 use crate::config::colors::Color;
+use crate::config::{Config, FocusBorder};
+use crate::edit;
 
 pub use app::App;
+
+/// The TUI is drawn to stdout, or stderr when stdout is not a terminal, like
+/// `$(otree ...)`. So that stdout is left for the output on exit.
+type Backend = CrosstermBackend<Box<dyn Write>>;
+
+fn output() -> Box<dyn Write> {
+    if io::stdout().is_terminal() {
+        Box::new(io::stdout())
+    } else {
+        Box::new(io::stderr())
+    }
+}
 pub use header::HeaderContext;
 
-fn get_border_style(focus_color: &Color, normal_color: &Color, focus: bool) -> (Style, BorderType) {
-    let color = if focus { focus_color } else { normal_color };
-    let border_type = if color.bold {
-        BorderType::Thick
+fn get_border_style(cfg: &Config, normal_color: &Color, focus: bool) -> (Style, border::Set) {
+    let (style, border_type) = if focus {
+        let color = &cfg.colors.focus_border;
+        let border_type = match cfg.ui.focus_border {
+            FocusBorder::Auto if color.bold => BorderType::Thick,
+            FocusBorder::Auto | FocusBorder::Plain => BorderType::Plain,
+            FocusBorder::Thick => BorderType::Thick,
+            FocusBorder::Double => BorderType::Double,
+            FocusBorder::Rounded => BorderType::Rounded,
+        };
+        (color.style, border_type)
+    } else if normal_color.bold {
+        (normal_color.style, BorderType::Thick)
     } else {
-        BorderType::Plain
+        (normal_color.style, BorderType::Plain)
     };
 
-    (color.style, border_type)
+    if !cfg.ui.ascii_only {
+        return (style, border_type.to_border_set());
+    }
+    let border_set = match border_type {
+        BorderType::Plain | BorderType::Rounded => ASCII_BORDER,
+        _ => ASCII_THICK_BORDER,
+    };
+    (style, border_set)
 }
 
-pub fn start(mut app: App) -> Result<()> {
-    let mut terminal = new_terminal()?;
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// The thick and double borders in ASCII, told from the plain ones by the horizontals.
+const ASCII_THICK_BORDER: border::Set = border::Set {
+    horizontal_top: "=",
+    horizontal_bottom: "=",
+    ..ASCII_BORDER
+};
+
+/// Choose the ASCII glyph when `ui.ascii_only` is enabled.
+fn get_glyph(cfg: &Config, unicode: &'static str, ascii: &'static str) -> &'static str {
+    if cfg.ui.ascii_only {
+        ascii
+    } else {
+        unicode
+    }
+}
+
+/// The title of the widget, with `ui.focus_symbol` when it is in focus.
+fn get_title(cfg: &Config, title: &str, focus: bool) -> String {
+    if !focus || cfg.ui.focus_symbol.is_empty() {
+        return title.to_string();
+    }
+    format!("{} {title}", cfg.ui.focus_symbol)
+}
+
+/// Restore the terminal on panics and termination signals. This changes the process
+/// globals, so it is only done by the otree command, not the embedded viewer.
+pub fn set_process_handlers(title: bool) -> Result<()> {
+    set_panic_hook();
+    #[cfg(unix)]
+    set_signal_handler(title)?;
+    Ok(())
+}
+
+pub fn start(cfg: &Config, app: &mut App) -> Result<()> {
+    let mouse = cfg.ui.mouse;
+    let mut terminal = new_terminal(mouse)?;
+    if let Some(title) = app.get_title() {
+        set_title(title)?;
+    }
     let mut result: Result<()> = Ok(());
 
     loop {
-        let show_result = app.show(&mut terminal);
-        if let Err(err) = show_result {
-            result = Err(err);
-            break;
-        }
+        let show_result = match app.show(&mut terminal) {
+            Ok(show_result) => show_result,
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        };
 
-        match show_result.unwrap() {
+        match show_result {
             ShowResult::Edit(edit) => {
-                restore(&mut terminal)?;
+                restore(&mut terminal, mouse)?;
                 edit.run();
-                terminal = new_terminal()?;
+                terminal = new_terminal(mouse)?;
+            }
+            ShowResult::Pager(pager) => {
+                restore(&mut terminal, mouse)?;
+                pager.run();
+                terminal = new_terminal(mouse)?;
             }
             ShowResult::Quit => break,
         }
@@ -55,32 +145,96 @@ pub fn start(mut app: App) -> Result<()> {
     // Regardless of how the TUI app executes, we should always restore the terminal.
     // Otherwise, if the app encounters an error (such as a draw error), the user's terminal
     // will become a mess.
-    restore(&mut terminal)?;
+    restore(&mut terminal, mouse)?;
+    if app.get_title().is_some() {
+        restore_title()?;
+    }
     result
 }
 
-fn new_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+/// Save the current title to the stack of terminal (the xterm `XTWINOPS` extension,
+/// ignored by the terminals not supporting it) before setting, so that it can be
+/// restored on exit.
+fn set_title(title: &str) -> Result<()> {
+    let mut out = output();
+    out.write_all(b"\x1b[22;0t")
+        .context("save terminal title")?;
+    crossterm::execute!(out, terminal::SetTitle(title)).context("set terminal title")
+}
+
+fn restore_title() -> Result<()> {
+    let mut out = output();
+    out.write_all(b"\x1b[23;0t")
+        .context("restore terminal title")?;
+    out.flush().context("flush terminal")
+}
+
+/// A panic inside the TUI would leave the terminal in raw mode and alternate screen,
+/// which makes the panic message unreadable. Restore the terminal before printing it.
+fn set_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        reset_terminal();
+        hook(info);
+    }));
+}
+
+/// Restore the terminal when the process is terminated externally, like closing the
+/// terminal window (SIGHUP) or `kill` (SIGTERM). The handler runs in a thread rather
+/// than the signal context, so it is safe to write the terminal there.
+#[cfg(unix)]
+fn set_signal_handler(title: bool) -> Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGQUIT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals =
+        Signals::new([SIGTERM, SIGHUP, SIGQUIT]).context("register signal handlers")?;
+    thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            reset_terminal();
+            if title {
+                let _ = restore_title();
+            }
+            edit::cleanup();
+            // The conventional exit code of the processes terminated by signals.
+            process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
+/// Reset the terminal without the handle, it is used when the TUI is interrupted.
+fn reset_terminal() {
+    let _ = terminal::disable_raw_mode();
+    let _ = crossterm::execute!(
+        output(),
+        terminal::LeaveAlternateScreen,
+        event::DisableMouseCapture,
+        cursor::Show
+    );
+}
+
+fn new_terminal(mouse: bool) -> Result<Terminal<Backend>> {
     terminal::enable_raw_mode().context("enable terminal raw mode")?;
-    let mut stdout = std::io::stdout();
-    crossterm::execute!(
-        stdout,
-        terminal::EnterAlternateScreen,
-        event::EnableMouseCapture
-    )
-    .context("execute terminal commands for stdout")?;
+    let mut stdout = output();
+    crossterm::execute!(stdout, terminal::EnterAlternateScreen)
+        .context("execute terminal commands for stdout")?;
+    if mouse {
+        crossterm::execute!(stdout, event::EnableMouseCapture).context("enable mouse capture")?;
+    }
 
     let terminal = Terminal::new(CrosstermBackend::new(stdout)).context("init terminal")?;
     Ok(terminal)
 }
 
-fn restore(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+fn restore(terminal: &mut Terminal<Backend>, mouse: bool) -> Result<()> {
     terminal::disable_raw_mode().context("disable terminal raw mode")?;
-    crossterm::execute!(
-        terminal.backend_mut(),
-        terminal::LeaveAlternateScreen,
-        event::DisableMouseCapture
-    )
-    .context("execute terminal commands")?;
+    crossterm::execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)
+        .context("execute terminal commands")?;
+    if mouse {
+        crossterm::execute!(terminal.backend_mut(), event::DisableMouseCapture)
+            .context("disable mouse capture")?;
+    }
     terminal.show_cursor().context("restore terminal cursor")?;
     Ok(())
 }
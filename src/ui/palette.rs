@@ -0,0 +1,307 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Alignment, Margin, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::keys::Action;
+use crate::config::Config;
+use crate::ui::app::ScrollDirection;
+use crate::ui::input::Input;
+use crate::ui::popup;
+
+pub(super) enum PaletteResult {
+    Update,
+    Skip,
+    Close,
+    Execute(Action),
+    /// Select the item with the keys in the tree overview.
+    Jump(Vec<String>),
+    /// Go back to the root at the depth of the root history.
+    Root(usize),
+}
+
+enum PaletteTarget {
+    Action(Action),
+    Jump(Vec<String>),
+    Root(usize),
+}
+
+struct PaletteEntry {
+    name: String,
+    /// Shown dimmed after the name, like the keys of the action.
+    hint: String,
+    target: PaletteTarget,
+}
+
+/// List all the actions with fuzzy search, so that the rarely used actions can be
+/// found without remembering their keys. It can also list the items to jump to, like
+/// the operations of OpenAPI.
+pub(super) struct Palette<'a> {
+    cfg: &'a Config,
+
+    title: String,
+    entries: Vec<PaletteEntry>,
+
+    input: Input,
+
+    /// The indexes of entries matched with the input, the best match comes first.
+    matched: Vec<usize>,
+    selected: usize,
+}
+
+impl<'a> Palette<'a> {
+    pub(super) fn new(cfg: &'a Config) -> Self {
+        let mut palette = Self {
+            cfg,
+            title: String::new(),
+            entries: vec![],
+            input: Input::default(),
+            matched: vec![],
+            selected: 0,
+        };
+        palette.reset();
+        palette
+    }
+
+    /// Open with all the actions.
+    pub(super) fn reset(&mut self) {
+        self.title = String::from("actions");
+        self.entries = Action::ALL
+            .iter()
+            .filter(|action| !matches!(action, Action::CommandPalette))
+            .map(|action| PaletteEntry {
+                name: action.name().to_string(),
+                hint: self.cfg.keys.get_action_keys(*action).join(", "),
+                target: PaletteTarget::Action(*action),
+            })
+            .collect();
+        self.input.clear();
+        self.update_matched();
+    }
+
+    /// Open with the items to jump to, each item is the name and the keys of it.
+    pub(super) fn open_jumps(&mut self, title: String, items: Vec<(String, Vec<String>)>) {
+        self.title = title;
+        self.entries = items
+            .into_iter()
+            .map(|(name, keys)| PaletteEntry {
+                name,
+                hint: String::new(),
+                target: PaletteTarget::Jump(keys),
+            })
+            .collect();
+        self.input.clear();
+        self.update_matched();
+    }
+
+    /// Open with the earlier roots, each root is the path and the depth of it.
+    pub(super) fn open_roots(&mut self, title: String, roots: Vec<(String, usize)>) {
+        self.title = title;
+        self.entries = roots
+            .into_iter()
+            .map(|(name, depth)| PaletteEntry {
+                name,
+                hint: format!("depth {depth}"),
+                target: PaletteTarget::Root(depth),
+            })
+            .collect();
+        self.input.clear();
+        self.update_matched();
+    }
+
+    pub(super) fn on_key(&mut self, key: KeyEvent) -> PaletteResult {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => PaletteResult::Close,
+            KeyCode::Enter => {
+                let entry = match self.matched.get(self.selected) {
+                    Some(idx) => &self.entries[*idx],
+                    None => return PaletteResult::Skip,
+                };
+                match &entry.target {
+                    PaletteTarget::Action(action) => PaletteResult::Execute(*action),
+                    PaletteTarget::Jump(keys) => PaletteResult::Jump(keys.clone()),
+                    PaletteTarget::Root(depth) => PaletteResult::Root(*depth),
+                }
+            }
+            KeyCode::Up => self.move_selected(ScrollDirection::Up),
+            KeyCode::Char('p' | 'k') if ctrl => self.move_selected(ScrollDirection::Up),
+            KeyCode::Down => self.move_selected(ScrollDirection::Down),
+            KeyCode::Char('n' | 'j') if ctrl => self.move_selected(ScrollDirection::Down),
+            _ => {
+                if !self.input.on_key(key) {
+                    return PaletteResult::Skip;
+                }
+                self.update_matched();
+                PaletteResult::Update
+            }
+        }
+    }
+
+    pub(super) fn paste(&mut self, text: &str) -> PaletteResult {
+        if !self.input.insert(text) {
+            return PaletteResult::Skip;
+        }
+        self.update_matched();
+        PaletteResult::Update
+    }
+
+    pub(super) fn on_scroll(&mut self, direction: ScrollDirection) -> PaletteResult {
+        self.move_selected(direction)
+    }
+
+    fn move_selected(&mut self, direction: ScrollDirection) -> PaletteResult {
+        let selected = match direction {
+            ScrollDirection::Up => self.selected.saturating_sub(1),
+            ScrollDirection::Down => (self.selected + 1).min(self.matched.len().saturating_sub(1)),
+        };
+        if selected == self.selected {
+            return PaletteResult::Skip;
+        }
+        self.selected = selected;
+        PaletteResult::Update
+    }
+
+    fn update_matched(&mut self) {
+        let query = self.input.text();
+        let mut matched: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| Some((fuzzy_score(query, &entry.name)?, idx)))
+            .collect();
+        // The sort is stable, entries with the same score keep the default order.
+        matched.sort_by_key(|(score, _)| *score);
+
+        self.matched = matched.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = 0;
+    }
+
+    pub(super) fn draw(&self, frame: &mut Frame) {
+        let area = popup::centered_rect(self.cfg, frame.area());
+
+        let border_color = &self.cfg.colors.focus_border;
+        let (border_style, border_set) = super::get_border_style(self.cfg, border_color, true);
+        let block = Block::new()
+            .border_set(border_set)
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title_alignment(Alignment::Center)
+            .title(self.title.as_str());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        if inner.height == 0 {
+            return;
+        }
+
+        let input_area = Rect { height: 1, ..inner };
+        self.input.draw(
+            frame,
+            input_area,
+            "> ",
+            self.cfg.colors.popup.info_text.style,
+        );
+
+        let list_area = Rect {
+            y: inner.y + 1,
+            height: inner.height - 1,
+            ..inner
+        };
+        if list_area.height == 0 {
+            return;
+        }
+
+        if self.matched.is_empty() {
+            let text = Paragraph::new("no matched item")
+                .style(Style::default().add_modifier(Modifier::DIM));
+            frame.render_widget(text, list_area);
+            return;
+        }
+
+        let height = list_area.height as usize;
+        let start = (self.selected + 1).saturating_sub(height);
+        let name_width = self
+            .matched
+            .iter()
+            .map(|idx| self.entries[*idx].name.width())
+            .max()
+            .unwrap_or_default();
+
+        let lines: Vec<_> = self
+            .matched
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .map(|(idx, entry)| {
+                let entry = &self.entries[*entry];
+                let padding = " ".repeat(name_width - entry.name.width() + 2);
+                let line = Line::from(vec![
+                    Span::raw(format!("{}{padding}", entry.name)),
+                    Span::styled(
+                        entry.hint.as_str(),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ]);
+                if idx == self.selected {
+                    line.style(self.cfg.colors.tree.selected.style)
+                } else {
+                    line
+                }
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), list_area);
+    }
+}
+
+/// Match the query as a subsequence of the name, ignoring case and whitespace. Returns
+/// `None` if not matched, otherwise the lower score means the better match: the
+/// characters skipped between the matched ones are counted, the characters skipped at
+/// a word boundary count less.
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name: Vec<char> = name.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    for ch in query.chars().filter(|ch| !ch.is_whitespace()) {
+        let ch = ch.to_ascii_lowercase();
+        let found = name[pos..]
+            .iter()
+            .position(|name_ch| name_ch.to_ascii_lowercase() == ch)?;
+        let at_boundary = pos + found == 0 || name[pos + found - 1] == '_';
+        score += if at_boundary { found.min(1) } else { found * 2 };
+        pos += found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "copy_name"), Some(0));
+        assert_eq!(fuzzy_score("copy", "copy_name"), Some(0));
+        assert_eq!(fuzzy_score("CN", "copy_name"), Some(1));
+        assert_eq!(fuzzy_score("copy name", "copy_name"), Some(1));
+        assert_eq!(fuzzy_score("nc", "copy_name"), None);
+        assert_eq!(fuzzy_score("x", "copy_name"), None);
+
+        let close = fuzzy_score("st", "sort_keys").unwrap();
+        let scattered = fuzzy_score("st", "select_first").unwrap();
+        assert!(close < scattered);
+
+        let boundary = fuzzy_score("cv", "copy_value").unwrap();
+        let inner = fuzzy_score("cv", "cave").unwrap();
+        assert!(boundary < inner);
+    }
+}
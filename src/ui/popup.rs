@@ -1,4 +1,4 @@
-use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::layout::{Alignment, Rect};
 use ratatui::text::Text;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
@@ -9,11 +9,15 @@ use crate::ui::app::ScrollDirection;
 
 #[derive(Debug, Clone, Copy)]
 pub(super) enum PopupLevel {
+    Info,
     Error,
 }
 
+const MIN_WIDTH: u16 = 30;
+const MIN_HEIGHT: u16 = 5;
+
 pub(super) struct Popup<'a> {
-    data: Option<(String, PopupLevel)>,
+    data: Option<(Text<'static>, PopupLevel)>,
 
     cfg: &'a Config,
 
@@ -29,8 +33,9 @@ impl<'a> Popup<'a> {
         }
     }
 
-    pub(super) fn set_data(&mut self, data: String, level: PopupLevel) {
-        self.data = Some((data, level));
+    /// Show the styled text, the style of level is used for the unstyled parts.
+    pub(super) fn set_text(&mut self, text: Text<'static>, level: PopupLevel) {
+        self.data = Some((text, level));
     }
 
     pub(super) fn on_key(&mut self, action: Action) -> bool {
@@ -92,47 +97,45 @@ impl<'a> Popup<'a> {
         };
 
         let border_color = &self.cfg.colors.focus_border;
-        let (border_style, border_type) = super::get_border_style(border_color, border_color, true);
+        let (border_style, border_set) = super::get_border_style(self.cfg, border_color, true);
 
         let (title, text_style) = match level {
+            PopupLevel::Info => ("info", self.cfg.colors.popup.info_text.style),
             PopupLevel::Error => ("error", self.cfg.colors.popup.error_text.style),
         };
 
         let block = Block::new()
-            .border_type(border_type)
+            .border_set(border_set)
             .borders(Borders::ALL)
             .border_style(border_style)
             .title_alignment(Alignment::Center)
             .title(title);
 
-        let text = Text::from(text.as_str());
-
-        let widget = Paragraph::new(text)
+        let widget = Paragraph::new(text.clone())
             .style(text_style)
             .block(block)
             .wrap(Wrap { trim: false })
             .scroll((self.scroll as u16, 0));
 
-        let area = Self::centered_rect(50, 50, frame.area());
+        let area = centered_rect(self.cfg, frame.area());
         frame.render_widget(Clear, area); // this clears out the background
         frame.render_widget(widget, area);
     }
+}
 
-    /// helper function to create a centered rect using up certain percentage of the
-    /// available rect `r`
-    fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-        let popup_layout = Layout::vertical([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-        Layout::horizontal([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+/// Create a centered rect using up the configured percentage of the available rect
+/// `r`, the size is clamped to the minimum so the popup is still readable in tiny
+/// terminals.
+pub(super) fn centered_rect(cfg: &Config, r: Rect) -> Rect {
+    let width = (r.width as u32 * cfg.popup.width_percent as u32 / 100) as u16;
+    let height = (r.height as u32 * cfg.popup.height_percent as u32 / 100) as u16;
+    let width = width.max(MIN_WIDTH).min(r.width);
+    let height = height.max(MIN_HEIGHT).min(r.height);
+
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
     }
 }
@@ -0,0 +1,93 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Alignment, Margin, Rect};
+use ratatui::widgets::{Block, Borders, Clear};
+use ratatui::Frame;
+
+use crate::config::Config;
+use crate::ui::input::Input;
+use crate::ui::popup;
+
+pub(super) enum PromptResult {
+    Update,
+    Skip,
+    Cancel,
+    Submit(String),
+}
+
+/// Ask the user to input a line of text.
+pub(super) struct Prompt<'a> {
+    cfg: &'a Config,
+
+    title: String,
+    input: Input,
+}
+
+impl<'a> Prompt<'a> {
+    const HEIGHT: u16 = 3;
+
+    pub(super) fn new(cfg: &'a Config) -> Self {
+        Self {
+            cfg,
+            title: String::new(),
+            input: Input::default(),
+        }
+    }
+
+    pub(super) fn open(&mut self, title: String, text: String) {
+        self.title = title;
+        self.input.set_text(text);
+    }
+
+    pub(super) fn on_key(&mut self, key: KeyEvent) -> PromptResult {
+        match key.code {
+            KeyCode::Esc => PromptResult::Cancel,
+            KeyCode::Enter => PromptResult::Submit(self.input.text().trim().to_string()),
+            _ => {
+                if self.input.on_key(key) {
+                    PromptResult::Update
+                } else {
+                    PromptResult::Skip
+                }
+            }
+        }
+    }
+
+    pub(super) fn paste(&mut self, text: &str) -> PromptResult {
+        if self.input.insert(text) {
+            PromptResult::Update
+        } else {
+            PromptResult::Skip
+        }
+    }
+
+    pub(super) fn draw(&self, frame: &mut Frame) {
+        let frame_area = frame.area();
+        let width = popup::centered_rect(self.cfg, frame_area).width;
+        let height = Self::HEIGHT.min(frame_area.height);
+        let area = Rect {
+            x: frame_area.x + (frame_area.width - width) / 2,
+            y: frame_area.y + (frame_area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let border_color = &self.cfg.colors.focus_border;
+        let (border_style, border_set) = super::get_border_style(self.cfg, border_color, true);
+        let block = Block::new()
+            .border_set(border_set)
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title_alignment(Alignment::Center)
+            .title(self.title.as_str());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        self.input
+            .draw(frame, inner, "> ", self.cfg.colors.popup.info_text.style);
+    }
+}
@@ -1,71 +1,393 @@
+use std::mem;
 use std::rc::Rc;
 
-use ratatui::layout::{Alignment, Position, Rect};
+use anyhow::{Context, Result};
+use ratatui::layout::{Alignment, Margin, Position, Rect};
+use ratatui::style::Style;
 use ratatui::widgets::{Block, Borders, Scrollbar, ScrollbarOrientation};
 use ratatui::Frame;
 use serde_json::Value;
 use tui_tree_widget::Tree as TreeWidget;
-use tui_tree_widget::TreeState;
+use tui_tree_widget::{TreeItem, TreeState};
+use unicode_width::UnicodeWidthStr;
 
 use crate::config::keys::Action;
 use crate::config::Config;
 use crate::parse::Parser;
-use crate::tree::{ItemValue, Tree};
+use crate::state::Notes;
+use crate::tree::{self, ItemValue, Tree};
 use crate::ui::app::ScrollDirection;
 
 pub(super) struct TreeOverview<'a> {
     cfg: &'a Config,
-    state: Option<TreeState<String>>,
-    tree: Option<Tree<'a>>,
+    state: TreeState<String>,
+    tree: Tree<'a>,
     last_switches: Vec<(Tree<'a>, TreeState<String>)>,
     root_switch: Option<(Tree<'a>, TreeState<String>)>,
     root_identifies: Vec<String>,
+    mark_differences: bool,
 }
 
 impl<'a> TreeOverview<'a> {
     pub(super) fn new(cfg: &'a Config, tree: Tree<'a>) -> Self {
+        let mut state = TreeState::default();
+        if tree.root_scalar {
+            // There is nothing to navigate, show the value in the data block directly.
+            state.select(vec![String::from("root")]);
+        }
         Self {
             cfg,
-            state: Some(TreeState::default()),
-            tree: Some(tree),
+            state,
+            tree,
             last_switches: vec![],
             root_switch: None,
             root_identifies: vec![],
+            mark_differences: false,
+        }
+    }
+
+    /// Replace the tree with a new version of the same document, like after reloading.
+    /// The roots changed into are found again in the new tree and the states are kept,
+    /// the roots that no longer exist are dropped, like resetting to the last one found.
+    pub(super) fn reload_tree(&mut self, tree: Tree<'a>) -> Result<()> {
+        let mut trees = vec![tree];
+        for id in self.root_identifies.iter() {
+            let parent = &trees[trees.len() - 1];
+            let value = match parent.get_value(id) {
+                Some(item) if matches!(item.value, Value::Array(_) | Value::Object(_)) => {
+                    item.value.clone()
+                }
+                _ => break,
+            };
+            let subtree = parent.subtree(id, value)?;
+            trees.push(subtree);
+        }
+        self.reset_root(trees.len() - 1);
+
+        let slots = self
+            .root_switch
+            .iter_mut()
+            .chain(self.last_switches.iter_mut())
+            .map(|(tree, _)| tree)
+            .chain([&mut self.tree]);
+        for (slot, tree) in slots.zip(trees) {
+            *slot = tree;
         }
+        Ok(())
+    }
+
+    /// Replace the tree with a new document, like a subtree of current one. Unlike
+    /// [`TreeOverview::reload_tree`], nothing of the old tree is kept.
+    pub(super) fn open_tree(&mut self, tree: Tree<'a>) {
+        self.state = TreeState::default();
+        self.last_switches.clear();
+        self.root_switch = None;
+        self.root_identifies.clear();
+        self.tree = tree;
     }
 
     pub(super) fn get_selected(&self) -> Option<String> {
-        let selected = self.state().selected();
+        let selected = self.state.selected();
+        if selected.is_empty() {
+            return None;
+        }
+        Some(tree::join_identify(selected))
+    }
+
+    /// Get the approximate memory usage of all trees, including the root switches.
+    pub(super) fn get_memory(&self) -> usize {
+        let switches_memory: usize = self
+            .root_switch
+            .iter()
+            .chain(self.last_switches.iter())
+            .map(|(tree, _)| tree.memory)
+            .sum();
+        self.tree.memory + switches_memory
+    }
+
+    pub(super) fn is_sort_keys(&self) -> bool {
+        self.tree.sort_keys
+    }
+
+    /// Toggle the key order of all trees, including the root switches. The states are
+    /// kept since the identifiers are not changed by sorting.
+    pub(super) fn toggle_sort_keys(&mut self) -> Result<()> {
+        self.rebuild(!self.tree.sort_keys, self.tree.mask)
+    }
+
+    pub(super) fn get_masked_value(&self, id: &str, value: &Value) -> Option<Value> {
+        self.tree.get_masked_value(id, value)
+    }
+
+    pub(super) fn mask_value_at(&self, id: &str, value: &Value) -> Option<Value> {
+        self.tree.mask_value_at(id, value)
+    }
+
+    pub(super) fn is_masked(&self) -> bool {
+        self.tree.mask
+    }
+
+    /// Toggle masking the sensitive values of all trees, like [`Self::toggle_sort_keys`].
+    pub(super) fn toggle_mask(&mut self) -> Result<()> {
+        self.rebuild(self.tree.sort_keys, !self.tree.mask)
+    }
+
+    fn rebuild(&mut self, sort_keys: bool, mask: bool) -> Result<()> {
+        let trees = self
+            .root_switch
+            .iter_mut()
+            .chain(self.last_switches.iter_mut())
+            .map(|(tree, _)| tree)
+            .chain([&mut self.tree]);
+        for tree in trees {
+            *tree = tree.rebuild(sort_keys, mask)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the differences are marked after toggling.
+    pub(super) fn toggle_mark_differences(&mut self) -> bool {
+        self.mark_differences = !self.mark_differences;
+        self.mark_differences
+    }
+
+    /// The width to show the visible (expanded) items without cutting, including the
+    /// borders and scrollbar.
+    pub(super) fn get_content_width(&self) -> usize {
+        let items = self.state.flatten(&self.tree.items);
+        let width = items
+            .iter()
+            .filter_map(|flattened| {
+                let item = self
+                    .tree
+                    .get_value(&tree::join_identify(&flattened.identifier))?;
+                Some(self.text_offset(&flattened.identifier, &item) + item.text_width)
+            })
+            .max()
+            .unwrap_or_default();
+        width + 3
+    }
+
+    /// Render the visible items as indented plain text, including the expansion state,
+    /// so that it can be pasted as a readable outline.
+    pub(super) fn get_outline(&self) -> Option<String> {
+        let items = self.state.flatten(&self.tree.items);
+        if items.is_empty() {
+            return None;
+        }
+
+        let mut outline = String::new();
+        for flattened in items {
+            let value = match self
+                .tree
+                .get_value(&tree::join_identify(&flattened.identifier))
+            {
+                Some(value) => value,
+                None => continue,
+            };
+            let symbol = self.node_symbol(&flattened.identifier, &value);
+
+            outline.push_str(&"  ".repeat(flattened.depth()));
+            outline.push_str(symbol);
+            outline.push_str(&value.plain_text(self.cfg));
+            outline.push('\n');
+        }
+        Some(outline)
+    }
+
+    /// Get the path of the selected item from the root of the document, it is not
+    /// changed by the `change_root` action.
+    pub(super) fn get_selected_path(&self) -> Option<String> {
+        let selected = self.state.selected();
         if selected.is_empty() {
             return None;
         }
-        Some(selected.join("/"))
+        Some(self.get_full_path(selected))
+    }
+
+    /// The keys of the full path for display, the array indexes are formatted by
+    /// [`crate::config::Tree::index_name`]. The `keys` are escaped.
+    pub(super) fn display_keys(&self, keys: &[&str]) -> Vec<String> {
+        let cfg = &self.cfg.tree;
+        if cfg.index_base == 0 && !cfg.index_padding {
+            return keys.iter().map(|key| key.to_string()).collect();
+        }
+        let root = match self.root_switch.as_ref() {
+            Some((tree, _)) => tree,
+            None => &self.tree,
+        };
+
+        let mut display = Vec::with_capacity(keys.len());
+        for (depth, key) in keys.iter().enumerate() {
+            let len = if depth == 0 {
+                root.root_array.then_some(root.items.len())
+            } else {
+                let parent = keys[..depth].join("/");
+                root.get_value(&parent)
+                    .and_then(|item| item.value.as_array().map(Vec::len))
+            };
+            let display_key = match (len, key.parse::<usize>()) {
+                (Some(len), Ok(idx)) => cfg.index_name(idx, len),
+                _ => key.to_string(),
+            };
+            display.push(display_key);
+        }
+        display
+    }
+
+    /// The root identifies and the selected identify for display in the footer, see
+    /// [`Self::display_keys`]. The identify is split into keys.
+    pub(super) fn get_display_identify(&self) -> (Vec<String>, Vec<String>) {
+        let mut keys: Vec<&str> = vec![];
+        let mut root_lens = Vec::with_capacity(self.root_identifies.len());
+        for root in self.root_identifies.iter() {
+            let root_keys: Vec<_> = root.split('/').collect();
+            root_lens.push(root_keys.len());
+            keys.extend(root_keys);
+        }
+        let selected: Vec<_> = self
+            .state
+            .selected()
+            .iter()
+            .map(|key| tree::escape_key(key))
+            .collect();
+        keys.extend(selected.iter().map(|key| key.as_ref()));
+
+        let mut display = self.display_keys(&keys).into_iter();
+        let roots = root_lens
+            .into_iter()
+            .map(|len| display.by_ref().take(len).collect::<Vec<_>>().join("/"))
+            .collect();
+        (roots, display.collect())
+    }
+
+    fn get_full_path(&self, id: &[String]) -> String {
+        let mut parts: Vec<&str> = self.root_identifies.iter().map(String::as_str).collect();
+        let id = tree::join_identify(id);
+        if !id.is_empty() {
+            parts.push(&id);
+        }
+        parts.join("/")
+    }
+
+    /// Visit the items of the selected array, or the root array if nothing is selected.
+    /// The items are visited with their identifies. Returns the path of the array, `None`
+    /// means there is no array to visit.
+    pub(super) fn visit_array<F>(&self, mut visit: F) -> Option<String>
+    where
+        F: FnMut(&str, &Value),
+    {
+        if let Some(id) = self.get_selected() {
+            let item = self.tree.get_value(&id)?;
+            let arr = item.value.as_array()?;
+            for (idx, value) in arr.iter().enumerate() {
+                visit(&format!("{id}/{idx}"), value);
+            }
+            return self.get_selected_path();
+        }
+
+        if !self.tree.root_array {
+            return None;
+        }
+        for item in self.tree.items.iter() {
+            let id = tree::escape_key(item.identifier());
+            if let Some(item) = self.tree.get_value(&id) {
+                visit(&id, &item.value);
+            }
+        }
+        if self.root_identifies.is_empty() {
+            return Some(String::from("root"));
+        }
+        Some(self.root_identifies.join("/"))
     }
 
+    #[cfg(test)]
     pub(super) fn get_root_identifies(&self) -> &[String] {
         self.root_identifies.as_ref()
     }
 
+    /// The roots before the current one, the latest first. Each root is the path and
+    /// the depth of it, the original root has depth 0.
+    pub(super) fn get_root_history(&self) -> Vec<(String, usize)> {
+        (0..self.root_identifies.len())
+            .rev()
+            .map(|depth| {
+                let path = if depth == 0 {
+                    String::from("root")
+                } else {
+                    format!("/{}", self.root_identifies[..depth].join("/"))
+                };
+                (path, depth)
+            })
+            .collect()
+    }
+
+    /// Go back to the root at the depth of the history directly, like pressing `reset`
+    /// several times.
+    pub(super) fn reset_root(&mut self, depth: usize) -> bool {
+        if depth >= self.root_identifies.len() {
+            return false;
+        }
+        while self.root_identifies.len() > depth {
+            self.reset();
+        }
+        true
+    }
+
     pub(super) fn get_value(&self, id: &str) -> Option<Rc<ItemValue>> {
-        self.tree().get_value(id)
+        self.tree.get_value(id)
+    }
+
+    /// Get the value by the full path, which is the identify in the original root tree.
+    pub(super) fn get_root_value(&self, path: &str) -> Option<Rc<ItemValue>> {
+        match self.root_switch.as_ref() {
+            Some((tree, _)) => tree.get_value(path),
+            None => self.tree.get_value(path),
+        }
     }
 
     pub(super) fn get_parser(&self) -> Rc<Box<dyn Parser>> {
-        self.tree().get_parser()
+        self.tree.get_parser()
+    }
+
+    pub(super) fn value_to_string(&self, id: &str, value: &Value) -> String {
+        self.tree.value_to_string(id, value)
+    }
+
+    /// Build the tree of the item as a new document, see [`Tree::subtree`].
+    pub(super) fn build_subtree(&self, id: &str, value: Value) -> Result<Tree<'a>> {
+        self.tree.subtree(id, value)
     }
 
     pub(super) fn on_key(&mut self, action: Action) -> bool {
         match action {
-            Action::MoveUp => self.state_mut().key_up(),
-            Action::MoveDown => self.state_mut().key_down(),
-            Action::SelectFocus => self.state_mut().toggle_selected(),
+            Action::MoveUp => self.state.key_up(),
+            Action::MoveDown => self.state.key_down(),
+            // Open and close without toggling, so that the items can still be opened
+            // when Enter is taken, like the picker mode.
+            Action::MoveLeft => self.state.key_left(),
+            Action::MoveRight => self.state.key_right(),
+            Action::SelectFocus => self.state.toggle_selected(),
             Action::SelectParent => self.select_parent(),
             Action::CloseParent => self.close_parent(),
-            Action::PageUp => self.state_mut().scroll_up(3),
-            Action::PageDown => self.state_mut().scroll_down(3),
-            Action::SelectFirst => self.state_mut().select_first(),
-            Action::SelectLast => self.state_mut().select_last(),
+            Action::PageUp => self.state.scroll_up(3),
+            Action::PageDown => self.state.scroll_down(3),
+            Action::SelectFirst => self.state.select_first(),
+            Action::SelectLast => self.state.select_last(),
             Action::ChangeRoot => self.change_root(),
+            Action::ExpandLevels => self.expand_levels(),
+            Action::NextSibling => {
+                self.select_sibling(|idx, len| (idx + 1 < len).then_some(idx + 1))
+            }
+            Action::PrevSibling => self.select_sibling(|idx, _| idx.checked_sub(1)),
+            Action::FirstSibling => self.select_sibling(|_, _| Some(0)),
+            Action::LastSibling => self.select_sibling(|_, len| len.checked_sub(1)),
+            Action::NextArray => self.select_kind(NodeKind::Array, true),
+            Action::PrevArray => self.select_kind(NodeKind::Array, false),
+            Action::NextObject => self.select_kind(NodeKind::Object, true),
+            Action::PrevObject => self.select_kind(NodeKind::Object, false),
+            Action::NextLeaf => self.select_kind(NodeKind::Leaf, true),
+            Action::PrevLeaf => self.select_kind(NodeKind::Leaf, false),
             Action::Reset => self.reset(),
             _ => false,
         }
@@ -77,7 +399,7 @@ impl<'a> TreeOverview<'a> {
             None => return false,
         };
 
-        let value = match self.tree().get_value(id.as_str()) {
+        let value = match self.tree.get_value(id.as_str()) {
             Some(item) => {
                 if !matches!(item.value, Value::Array(_) | Value::Object(_)) {
                     // We don't allow to change root to non-expandable value
@@ -88,10 +410,15 @@ impl<'a> TreeOverview<'a> {
             None => return false,
         };
 
-        let new_tree = Tree::from_value(self.cfg, value, self.tree().get_parser());
+        // The new tree is a part of current tree, the nodes limitation will not be
+        // exceeded, so it is safe to ignore the error.
+        let new_tree = match self.tree.subtree(&id, value) {
+            Ok(tree) => tree,
+            Err(_) => return false,
+        };
 
-        let current_tree = self.tree.take().unwrap();
-        let current_state = self.state.take().unwrap();
+        let current_tree = mem::replace(&mut self.tree, new_tree);
+        let current_state = mem::take(&mut self.state);
         let switch = (current_tree, current_state);
 
         if self.root_switch.is_none() {
@@ -101,8 +428,6 @@ impl<'a> TreeOverview<'a> {
         }
 
         self.root_identifies.push(id);
-        self.state = Some(TreeState::default());
-        self.tree = Some(new_tree);
 
         true
     }
@@ -116,37 +441,137 @@ impl<'a> TreeOverview<'a> {
                     if self.get_selected().is_none() {
                         return false;
                     }
-                    self.state = Some(TreeState::default());
+                    self.state = TreeState::default();
                     return true;
                 }
             },
         };
 
         self.root_identifies.pop();
-        self.tree = Some(reset_tree);
-        self.state = Some(reset_state);
+        self.tree = reset_tree;
+        self.state = reset_state;
 
         true
     }
 
+    /// Open the selected item and its descendants, `tree.expand_levels` levels in total.
+    /// The items are resolved through the tree structure, so that it works for the
+    /// nested items as well.
+    fn expand_levels(&mut self) -> bool {
+        let selected = self.state.selected().to_vec();
+        let item = match find_item(&self.tree.items, &selected) {
+            Some(item) => item,
+            None => return false,
+        };
+
+        let mut changed = false;
+        let mut stack = vec![(selected, item, 1)];
+        while let Some((id, item, level)) = stack.pop() {
+            if item.children().is_empty() {
+                continue;
+            }
+            changed |= self.state.open(id.clone());
+            if level >= self.cfg.tree.expand_levels {
+                continue;
+            }
+            for child in item.children() {
+                let mut child_id = id.clone();
+                child_id.push(child.identifier().clone());
+                stack.push((child_id, child, level + 1));
+            }
+        }
+        changed
+    }
+
+    /// Select the sibling of the selected item, `index` gets the index of the sibling
+    /// from the index of the selected item and the number of siblings.
+    fn select_sibling<F>(&mut self, index: F) -> bool
+    where
+        F: FnOnce(usize, usize) -> Option<usize>,
+    {
+        let selected = self.state.selected();
+        let Some((key, parent)) = selected.split_last() else {
+            return false;
+        };
+        let siblings = if parent.is_empty() {
+            self.tree.items.as_slice()
+        } else {
+            match find_item(&self.tree.items, parent) {
+                Some(item) => item.children(),
+                None => return false,
+            }
+        };
+        let Some(idx) = siblings.iter().position(|item| item.identifier() == key) else {
+            return false;
+        };
+        let Some(sibling) = index(idx, siblings.len()).and_then(|idx| siblings.get(idx)) else {
+            return false;
+        };
+
+        let mut id = parent.to_vec();
+        id.push(sibling.identifier().clone());
+        self.state.select(id)
+    }
+
+    /// Select the next (or previous) item of the kind in document order, the items in
+    /// the closed ones are included, their ancestors are opened.
+    fn select_kind(&mut self, kind: NodeKind, forward: bool) -> bool {
+        let mut ids = vec![];
+        let mut stack: Vec<_> = self
+            .tree
+            .items
+            .iter()
+            .rev()
+            .map(|item| (vec![item.identifier().clone()], item))
+            .collect();
+        while let Some((id, item)) = stack.pop() {
+            for child in item.children().iter().rev() {
+                let mut child_id = id.clone();
+                child_id.push(child.identifier().clone());
+                stack.push((child_id, child));
+            }
+            ids.push(id);
+        }
+
+        let selected = self.state.selected();
+        let position = ids.iter().position(|id| id.as_slice() == selected);
+        let mut candidates: Box<dyn Iterator<Item = &Vec<String>>> = match (position, forward) {
+            (Some(pos), true) => Box::new(ids[pos + 1..].iter()),
+            (Some(pos), false) => Box::new(ids[..pos].iter().rev()),
+            (None, true) => Box::new(ids.iter()),
+            (None, false) => Box::new(ids.iter().rev()),
+        };
+        let found = candidates
+            .find(|id| {
+                self.tree
+                    .get_value(&tree::join_identify(id))
+                    .is_some_and(|item| kind.matches(&item.value))
+            })
+            .cloned();
+        match found {
+            Some(id) => self.select_keys(id),
+            None => false,
+        }
+    }
+
     fn close_parent(&mut self) -> bool {
         if !self.select_parent() {
             return false;
         }
 
-        self.state_mut().toggle_selected()
+        self.state.toggle_selected()
     }
 
     fn select_parent(&mut self) -> bool {
         if let Some(parent) = self.get_selected_parent() {
-            self.state_mut().select(parent);
+            self.state.select(parent);
             return true;
         }
         false
     }
 
     fn get_selected_parent(&self) -> Option<Vec<String>> {
-        let selected = self.state().selected();
+        let selected = self.state.selected();
         if selected.len() <= 1 {
             return None;
         }
@@ -156,57 +581,379 @@ impl<'a> TreeOverview<'a> {
         Some(parent)
     }
 
-    pub(super) fn on_click(&mut self, column: u16, row: u16) {
-        let changed = self.state_mut().click_at(Position { x: column, y: row });
-        if !changed {
-            self.state_mut().toggle_selected();
+    /// Select the clicked item, double click toggles it.
+    pub(super) fn on_click(&mut self, column: u16, row: u16, double_click: bool) {
+        let changed = self.state.click_at(Position { x: column, y: row });
+        if !changed && double_click {
+            self.state.toggle_selected();
+        }
+    }
+
+    /// Select the item with the keys, its ancestors are opened.
+    pub(super) fn select_keys(&mut self, keys: Vec<String>) -> bool {
+        if self.tree.get_value(&tree::join_identify(&keys)).is_none() {
+            return false;
+        }
+        for depth in 1..keys.len() {
+            self.state.open(keys[..depth].to_vec());
         }
+        self.state.select(keys);
+        true
+    }
+
+    /// Select the ancestor of the selected item at `depth`, `1` means the top level.
+    pub(super) fn select_ancestor(&mut self, depth: usize) -> bool {
+        let selected = self.state.selected();
+        if depth == 0 || depth >= selected.len() {
+            return false;
+        }
+        let ancestor = selected[..depth].to_vec();
+        self.state.select(ancestor)
     }
 
     pub(super) fn on_scroll(&mut self, direction: ScrollDirection) -> bool {
         match direction {
-            ScrollDirection::Up => self.state_mut().scroll_up(1),
-            ScrollDirection::Down => self.state_mut().scroll_down(1),
+            ScrollDirection::Up => self.state.scroll_up(1),
+            ScrollDirection::Down => self.state.scroll_down(1),
         }
     }
 
-    pub(super) fn draw(&mut self, frame: &mut Frame, area: Rect, focus: bool) {
-        let (border_style, border_type) = super::get_border_style(
-            &self.cfg.colors.focus_border,
-            &self.cfg.colors.tree.border,
-            focus,
-        );
+    pub(super) fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        focus: bool,
+        notes: &Notes,
+    ) -> Result<()> {
+        let (border_style, border_set) =
+            super::get_border_style(self.cfg, &self.cfg.colors.tree.border, focus);
 
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .thumb_symbol(super::get_glyph(self.cfg, "█", "#"))
             .begin_symbol(None)
             .end_symbol(None)
             .track_symbol(None);
         let block = Block::new()
-            .border_type(border_type)
+            .border_set(border_set)
             .borders(Borders::ALL)
             .border_style(border_style)
             .title_alignment(Alignment::Center)
-            .title("Tree Overview");
-        let mut state = self.state.take().unwrap();
-        let widget = TreeWidget::new(&self.tree().items)
-            .unwrap()
+            .title(super::get_title(self.cfg, "Tree Overview", focus));
+        let tree_cfg = &self.cfg.tree;
+        let mut widget = TreeWidget::new(&self.tree.items)
+            .context("build tree widget")?
             .experimental_scrollbar(Some(scrollbar))
-            .highlight_style(self.cfg.colors.tree.selected.style)
+            .highlight_symbol(&tree_cfg.selected_symbol)
+            .node_closed_symbol(&tree_cfg.node_closed_symbol)
+            .node_open_symbol(&tree_cfg.node_open_symbol)
+            .node_no_children_symbol(&tree_cfg.node_no_children_symbol)
             .block(block);
+        if tree_cfg.selected_highlight {
+            widget = widget.highlight_style(self.cfg.colors.tree.selected.style);
+        }
 
-        frame.render_stateful_widget(widget, area, &mut state);
-        self.state = Some(state);
+        frame.render_stateful_widget(widget, area, &mut self.state);
+        let shifts = if self.cfg.tree.align_columns {
+            self.draw_alignment(frame, area)
+        } else {
+            vec![]
+        };
+        self.draw_ellipsis(frame, area, &shifts);
+        if self.mark_differences {
+            self.draw_differences(frame, area);
+        }
+        self.draw_notes(frame, area, notes, &shifts);
+        Ok(())
     }
 
-    fn tree(&self) -> &Tree<'a> {
-        self.tree.as_ref().unwrap()
+    /// The symbol drawn by the tree widget before the item text.
+    fn node_symbol(&self, id: &[String], item: &ItemValue) -> &str {
+        let has_children = match &item.value {
+            Value::Array(arr) => !arr.is_empty(),
+            Value::Object(obj) => !obj.is_empty(),
+            _ => false,
+        };
+        if !has_children {
+            &self.cfg.tree.node_no_children_symbol
+        } else if self.state.opened().contains(id) {
+            &self.cfg.tree.node_open_symbol
+        } else {
+            &self.cfg.tree.node_closed_symbol
+        }
     }
 
-    fn state(&self) -> &TreeState<String> {
-        self.state.as_ref().unwrap()
+    /// The columns before the item text in the tree widget: the selected symbol (its
+    /// width is reserved for all items when there is a selection), the indentation of
+    /// two columns for each level and the node symbol.
+    fn text_offset(&self, id: &[String], item: &ItemValue) -> usize {
+        let selected_width = if self.state.selected().is_empty() {
+            0
+        } else {
+            self.cfg.tree.selected_symbol.width()
+        };
+        selected_width + id.len().saturating_sub(1) * 2 + self.node_symbol(id, item).width()
     }
 
-    fn state_mut(&mut self) -> &mut TreeState<String> {
-        self.state.as_mut().unwrap()
+    /// Align the type labels and value descriptions of the visible items to the same
+    /// columns, by moving the cells after the names. The names wider than half of the
+    /// tree overview are not aligned, so that a long name doesn't push the others out.
+    /// Returns the columns each row is moved by, indexed by row.
+    fn draw_alignment(&self, frame: &mut Frame, area: Rect) -> Vec<usize> {
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+
+        let mut rows = vec![];
+        let mut last_id: Option<&[String]> = None;
+        for y in inner.top()..inner.bottom() {
+            let id = match self.state.rendered_at(Position { x: inner.x, y }) {
+                Some(id) => id,
+                None => break,
+            };
+            if last_id == Some(id) {
+                break;
+            }
+            last_id = Some(id);
+
+            let (offset, columns) = match self.tree.get_value(&tree::join_identify(id)) {
+                Some(item) => (self.text_offset(id, &item), item.columns),
+                None => continue,
+            };
+            let name_end = offset + columns.name_offset + columns.name_width;
+            rows.push((y, name_end, columns.type_width));
+        }
+
+        let max_name_end = inner.width as usize / 2;
+        let name_end = rows
+            .iter()
+            .map(|(_, name_end, _)| *name_end)
+            .filter(|name_end| *name_end <= max_name_end)
+            .max()
+            .unwrap_or_default();
+        let type_width = rows
+            .iter()
+            .map(|(_, _, type_width)| *type_width)
+            .max()
+            .unwrap_or_default();
+
+        let buffer = frame.buffer_mut();
+        let mut shifts = vec![0; inner.height as usize];
+        for (y, row_name_end, row_type_width) in rows {
+            if row_name_end > name_end {
+                continue;
+            }
+            let line: Vec<_> = (inner.left()..inner.right())
+                .map(|x| buffer[(x, y)].clone())
+                .collect();
+            // The space after the name, use it to pad so that the padding has the same
+            // style, like the highlight of the selected item.
+            let padding = match line.get(row_name_end) {
+                Some(cell) => cell.clone(),
+                None => continue,
+            };
+
+            let mut aligned = Vec::with_capacity(line.len() * 2);
+            aligned.extend_from_slice(&line[..row_name_end]);
+            let pad = name_end - row_name_end + 1;
+            aligned.extend(std::iter::repeat_n(padding.clone(), pad));
+            let mut rest = row_name_end + 1;
+            if type_width > 0 {
+                let type_end = (rest + row_type_width).min(line.len());
+                aligned.extend_from_slice(&line[rest..type_end]);
+                let pad = type_width - row_type_width + 1;
+                aligned.extend(std::iter::repeat_n(padding, pad));
+                rest = (type_end + 1).min(line.len());
+            }
+            aligned.extend_from_slice(&line[rest..]);
+
+            for (x, cell) in (inner.left()..inner.right()).zip(aligned) {
+                buffer[(x, y)] = cell;
+            }
+            shifts[(y - inner.y) as usize] = name_end - row_name_end + type_width - row_type_width;
+        }
+        shifts
+    }
+
+    /// Highlight the names of the array item fields whose values differ across the
+    /// items.
+    fn draw_differences(&self, frame: &mut Frame, area: Rect) {
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+
+        let style = self.cfg.colors.tree.difference.style;
+        let buffer = frame.buffer_mut();
+        let mut last_id: Option<&[String]> = None;
+        for y in inner.top()..inner.bottom() {
+            let id = match self.state.rendered_at(Position { x: inner.x, y }) {
+                Some(id) => id,
+                None => break,
+            };
+            if last_id == Some(id) {
+                break;
+            }
+            last_id = Some(id);
+
+            let path = tree::join_identify(id);
+            if !self.tree.is_difference(&path) {
+                continue;
+            }
+            let (offset, columns) = match self.tree.get_value(&path) {
+                Some(item) => (self.text_offset(id, &item), item.columns),
+                None => continue,
+            };
+            let start = inner.x as usize + offset + columns.name_offset;
+            let end = (start + columns.name_width).min(inner.right() as usize);
+            for x in start..end {
+                buffer[(x as u16, y)].set_style(style);
+            }
+        }
+    }
+
+    /// Draw the note symbol after the items with notes, if the item is too wide, the
+    /// symbol is drawn before the ellipsis.
+    fn draw_notes(&self, frame: &mut Frame, area: Rect, notes: &Notes, shifts: &[usize]) {
+        let symbol = self.cfg.tree.note_symbol.as_str();
+        let symbol_width = symbol.width() as u16;
+        if symbol_width == 0 || notes.is_empty() {
+            return;
+        }
+
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let ellipsis_width = self.cfg.tree.ellipsis.width() as u16;
+        if inner.width <= symbol_width + ellipsis_width {
+            return;
+        }
+
+        let buffer = frame.buffer_mut();
+        let mut last_id: Option<&[String]> = None;
+        for y in inner.top()..inner.bottom() {
+            let id = match self.state.rendered_at(Position { x: inner.x, y }) {
+                Some(id) => id,
+                None => break,
+            };
+            if last_id == Some(id) {
+                break;
+            }
+            last_id = Some(id);
+
+            if !notes.contains(&self.get_full_path(id)) {
+                continue;
+            }
+            let item = match self.tree.get_value(&tree::join_identify(id)) {
+                Some(item) => item,
+                None => continue,
+            };
+
+            let shift = shifts
+                .get((y - inner.y) as usize)
+                .copied()
+                .unwrap_or_default();
+            let text_end = inner.x as usize + self.text_offset(id, &item) + item.text_width + shift;
+            let max_x = inner.right() - ellipsis_width - symbol_width;
+            let x = (text_end + 1).min(max_x as usize) as u16;
+            if x > inner.x {
+                let cell = &mut buffer[(x - 1, y)];
+                if cell.symbol().width() > 1 {
+                    cell.set_symbol(" ");
+                }
+            }
+            let style = self.cfg.colors.tree.note.style;
+            buffer.set_stringn(x, y, symbol, symbol_width as usize, style);
+        }
+    }
+
+    /// The tree widget cuts the items wider than the area silently, draw an ellipsis at
+    /// the end of them so that the user knows there is more content. This is done after
+    /// rendering, so it always follows the current size of the tree overview.
+    fn draw_ellipsis(&self, frame: &mut Frame, area: Rect, shifts: &[usize]) {
+        let ellipsis = self.cfg.tree.ellipsis.as_str();
+        let ellipsis_width = ellipsis.width() as u16;
+        if ellipsis_width == 0 {
+            return;
+        }
+
+        let inner = area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        if inner.width <= ellipsis_width {
+            return;
+        }
+
+        let buffer = frame.buffer_mut();
+        let mut last_id: Option<&[String]> = None;
+        for y in inner.top()..inner.bottom() {
+            let id = match self.state.rendered_at(Position { x: inner.x, y }) {
+                Some(id) => id,
+                None => break,
+            };
+            // Every item takes one line, the same identifier means that we have gone
+            // past the last rendered item.
+            if last_id == Some(id) {
+                break;
+            }
+            last_id = Some(id);
+
+            let item = match self.tree.get_value(&tree::join_identify(id)) {
+                Some(item) => item,
+                None => continue,
+            };
+
+            let text_x = inner.x as usize + self.text_offset(id, &item);
+            let shift = shifts
+                .get((y - inner.y) as usize)
+                .copied()
+                .unwrap_or_default();
+            if text_x + item.text_width + shift <= inner.right() as usize {
+                continue;
+            }
+
+            let x = inner.right() - ellipsis_width;
+            if x > inner.x {
+                // Don't leave half of a wide character before the ellipsis.
+                let cell = &mut buffer[(x - 1, y)];
+                if cell.symbol().width() > 1 {
+                    cell.set_symbol(" ");
+                }
+            }
+            buffer.set_stringn(x, y, ellipsis, ellipsis_width as usize, Style::default());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NodeKind {
+    Array,
+    Object,
+    /// The scalars except null.
+    Leaf,
+}
+
+impl NodeKind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+            Self::Leaf => !matches!(value, Value::Null | Value::Array(_) | Value::Object(_)),
+        }
+    }
+}
+
+/// Find the item by its identifier, the keys from the top level item.
+fn find_item<'b>(
+    items: &'b [TreeItem<'static, String>],
+    keys: &[String],
+) -> Option<&'b TreeItem<'static, String>> {
+    let (first, rest) = keys.split_first()?;
+    let item = items.iter().find(|item| item.identifier() == first)?;
+    if rest.is_empty() {
+        return Some(item);
     }
+    find_item(item.children(), rest)
 }